@@ -0,0 +1,247 @@
+use rusty_fractals::formulas::Registry;
+use rusty_fractals::progress::ProgressBar;
+use rusty_fractals::{distributed, dzi, formulas, plugin, tile_server};
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+// loaded if it exists, other directories must exist
+const PLUGINS_DIR: &str = "plugins";
+const SERVE_PORT: u16 = 8080;
+const TILE_CACHE_DIR: &str = "tile_cache";
+const COORDINATOR_PORT: u16 = 8090;
+const USAGE: &str = "usage: fractal_cli [--plugins <dir>] [--size <width>x<height>] [--tile <px>] \
+    [<formula> <image.png | image.dzi> | serve [<port> [<cache dir>]] \
+    | coordinate <formula> <frames> <frames dir> [<port>] | work <formula> [<port>]]";
+
+/**
+ * Renders built-in and plugin formulas to image file, without window
+ * Without formula lists names of the formulas.
+ * Image with .dzi extension is exported as Deep Zoom pyramid, for print size renders.
+ * --tile renders .png or .dzi image larger than memory tile by tile.
+ * serve explores the formulas in a browser, as web map tiles on http://localhost:<port>/tiles/{fractal}/{z}/{x}/{y}.png
+ * coordinate hands out frames of zoom video to work processes of the same formula on localhost:<port>,
+ * each frame is rendered on its own, formulas of DynamicSequenceNebula aren't supported yet and are refused.
+ */
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: &[String]) -> Result<(), String> {
+    let mut plugins_dir = PLUGINS_DIR;
+    let mut size = None;
+    let mut tile_size = None;
+    loop {
+        match args {
+            [flag, dir, rest @ ..] if flag == "--plugins" => {
+                plugins_dir = dir;
+                args = rest;
+            }
+            [flag, value, rest @ ..] if flag == "--size" => {
+                size = Some(parse_size(value).ok_or_else(|| USAGE.to_string())?);
+                args = rest;
+            }
+            [flag, value, rest @ ..] if flag == "--tile" => {
+                tile_size = Some(value.parse().map_err(|_| USAGE.to_string())?);
+                args = rest;
+            }
+            _ => break,
+        }
+    }
+    let registry = registry(Path::new(plugins_dir))?;
+    match args {
+        [] => {
+            for name in registry.names() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        [command, rest @ ..] if command == "serve" && rest.len() <= 2 => {
+            let port = parse_port(rest.first(), SERVE_PORT)?;
+            let cache_dir = rest.get(1).map_or(TILE_CACHE_DIR, |dir| dir.as_str());
+            serve(registry, port, Path::new(cache_dir))
+        }
+        [command, name, frames, frames_dir, rest @ ..]
+            if command == "coordinate" && rest.len() <= 1 =>
+        {
+            let frames = frames.parse().map_err(|_| USAGE.to_string())?;
+            let port = parse_port(rest.first(), COORDINATOR_PORT)?;
+            coordinate(&registry, name, frames, Path::new(frames_dir), port, size)
+        }
+        [command, name, rest @ ..] if command == "work" && rest.len() <= 1 => {
+            let port = parse_port(rest.first(), COORDINATOR_PORT)?;
+            work(&registry, name, port)
+        }
+        [name, image] => render(&registry, name, image, size, tile_size),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn registry(plugins_dir: &Path) -> Result<Registry, String> {
+    let mut registry = formulas::init_builtin();
+    if plugins_dir != Path::new(PLUGINS_DIR) || plugins_dir.is_dir() {
+        plugin::load_dir(plugins_dir, &mut registry).map_err(|e| e.to_string())?;
+    }
+    Ok(registry)
+}
+
+// 12000x8000
+fn parse_size(size: &str) -> Option<(usize, usize)> {
+    let (width, height) = size.split_once('x')?;
+    match (width.parse().ok()?, height.parse().ok()?) {
+        (0, _) | (_, 0) => None,
+        size => Some(size),
+    }
+}
+
+fn parse_port(port: Option<&String>, default: u16) -> Result<u16, String> {
+    match port {
+        Some(port) => port.parse().map_err(|_| USAGE.to_string()),
+        None => Ok(default),
+    }
+}
+
+fn render(
+    registry: &Registry,
+    name: &str,
+    image: &str,
+    size: Option<(usize, usize)>,
+    tile_size: Option<usize>,
+) -> Result<(), String> {
+    let formula = registry
+        .get(name)
+        .ok_or_else(|| format!("unknown formula {}", name))?;
+    let mut config = formula.config.clone();
+    if let Some((width_x, height_y)) = size {
+        config.width_x = width_x;
+        config.height_y = height_y;
+    }
+    let image = Path::new(image);
+    if let Some(tile_size) = tile_size {
+        return formula
+            .render_tiled(&config, tile_size, image)
+            .map_err(|e| e.to_string());
+    }
+    let result = formula
+        .render(&config, Some(Arc::new(ProgressBar)))
+        .map_err(|e| e.to_string())?;
+    if image.extension().is_some_and(|e| e == "dzi") {
+        dzi::export(&result.image, image).map_err(|e| e.to_string())
+    } else {
+        result.image.save(image).map_err(|e| e.to_string())
+    }
+}
+
+fn serve(registry: Registry, port: u16, cache_dir: &Path) -> Result<(), String> {
+    let listener = tile_server::bind(port).map_err(|e| e.to_string())?;
+    println!(
+        "serving http://{}/tiles/{{fractal}}/{{z}}/{{x}}/{{y}}.png",
+        listener.local_addr().map_err(|e| e.to_string())?
+    );
+    Arc::new(tile_server::init(registry, cache_dir))
+        .serve(listener)
+        .map_err(|e| e.to_string())
+}
+
+fn coordinate(
+    registry: &Registry,
+    name: &str,
+    frames: u32,
+    frames_dir: &Path,
+    port: u16,
+    size: Option<(usize, usize)>,
+) -> Result<(), String> {
+    let formula = registry
+        .get(name)
+        .ok_or_else(|| format!("unknown formula {}", name))?;
+    let mut config = formula.config.clone();
+    if let Some((width_x, height_y)) = size {
+        config.width_x = width_x;
+        config.height_y = height_y;
+    }
+    let coordinator =
+        distributed::init(name, &config, frames, frames_dir).map_err(|e| e.to_string())?;
+    let listener = distributed::bind(port).map_err(|e| e.to_string())?;
+    println!(
+        "coordinating {} frames of {} on {}",
+        frames,
+        name,
+        listener.local_addr().map_err(|e| e.to_string())?
+    );
+    Arc::new(coordinator)
+        .run(listener)
+        .map_err(|e| e.to_string())
+}
+
+fn work(registry: &Registry, name: &str, port: u16) -> Result<(), String> {
+    let formula = registry
+        .get(name)
+        .ok_or_else(|| format!("unknown formula {}", name))?;
+    let rendered = distributed::work(formula, port).map_err(|e| e.to_string())?;
+    println!("rendered {} frames", rendered);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_size, registry, run, USAGE};
+    use std::path::Path;
+
+    #[test]
+    fn test_run() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert!(run(&[]).is_ok());
+        assert_eq!(run(&args(&["lotus"])), Err(USAGE.to_string()));
+        assert_eq!(
+            run(&args(&["lotus_x", "lotus.png"])),
+            Err("unknown formula lotus_x".to_string())
+        );
+        assert!(run(&args(&["--plugins", "plugins_x"])).is_err());
+        assert_eq!(run(&args(&["serve", "port_x"])), Err(USAGE.to_string()));
+        assert_eq!(
+            run(&args(&["coordinate", "lotus", "frames_x", "frames"])),
+            Err(USAGE.to_string())
+        );
+        assert!(run(&args(&["coordinate", "nebula_side", "10", "frames"]))
+            .unwrap_err()
+            .contains("DynamicSequenceNebula"));
+        assert_eq!(
+            run(&args(&["work", "lotus_x"])),
+            Err("unknown formula lotus_x".to_string())
+        );
+        assert_eq!(
+            run(&args(&["--size", "0x10", "lotus", "lotus.dzi"])),
+            Err(USAGE.to_string())
+        );
+        assert_eq!(
+            run(&args(&["--tile", "0", "lotus", "lotus.png"])),
+            Err("config: tile size must be positive".to_string())
+        );
+        assert_eq!(
+            run(&args(&["--tile", "512", "lotus", "lotus.jpg"])),
+            Err("config: tiled image is saved only as .png or .dzi".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("12000x8000"), Some((12000, 8000)));
+        assert_eq!(parse_size("12000"), None);
+        assert_eq!(parse_size("12000x"), None);
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = registry(Path::new("plugins")).unwrap();
+        assert!(registry.get("lotus").is_some());
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::{env, fs, io, thread};
+
+// coordinator and worker processes on one machine
+#[test]
+fn test_coordinate_work() {
+    let frames_dir = env::temp_dir().join("fractal_cli_test_frames");
+    let _ = fs::remove_dir_all(&frames_dir);
+    let cli = env!("CARGO_BIN_EXE_fractal_cli");
+
+    let mut coordinator = Command::new(cli)
+        .args(["--size", "320x240", "coordinate", "mandelbrot", "8"])
+        .arg(&frames_dir)
+        .arg("0")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // coordinating 8 frames of mandelbrot on 127.0.0.1:<port>, log may go first
+    let mut stdout = BufReader::new(coordinator.stdout.take().unwrap());
+    let mut line = String::new();
+    while !line.starts_with("coordinating ") {
+        line.clear();
+        assert!(stdout.read_line(&mut line).unwrap() > 0);
+    }
+    let port = line.trim().rsplit(':').next().unwrap().to_string();
+    // log follows, coordinator can't write to closed stdout
+    thread::spawn(move || io::copy(&mut stdout, &mut io::sink()));
+
+    let workers: Vec<_> = (0..3)
+        .map(|_| {
+            Command::new(cli)
+                .args(["work", "mandelbrot", &port])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut worker in workers {
+        assert!(worker.wait().unwrap().success());
+    }
+    assert!(coordinator.wait().unwrap().success());
+
+    for frame in 1..=8 {
+        let frame = image::open(frames_dir.join(format!("{:05}.png", frame))).unwrap();
+        assert_eq!((frame.width(), frame.height()), (320, 240));
+    }
+    fs::remove_dir_all(&frames_dir).unwrap();
+}
@@ -1,56 +1,28 @@
 use rusty_fractals::application;
-use rusty_fractals::fractal::FractalCalculationType::StaticSequenceMandelbrot;
-use rusty_fractals::fractal::OrbitType::Infinite;
-use rusty_fractals::fractal::{FractalConfig, FractalMath};
-use rusty_fractals::mem_collatz::MemCollatz;
-use rusty_fractals::palettes::PaletteName::{BlueToWhiteCircleUp, Nothing};
-use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square11;
-
-pub struct CollatzConjectureOrbits {}
-
-impl FractalMath<MemCollatz> for CollatzConjectureOrbits {
-    fn math(&self, m: &mut MemCollatz, origin_re: f64, origin_im: f64) {
-        m.square();
-        m.collatz_conjecture();
-        m.plus(origin_re, origin_im);
-    }
-}
+use rusty_fractals::formulas::CollatzConjecture;
+use rusty_fractals::fractal::FractalConfig;
+use rusty_fractals::presets;
 
 fn main() {
-    let fractal_config = FractalConfig {
-        name: "CollatzConjectureOrbits",
-        fractal_calc_type: StaticSequenceMandelbrot,
-
-        iteration_min: 7,
-        iteration_max: 1348,
-        resolution_multiplier: Square11,
-        palette: BlueToWhiteCircleUp,
-        palette_zero: Nothing,
-
-        width_x: 1280,
-        height_y: 720,
-        width_re: 5.0,
-        center_re: -0.088485445553580480,
-        center_im: -0.200679435068532800,
-
-        orbits: Infinite,
-        update_max: 150,
-        update_min: 0,
-    };
+    application::execute(fractal_config(), CollatzConjecture);
+}
 
-    application::execute(fractal_config, CollatzConjectureOrbits {});
+fn fractal_config() -> FractalConfig {
+    presets::collatz_conjecture_orbits().build()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::CollatzConjectureOrbits;
+    use crate::fractal_config;
+    use rusty_fractals::formulas::CollatzConjecture;
     use rusty_fractals::fractal::FractalMath;
+    use rusty_fractals::golden;
     use rusty_fractals::mem::Mem;
     use rusty_fractals::mem_collatz::MemCollatz;
 
     #[test]
     fn test_math() {
-        let collatz = CollatzConjectureOrbits {};
+        let collatz = CollatzConjecture;
         let mut mc = MemCollatz {
             m: Mem { re: 0.0, im: 0.0 },
             num: 7,
@@ -59,4 +31,13 @@ mod tests {
         assert_eq!(mc.m.re, 2.0);
         assert_eq!(mc.m.im, 1.1);
     }
+
+    #[test]
+    fn test_golden() {
+        golden::assert_golden(
+            env!("CARGO_MANIFEST_DIR"),
+            fractal_config(),
+            CollatzConjecture,
+        );
+    }
 }
@@ -4,11 +4,8 @@ mod pixel;
 
 use crate::mem_euler::MemEuler;
 use rusty_fractals::application;
-use rusty_fractals::fractal::FractalCalculationType::StaticSpectralImageEuler;
-use rusty_fractals::fractal::OrbitType::Infinite;
-use rusty_fractals::fractal::{FractalConfig, FractalMath};
-use rusty_fractals::palettes::PaletteName::{BlueToWhiteCircleUp, Nothing};
-use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Single;
+use rusty_fractals::fractal::FractalMath;
+use rusty_fractals::presets;
 
 struct Euler {}
 
@@ -23,28 +20,7 @@ impl FractalMath<MemEuler> for Euler {
 }
 
 fn main() {
-    let fractal_config = FractalConfig {
-        name: "Euler",
-        fractal_calc_type: StaticSpectralImageEuler,
-
-        iteration_min: 42,
-        iteration_max: 80000,
-        resolution_multiplier: Single,
-        palette: BlueToWhiteCircleUp, // TODO PALETTE_3_RGB
-        palette_zero: Nothing,
-
-        // area
-        width_x: 1920,
-        height_y: 1080,
-        width_re: 4.0,
-        center_re: 0.0,
-        center_im: 0.0,
-
-        // calculation config
-        orbits: Infinite, // TODO probably not
-        update_max: 150,
-        update_min: 0,
-    };
+    let fractal_config = presets::euler().build();
 
     application::execute(fractal_config, Euler {});
 }
@@ -1,64 +1,29 @@
 use rusty_fractals::application;
-use rusty_fractals::constants::{PHOENIX_INIT_C, PHOENIX_INIT_P};
-use rusty_fractals::fractal::FractalCalculationType::StaticImageNebula;
-use rusty_fractals::fractal::OrbitType::Finite;
-use rusty_fractals::fractal::{FractalConfig, FractalMath};
-use rusty_fractals::mem_phoenix::MemPhoenix;
-use rusty_fractals::palettes::PaletteName::{BlueToWhiteCircleUp, Nothing};
-use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square9;
-
-pub struct Head {}
-
-impl FractalMath<MemPhoenix> for Head {
-    fn math(&self, mp: &mut MemPhoenix, origin_re: f64, origin_im: f64) {
-        mp.square();
-        mp.m.re += PHOENIX_INIT_C;
-        mp.m.re += PHOENIX_INIT_P * mp.prev_prev_re;
-        mp.m.im += PHOENIX_INIT_P * mp.prev_prev_im;
-        // previous iteration values
-        mp.prev_prev_re = mp.prev_re;
-        mp.prev_prev_im = mp.prev_im;
-        mp.prev_re = mp.m.re;
-        mp.prev_im = mp.m.im;
-        mp.plus(origin_re, origin_im);
-    }
-}
+use rusty_fractals::formulas::Head;
+use rusty_fractals::fractal::FractalConfig;
+use rusty_fractals::presets;
 
 fn main() {
-    let fractal_config = FractalConfig {
-        name: "Head",
-        iteration_min: 8,
-        iteration_max: 25000,
-        fractal_calc_type: StaticImageNebula,
-        resolution_multiplier: Square9,
-        palette: BlueToWhiteCircleUp,
-
-        palette_zero: Nothing,
-        width_x: 1280,
-        height_y: 720,
-        width_re: 5.0,
-        center_re: -0.16884290496519,
-        center_im: -0.37573460559804,
-
-        orbits: Finite,
-        update_max: 150,
-        update_min: 0,
-    };
+    application::execute(fractal_config(), Head);
+}
 
-    application::execute(fractal_config, Head {});
+fn fractal_config() -> FractalConfig {
+    presets::head().build()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Head;
+    use crate::fractal_config;
     use rusty_fractals::constants::PHOENIX_INITIALIZER;
+    use rusty_fractals::formulas::Head;
     use rusty_fractals::fractal::FractalMath;
+    use rusty_fractals::golden;
     use rusty_fractals::mem::Mem;
     use rusty_fractals::mem_phoenix::MemPhoenix;
 
     #[test]
     fn test_math() {
-        let head = Head {};
+        let head = Head;
         let mut mp = MemPhoenix {
             m: Mem { re: 0.0, im: 0.0 },
             prev_prev_re: PHOENIX_INITIALIZER,
@@ -72,4 +37,9 @@ mod tests {
         assert_eq!(mp.m.re, 1.1);
         assert_eq!(mp.m.im, -0.15);
     }
+
+    #[test]
+    fn test_golden() {
+        golden::assert_golden(env!("CARGO_MANIFEST_DIR"), fractal_config(), Head);
+    }
 }
@@ -1,56 +1,27 @@
 use rusty_fractals::application;
-use rusty_fractals::fractal::FractalCalculationType::StaticImageNebula;
-use rusty_fractals::fractal::OrbitType::Finite;
-use rusty_fractals::fractal::{FractalConfig, FractalMath};
-use rusty_fractals::mem::Mem;
-use rusty_fractals::palettes::PaletteName::{BlueToWhiteCircleUp, Nothing};
-use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square11;
-
-pub struct Lotus {}
-
-impl FractalMath<Mem> for Lotus {
-    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
-        m.conjugation();
-        m.square();
-        m.plus(origin_re, origin_im);
-    }
-}
+use rusty_fractals::formulas::Lotus;
+use rusty_fractals::fractal::FractalConfig;
+use rusty_fractals::presets;
 
 fn main() {
-    let fractal_config = FractalConfig {
-        name: "Lotus",
-        fractal_calc_type: StaticImageNebula,
-
-        iteration_min: 42,
-        iteration_max: 8000,
-        resolution_multiplier: Square11,
-
-        palette: BlueToWhiteCircleUp,
-        palette_zero: Nothing,
-
-        width_x: 1280,
-        height_y: 1000,
-        width_re: 3.5,
-        center_re: 0.67748277351478,
-        center_im: -1.18770078111202,
-
-        orbits: Finite,
-        update_max: 150,
-        update_min: 0,
-    };
+    application::execute(fractal_config(), Lotus);
+}
 
-    application::execute(fractal_config, Lotus {});
+fn fractal_config() -> FractalConfig {
+    presets::lotus().build()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Lotus;
+    use crate::fractal_config;
+    use rusty_fractals::formulas::Lotus;
     use rusty_fractals::fractal::FractalMath;
+    use rusty_fractals::golden;
     use rusty_fractals::mem::Mem;
 
     #[test]
     fn test_math() {
-        let lotus = Lotus {};
+        let lotus = Lotus;
         let mut m = Mem { re: 0.0, im: 0.0 };
 
         lotus.math(&mut m, 1.0, 0.1);
@@ -58,4 +29,9 @@ mod tests {
         assert_eq!(m.re, 1.0);
         assert_eq!(m.im, 0.1);
     }
+
+    #[test]
+    fn test_golden() {
+        golden::assert_golden(env!("CARGO_MANIFEST_DIR"), fractal_config(), Lotus);
+    }
 }
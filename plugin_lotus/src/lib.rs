@@ -0,0 +1,47 @@
+use rusty_fractals::constants::MAGNIFICATION_WIDTH_RE;
+use rusty_fractals::fractal::FractalCalculationType::StaticImageNebula;
+use rusty_fractals::fractal::OrbitType::Finite;
+use rusty_fractals::mem::Mem;
+use rusty_fractals::palettes::PaletteName::{BlueToWhiteCircleUp, Nothing};
+use rusty_fractals::plugin::{PluginConfig, PluginDescriptor, PluginFormula, PluginMath};
+use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square11;
+
+/**
+ * Lotus as a plugin
+ * Build it and copy libplugin_lotus.so to the plugins directory of fractal_cli.
+ */
+extern "C" fn lotus(m: &mut Mem, origin_re: f64, origin_im: f64) {
+    m.conjugation();
+    m.square();
+    m.plus(origin_re, origin_im);
+}
+
+static FORMULAS: [PluginFormula; 1] = [PluginFormula {
+    name: c"plugin_lotus".as_ptr(),
+    config: PluginConfig {
+        name: c"Lotus".as_ptr(),
+        fractal_calc_type: StaticImageNebula,
+        orbits: Finite,
+        iteration_min: 42,
+        iteration_max: 8000,
+        resolution_multiplier: Square11,
+        palette: BlueToWhiteCircleUp,
+        palette_zero: Nothing,
+        width_x: 1280,
+        height_y: 1000,
+        width_re: MAGNIFICATION_WIDTH_RE,
+        center_re: 0.67748277351478,
+        center_im: -1.18770078111202,
+    },
+    math: PluginMath::Mem(lotus),
+}];
+
+static DESCRIPTOR: PluginDescriptor = PluginDescriptor {
+    formulas: FORMULAS.as_ptr(),
+    formulas_len: FORMULAS.len(),
+};
+
+#[no_mangle]
+pub extern "C" fn rusty_fractals_plugin_v1() -> *const PluginDescriptor {
+    &DESCRIPTOR
+}
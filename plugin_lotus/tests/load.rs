@@ -0,0 +1,36 @@
+use rusty_fractals::formulas::Lotus;
+use rusty_fractals::palettes::PaletteName::GrayToBlackCircleDown;
+use rusty_fractals::{formulas, golden, plugin, presets};
+use std::env;
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+
+/**
+ * Loads the plugin built by cargo and renders it like fractal_lotus
+ */
+#[test]
+fn test_load() {
+    // next to the test in target/debug/deps, or in target/debug
+    let exe = env::current_exe().unwrap();
+    let file = format!("{}plugin_lotus{}", DLL_PREFIX, DLL_SUFFIX);
+    let path = exe
+        .ancestors()
+        .skip(1)
+        .take(2)
+        .map(|dir| dir.join(&file))
+        .find(|path| path.exists())
+        .expect("could not find plugin library");
+
+    let mut registry = formulas::init();
+    let names = plugin::load(&path, &mut registry).unwrap();
+    assert_eq!(names, vec!["plugin_lotus"]);
+
+    let formula = registry.get("plugin_lotus").unwrap();
+    assert_eq!(formula.config, presets::lotus().build());
+
+    let mut config = golden::reference_config(formula.config.clone());
+    // every pixel is coloured, also those with zero value
+    config.palette_zero = GrayToBlackCircleDown;
+    let result = formula.render(&config, None).unwrap();
+    assert!(result.values.values.iter().any(|value| *value > 0));
+    assert_eq!(result.values.values, golden::render_grid(&config, Lotus));
+}
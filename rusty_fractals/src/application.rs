@@ -1,25 +1,41 @@
 use crate::area::{Area, AreaDataCopy};
+use crate::canvas::Canvas;
+use crate::control::Control;
 use crate::data_image::{colour_for_state, DataImage};
 use crate::fractal::{FractalConfig, FractalMath, MemType};
+use crate::fractal_log;
 use crate::machine;
 use crate::machine::Machine;
 use crate::pixel_states::{is_active_new, DomainElementState};
+use crate::{debug, error, info};
 use fltk::app::{event_button, event_coords, event_key};
 use fltk::enums::{Color, Event, Key};
 use fltk::window::DoubleWindow;
 use fltk::{app, draw, prelude::*, window::Window};
-use image::{Pixel, Rgb};
+use image::{Pixel, Rgb, RgbImage};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 /**
- * Application owns Machine
+ * Application owns Machine and its window
+ * Machine paints to the window as its Canvas, without reference to Application.
  */
 pub struct Application<F, M>
 where
     F: FractalMath<M> + 'static,
     M: MemType<M> + 'static,
 {
+    pub window: Arc<ApplicationWindow>,
+    pub machine_arc: Arc<Mutex<Machine<F, M>>>,
+    pub is_shutting_down: Arc<AtomicBool>,
+    // Machine is locked by calculation thread, use control to pause or cancel it
+    pub control: Arc<Control>,
+}
+
+/**
+ * Window of Application, Canvas of its Machine
+ */
+pub struct ApplicationWindow {
     /* DoubleWindow class provides a **double-buffered** window.
     - In double buffering:
     - All drawing operations are first performed in an **off-screen buffer**.
@@ -27,15 +43,13 @@ where
     - This eliminates flickering during redraws, as the user only sees the final, fully-drawn frame.*/
     pub window: Arc<Mutex<DoubleWindow>>, // Shared ownership of the GUI Window
     application_data: Arc<Mutex<ApplicationData>>,
-    pub machine_arc: Arc<Mutex<Machine<'static, F, M>>>,
-    pub is_shutting_down: Arc<AtomicBool>,
 }
 
 struct ApplicationData {
     pub last_max_value: u32,
 }
 
-fn init<'lt, F, M>(config: &FractalConfig, fractal: F) -> Arc<Mutex<Application<F, M>>>
+fn init<F, M>(config: &FractalConfig, fractal: F) -> Application<F, M>
 where
     F: FractalMath<M> + 'static,
     M: MemType<M> + 'static,
@@ -44,36 +58,27 @@ where
 
     let width = config.width_x as i32;
     let height = config.height_y as i32;
-    let name = config.name;
-
-    window.set_label(name);
+    window.set_label(&config.name);
     window.set_size(width, height);
 
     window.end();
     window.show();
 
-    let machine = machine::init(&config, fractal);
-    let machine_arc = Arc::new(Mutex::new(machine));
-
-    let application = Application {
+    let window = Arc::new(ApplicationWindow {
         window: Arc::new(Mutex::new(window)),
         application_data: Arc::new(Mutex::new(ApplicationData { last_max_value: 0 })),
-        machine_arc,
-        is_shutting_down: Arc::new(Default::default()),
-    };
-
-    let application_arc = Arc::new(Mutex::new(application));
+    });
 
-    // Set Application ref for Machine
-    application_arc
-        .lock()
-        .unwrap()
-        .machine_arc
-        .lock()
-        .unwrap()
-        .set_application_ref(application_arc.clone());
+    let mut machine = machine::init(config, fractal);
+    machine.set_canvas(window.clone());
+    let control = machine.control.clone();
 
-    application_arc
+    Application {
+        window,
+        machine_arc: Arc::new(Mutex::new(machine)),
+        is_shutting_down: Arc::new(Default::default()),
+        control,
+    }
 }
 
 /**
@@ -84,30 +89,32 @@ where
     F: FractalMath<M> + 'static,
     M: MemType<M> + 'static,
 {
-    println!("application.execute()");
+    debug!("application.execute()");
 
     let app = app::App::default();
-    let application_arc = init(&config, fractal);
+    let application = init(&config, fractal);
 
     // Window actions
-    application_arc.lock().unwrap().init_window_actions();
+    application.init_window_actions();
 
-    let machine_arc_clone = application_arc.lock().unwrap().machine_arc.clone();
+    let machine_arc_clone = application.machine_arc.clone();
 
-    println!("calculation - new thread ");
+    debug!("calculation - new thread ");
     let task = move || {
         /*
          * execute fractal calculation
          */
-        machine_arc_clone.lock().unwrap().execute_calculation();
+        if let Err(e) = machine_arc_clone.lock().unwrap().execute_calculation() {
+            error!("calculation failed: {}", e);
+        }
     };
     rayon::spawn_fifo(task);
 
-    println!("run().unwrap()");
+    debug!("run().unwrap()");
     // The last line of the program
     app.run().unwrap();
 
-    println!("execute() end.");
+    debug!("execute() end.");
 }
 
 /**
@@ -119,14 +126,16 @@ where
     M: MemType<M> + 'static,
 {
     pub fn init_window_actions(&self) {
-        println!("init_window_actions()");
+        debug!("init_window_actions()");
 
         let shutdown_flag = self.is_shutting_down.clone();
+        let control = self.control.clone();
 
-        // clone Arc, not Machine
-        let machine_ref = self.machine_arc.clone();
+        // Machine holds the window as its Canvas, strong reference from the window's handler would be a cycle
+        let machine_ref = Arc::downgrade(&self.machine_arc);
 
         self.window
+            .window
             .lock()
             .unwrap()
             .handle(move |_, event| match event {
@@ -134,27 +143,44 @@ where
                     let ek = event_key();
                     match ek {
                         Key::Escape => {
-                            println!("exit");
+                            info!("exit");
                             shutdown_flag.store(true, Ordering::Relaxed); // Signal shutdown
+                            control.cancel(); // Stop calculation, partial result is painted
                             app::awake(); // Wake the app so it can break the event loop
                         }
                         _ => {}
                     }
                     match ek.to_char().unwrap() {
                         'i' => {
-                            println!("i");
+                            debug!("i");
                             true
                         }
                         's' => {
-                            println!("s");
+                            debug!("s");
+                            true
+                        }
+                        'p' => {
+                            debug!("p");
+                            // pause or resume calculation
+                            control.toggle_pause();
+                            true
+                        }
+                        't' => {
+                            // log calculation of the pixel under mouse
+                            let (x, y) = event_coords();
+                            info!("trace pixel [{}, {}]", x, y);
+                            fractal_log::trace_pixel(Some([x as usize, y as usize]));
                             true
                         }
                         ' ' => {
-                            println!("space bar");
-                            machine_ref
-                                .lock()
-                                .unwrap()
-                                .zoom_in_recalculate_pixel_positions();
+                            debug!("space bar");
+                            if let Some(machine) = machine_ref.upgrade() {
+                                if let Err(e) =
+                                    machine.lock().unwrap().zoom_in_recalculate_pixel_positions()
+                                {
+                                    error!("zoom in: {}", e);
+                                }
+                            }
                             true
                         }
                         _ => false,
@@ -165,24 +191,25 @@ where
                     let left = event_button() == 1;
                     if left {
                         let (x, y) = event_coords();
-                        println!("c: {} {}", x, y);
+                        debug!("c: {} {}", x, y);
 
-                        machine_ref
-                            .lock()
-                            .unwrap()
-                            .move_target(x as usize, y as usize);
+                        if let Some(machine) = machine_ref.upgrade() {
+                            let machine = machine.lock().unwrap();
+                            machine.move_target(x as usize, y as usize);
 
-                        machine_ref
-                            .lock()
-                            .unwrap()
-                            .zoom_in_recalculate_pixel_positions();
+                            if let Err(e) = machine.zoom_in_recalculate_pixel_positions() {
+                                error!("zoom in: {}", e);
+                            }
+                        }
                     }
                     false
                 }
                 _ => false,
             });
     }
+}
 
+impl Canvas for ApplicationWindow {
     /**
      * This method paints only colors from data_image.
      * Use other painting methods to display the element states before and during calculation.
@@ -191,29 +218,52 @@ where
      * Colors
      * ------
      */
-    pub fn paint_final_calculation_result_colors(&self, data_image: &DataImage) {
+    fn paint_final_calculation_result_colors(&self, data_image: &DataImage) {
+        let width = data_image.width_x;
+        let height = data_image.height_y;
+
+        let pixel_colors: Vec<Option<Rgb<u8>>> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| data_image.colour_at(x, y)))
+            .collect();
+
+        self.paint_colors(width, height, pixel_colors);
+    }
+
+    /**
+     * Paints coloured zoom frame, which is no longer in data_image
+     */
+    fn paint_frame(&self, image: &RgbImage) {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+
+        let pixel_colors: Vec<Option<Rgb<u8>>> = image.pixels().map(|c| Some(*c)).collect();
+
+        self.paint_colors(width, height, pixel_colors);
+    }
+
+    /**
+     * Paints one level of progressive calculation
+     * Each calculated pixel [x, y] with x and y divisible by step is painted as step x step square
+     */
+    fn paint_refinement_level(&self, data_image: &DataImage, step: usize) {
         match app::lock() {
             Ok(_) => {
                 let width = data_image.width_x;
                 let height = data_image.height_y;
 
-                let pixel_colors: Vec<Option<Rgb<u8>>> = (0..height)
-                    .flat_map(|y| (0..width).map(move |x| data_image.colour_at(x, y)))
+                let samples: Vec<(usize, usize, Rgb<u8>)> = (0..height)
+                    .step_by(step)
+                    .flat_map(|y| {
+                        (0..width)
+                            .step_by(step)
+                            .filter_map(move |x| data_image.colour_at(x, y).map(|c| (x, y, c)))
+                    })
                     .collect();
 
                 let mut window = self.window.lock().unwrap();
                 window.draw(move |_| {
-                    // never use self in here
-                    // locking / unlocking app for draw is not necessary, says so AI
-                    // redraw() can't be called from draw()
-
-                    for y in 0..height {
-                        for x in 0..width {
-                            let color_index = pixel_colors[y * width + x];
-                            if let Some(color) = color_index {
-                                draw_colored_point(x, y, &color);
-                            }
-                        }
+                    for (x, y, color) in &samples {
+                        draw_colored_square(*x, *y, step, color);
                     }
                 });
 
@@ -222,7 +272,7 @@ where
                 app::awake();
             }
             Err(_) => {
-                println!("paint_final_calculation_result_colors(): app::lock() failed");
+                error!("paint_refinement_level(): app::lock() failed");
             }
         }
         app::unlock();
@@ -236,7 +286,7 @@ where
      * STATES
      * ------
      */
-    pub fn paint_partial_calculation_result_states(
+    fn paint_partial_calculation_result_states(
         &self,
         data_image: &DataImage,
         area: &Area,
@@ -332,7 +382,7 @@ where
                 app::awake();
             }
             Err(_) => {
-                println!("paint_partial_calculation_result_states(): app::lock() failed");
+                error!("paint_partial_calculation_result_states(): app::lock() failed");
             }
         }
         app::unlock();
@@ -341,7 +391,7 @@ where
     /**
      * paint only pixel states
      */
-    pub fn paint_pixel_states(&self, data_image: &DataImage) {
+    fn paint_pixel_states(&self, data_image: &DataImage) {
         match app::lock() {
             Ok(_) => {
                 let width = data_image.width_x;
@@ -374,7 +424,39 @@ where
                 app::awake();
             }
             Err(_) => {
-                println!("paint_pixel_states(): app::lock() failed");
+                error!("paint_pixel_states(): app::lock() failed");
+            }
+        }
+        app::unlock();
+    }
+}
+
+impl ApplicationWindow {
+    fn paint_colors(&self, width: usize, height: usize, pixel_colors: Vec<Option<Rgb<u8>>>) {
+        match app::lock() {
+            Ok(_) => {
+                let mut window = self.window.lock().unwrap();
+                window.draw(move |_| {
+                    // never use self in here
+                    // locking / unlocking app for draw is not necessary, says so AI
+                    // redraw() can't be called from draw()
+
+                    for y in 0..height {
+                        for x in 0..width {
+                            let color_index = pixel_colors[y * width + x];
+                            if let Some(color) = color_index {
+                                draw_colored_point(x, y, &color);
+                            }
+                        }
+                    }
+                });
+
+                // Trigger redraw events from the main thread
+                window.redraw();
+                app::awake();
+            }
+            Err(_) => {
+                error!("paint_colors(): app::lock() failed");
             }
         }
         app::unlock();
@@ -394,3 +476,13 @@ fn draw_colored_point(x: usize, y: usize, color: &Rgb<u8>) {
     draw::set_draw_color(Color::from_rgb(r, g, b));
     draw::draw_point(x as i32, y as i32);
 }
+
+// called only from main thread within window.show() method
+fn draw_colored_square(x: usize, y: usize, size: usize, color: &Rgb<u8>) {
+    let r = *color.channels().first().unwrap();
+    let g = *color.channels().get(1).unwrap();
+    let b = *color.channels().get(2).unwrap();
+
+    draw::set_draw_color(Color::from_rgb(r, g, b));
+    draw::draw_rectf(x as i32, y as i32, size as i32, size as i32);
+}
@@ -1,6 +1,7 @@
 use crate::constants::ZOOM;
 use crate::fractal::FractalConfig;
-use std::sync::Mutex;
+use crate::{debug, info, warn};
+use std::sync::{Mutex, MutexGuard};
 
 /**
  * RxR Area on which the Fractal is calculated
@@ -34,6 +35,11 @@ pub struct AreaData {
     plank: f64,
 }
 
+/**
+ * Immutable snapshot of Area, taken once per frame.
+ * Calculation hot path reads only the snapshot, so it never locks the Area.
+ */
+#[derive(Clone, Copy)]
 pub struct AreaDataCopy {
     pub center_re: f64,
     pub center_im: f64,
@@ -43,9 +49,21 @@ pub struct AreaDataCopy {
     pub height_im: f64,
     pub height_yf64: f64,
     pub height_half_yf64: f64,
+    pub border_low_re: f64,
+    pub border_low_im: f64,
+    pub border_high_re: f64,
+    pub border_high_im: f64,
+    pub plank: f64,
 }
 
 impl AreaDataCopy {
+    pub fn contains(&self, re: f64, im: f64) -> bool {
+        re > self.border_low_re
+            && re < self.border_high_re
+            && im > self.border_low_im
+            && im < self.border_high_im
+    }
+
     pub fn point_to_pixel(&self, re: f64, im: f64) -> (usize, usize) {
         let px = (self.width_xf64 * (re - self.center_re) / self.width_re) + self.width_half_xf64;
         let py =
@@ -55,71 +73,57 @@ impl AreaDataCopy {
 }
 
 impl<'lt> Area {
+    /**
+     * Area data are plain numbers, a panic of another thread can't leave them inconsistent
+     * Poisoned lock is recovered, so that one failed chunk doesn't fail all following frames.
+     */
+    fn lock(&self) -> MutexGuard<'_, AreaData> {
+        self.data.lock().unwrap_or_else(|poisoned| {
+            warn!("Area lock poisoned, recovered");
+            poisoned.into_inner()
+        })
+    }
+
     pub fn contains(&self, re: f64, im: f64) -> bool {
-        match self.data.lock() {
-            Ok(d) => {
-                re > d.border_low_re
-                    && re < d.border_high_re
-                    && im > d.border_low_im
-                    && im < d.border_high_im
-            }
-            Err(e) => {
-                println!("Area.contains(): {}", e);
-                panic!()
-            }
-        }
+        let d = self.lock();
+        re > d.border_low_re
+            && re < d.border_high_re
+            && im > d.border_low_im
+            && im < d.border_high_im
     }
 
     /**
      * Maps pixels [x, y] to their center [re, im]
      */
     pub fn screen_to_domain_re_copy(&self) -> Vec<f64> {
-        println!("screen_to_domain_re_copy()");
-        match self.data.lock() {
-            Ok(d) => d.numbers_re.clone(),
-            Err(e) => {
-                println!("(): {}", e);
-                panic!()
-            }
-        }
+        debug!("screen_to_domain_re_copy()");
+        self.lock().numbers_re.clone()
     }
 
     /**
      * Maps pixels [x, y] to their center [re, im]
      */
     pub fn screen_to_domain_im_copy(&self) -> Vec<f64> {
-        match self.data.lock() {
-            Ok(d) => d.numbers_im.clone(),
-            Err(e) => {
-                println!("(): {}", e);
-                panic!()
-            }
-        }
+        self.lock().numbers_im.clone()
     }
 
     /**
      * Check first, if element can convert, only then call this method
      */
     pub fn point_to_pixel(&self, re: f64, im: f64) -> (usize, usize) {
-        match self.data.lock() {
-            Ok(d) => {
-                let px = (d.width_xf64 * (re - d.center_re) / d.width_re) + d.width_half_xf64;
-                let py = (d.height_yf64 * (im - d.center_im) / d.height_im) + d.height_half_yf64;
-                (px as usize, py as usize)
-            }
-            Err(e) => {
-                println!("point_to_pixel({},{}): {}", re, im, e);
-                panic!()
-            }
-        }
+        let d = self.lock();
+        let px = (d.width_xf64 * (re - d.center_re) / d.width_re) + d.width_half_xf64;
+        let py = (d.height_yf64 * (im - d.center_im) / d.height_im) + d.height_half_yf64;
+        (px as usize, py as usize)
     }
 
     /**
-     * copy area data for point_to_pixel method
+     * copy area data for contains and point_to_pixel methods
      * element's re, im coordinates can be converted to x,y because they were verified during path calculation
+     * Area may change only between frames, by zoom_in() and move_target()
      */
     pub fn copy_data(&self) -> AreaDataCopy {
-        let area = &self.data.lock().unwrap();
+        let area = &self.lock();
         AreaDataCopy {
             center_re: area.center_re,
             center_im: area.center_im,
@@ -129,104 +133,91 @@ impl<'lt> Area {
             height_im: area.height_im,
             height_yf64: area.height_yf64,
             height_half_yf64: area.height_half_yf64,
+            border_low_re: area.border_low_re,
+            border_low_im: area.border_low_im,
+            border_high_re: area.border_high_re,
+            border_high_im: area.border_high_im,
+            plank: area.plank,
         }
     }
 
     pub fn zoom_in(&self) {
-        println!("zoom_in()");
-        match self.data.lock() {
-            Ok(mut d) => {
-                d.width_re = d.width_re * ZOOM;
-                d.height_im = d.width_re * ((d.height_y as f64) / (d.width_x as f64));
-
-                d.plank = d.width_re / d.width_x as f64;
-
-                d.border_low_re = d.center_re - d.width_re / 2.0;
-                d.border_high_re = d.center_re + d.width_re / 2.0 - d.plank;
-                d.border_low_im = d.center_im - d.height_im / 2.0;
-                d.border_high_im = d.center_im + d.height_im / 2.0 - d.plank;
-
-                d.numbers_re.clear();
-                d.numbers_im.clear();
-
-                // use re, im in the center of each pixel
-                let ph = d.plank / 2.0;
-
-                // re
-                for x in 0..d.width_x {
-                    let v = d.border_low_re + (d.plank * x as f64) + ph;
-                    d.numbers_re.push(v);
-                }
-
-                // im
-                for y in 0..d.height_y {
-                    let v = d.border_low_im + (d.plank * y as f64) + ph;
-                    d.numbers_im.push(v);
-                }
-            }
-            Err(e) => {
-                println!("(): {}", e);
-            }
+        debug!("zoom_in()");
+        let d = &mut self.lock();
+        d.width_re *= ZOOM;
+        d.height_im = d.width_re * ((d.height_y as f64) / (d.width_x as f64));
+
+        d.plank = d.width_re / d.width_x as f64;
+
+        d.border_low_re = d.center_re - d.width_re / 2.0;
+        d.border_high_re = d.center_re + d.width_re / 2.0 - d.plank;
+        d.border_low_im = d.center_im - d.height_im / 2.0;
+        d.border_high_im = d.center_im + d.height_im / 2.0 - d.plank;
+
+        d.numbers_re.clear();
+        d.numbers_im.clear();
+
+        // use re, im in the center of each pixel
+        let ph = d.plank / 2.0;
+
+        // re
+        for x in 0..d.width_x {
+            let v = d.border_low_re + (d.plank * x as f64) + ph;
+            d.numbers_re.push(v);
+        }
+
+        // im
+        for y in 0..d.height_y {
+            let v = d.border_low_im + (d.plank * y as f64) + ph;
+            d.numbers_im.push(v);
         }
     }
 
     // TODO
     pub fn move_to_initial_coordinates(&self, init_target_re: f64, init_target_im: f64) {
-        println!("move_to_initial_coordinates()");
-        match self.data.lock() {
-            Ok(mut d) => {
-                d.center_re = init_target_re;
-                d.center_im = init_target_im;
-            }
-            Err(e) => {
-                println!("Area.move_to_initial_coordinates(): {}", e);
-            }
-        }
+        debug!("move_to_initial_coordinates()");
+        let d = &mut self.lock();
+        d.center_re = init_target_re;
+        d.center_im = init_target_im;
     }
 
     pub fn plank(&self) -> f64 {
-        self.data.lock().unwrap().plank
+        self.lock().plank
     }
 
     // TODO
     pub fn move_target(&self, x: usize, y: usize) {
-        match self.data.lock() {
-            Ok(mut d) => {
-                println!("move_target({}, {})", x, y);
-                let re = d.numbers_re[x];
-                let im = d.numbers_im[y];
-                println!("move_target({}, {})", re, im);
-                d.center_re = re;
-                d.center_im = im;
-
-                d.border_low_re = d.center_re - d.width_re / 2.0;
-                d.border_high_re = d.center_re + d.width_re / 2.0 - d.plank;
-                d.border_low_im = d.center_im - d.height_im / 2.0;
-                d.border_high_im = d.center_im + d.height_im / 2.0 - d.plank;
-
-                d.numbers_re.clear();
-                d.numbers_im.clear();
-                // use re, im in the center of each pixel
-                let ph = d.plank / 2.0;
-                for x in 0..d.width_x {
-                    let v = d.border_low_re + (d.plank * x as f64) + ph;
-                    d.numbers_re.push(v);
-                }
-                for y in 0..d.height_y {
-                    let v = d.border_low_im + (d.plank * y as f64) + ph;
-                    d.numbers_im.push(v);
-                }
-                println!("recalculated");
-            }
-            Err(e) => {
-                println!("(): {}", e);
-            }
+        let d = &mut self.lock();
+        info!("move_target({}, {})", x, y);
+        let re = d.numbers_re[x];
+        let im = d.numbers_im[y];
+        info!("move_target({}, {})", re, im);
+        d.center_re = re;
+        d.center_im = im;
+
+        d.border_low_re = d.center_re - d.width_re / 2.0;
+        d.border_high_re = d.center_re + d.width_re / 2.0 - d.plank;
+        d.border_low_im = d.center_im - d.height_im / 2.0;
+        d.border_high_im = d.center_im + d.height_im / 2.0 - d.plank;
+
+        d.numbers_re.clear();
+        d.numbers_im.clear();
+        // use re, im in the center of each pixel
+        let ph = d.plank / 2.0;
+        for x in 0..d.width_x {
+            let v = d.border_low_re + (d.plank * x as f64) + ph;
+            d.numbers_re.push(v);
+        }
+        for y in 0..d.height_y {
+            let v = d.border_low_im + (d.plank * y as f64) + ph;
+            d.numbers_im.push(v);
         }
+        debug!("recalculated");
     }
 }
 
 pub fn init<'lt>(config: &FractalConfig) -> Area {
-    println!("init()");
+    debug!("init()");
     let width_re = config.width_re;
     let center_re = config.center_re;
     let center_im = config.center_im;
@@ -244,14 +235,14 @@ pub fn init<'lt>(config: &FractalConfig) -> Area {
     let border_low_im = center_im - height_im / 2.0;
     let border_high_im = center_im + height_im / 2.0;
 
-    println!("width_re:       {}", width_re);
-    println!("height_im:      {}", height_im);
-    println!("border_low_re:  {}", border_low_re);
-    println!("border_high_re: {}", border_high_re);
-    println!("border_low_im:  {}", border_low_im);
-    println!("border_high_im: {}", border_high_im);
-    println!("(plank):        {}", plank);
-    println!("(plank_half):   {}", plank_half);
+    debug!("width_re:       {}", width_re);
+    debug!("height_im:      {}", height_im);
+    debug!("border_low_re:  {}", border_low_re);
+    debug!("border_high_re: {}", border_high_re);
+    debug!("border_low_im:  {}", border_low_im);
+    debug!("border_high_im: {}", border_high_im);
+    debug!("(plank):        {}", plank);
+    debug!("(plank_half):   {}", plank_half);
 
     /* Generate domain elements */
     let mut numbers_re: Vec<f64> = Vec::new();
@@ -337,6 +328,24 @@ mod tests {
         assert_eq!(area.contains(0.5, -0.4), false);
     }
 
+    #[test]
+    fn test_copy_data_contains() {
+        let conf = fractal::init_trivial_static_config();
+        let area = init(&conf);
+        let copy = area.copy_data();
+
+        assert!(copy.contains(0.4, 0.4));
+        assert!(!copy.contains(0.4, 0.6));
+        assert!(!copy.contains(-0.6, -0.4));
+        assert_eq!(copy.contains(0.5, -0.4), area.contains(0.5, -0.4));
+        assert_eq!(copy.point_to_pixel(0.4, 0.4), area.point_to_pixel(0.4, 0.4));
+
+        // snapshot doesn't change with Area
+        area.zoom_in();
+        assert!(copy.contains(0.495, 0.0));
+        assert!(!area.contains(0.495, 0.0));
+    }
+
     #[test]
     fn test_point_to_pixel() {
         let conf = fractal::init_trivial_static_config();
@@ -0,0 +1,40 @@
+use crate::area::Area;
+use crate::data_image::DataImage;
+use image::RgbImage;
+
+/**
+ * Shows the running calculation, e.g. the Application window
+ * Machine paints to its canvas, the canvas doesn't reference Machine back,
+ * so Machine and its owner drop cleanly.
+ */
+pub trait Canvas: Send + Sync {
+    /**
+     * paints only colours from data_image
+     */
+    fn paint_final_calculation_result_colors(&self, data_image: &DataImage);
+
+    /**
+     * paints coloured zoom frame, which is no longer in data_image
+     */
+    fn paint_frame(&self, image: &RgbImage);
+
+    /**
+     * paints one level of progressive calculation, see RenderSink::refinement_level
+     */
+    fn paint_refinement_level(&self, data_image: &DataImage, step: usize);
+
+    /**
+     * paints pixel states, finished pixels with their colour, and optionally a calculation path
+     */
+    fn paint_partial_calculation_result_states(
+        &self,
+        data_image: &DataImage,
+        area: &Area,
+        path: Option<Vec<[f64; 2]>>,
+    );
+
+    /**
+     * paints only pixel states
+     */
+    fn paint_pixel_states(&self, data_image: &DataImage);
+}
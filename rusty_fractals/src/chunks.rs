@@ -0,0 +1,169 @@
+use crate::fractal::FractalConfig;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/**
+ * How is the domain split to chunks, which are calculated in parallel
+ */
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ChunkLayout {
+    // Amount of chunks along each side, 20 for 20 x 20 = 400 chunks
+    // Less chunks are used if image is smaller than that
+    Count(usize),
+    // Size of square chunk in pixels
+    Size(usize),
+}
+
+/**
+ * Chunks of the domain
+ * Chunks in the last column and the last row are smaller,
+ * if image width or height isn't divisible by chunk size.
+ */
+#[derive(Clone, Copy)]
+pub struct Chunks {
+    pub width_x: usize,
+    pub height_y: usize,
+    pub chunk_size_x: usize,
+    pub chunk_size_y: usize,
+    pub chunks_x: usize,
+    pub chunks_y: usize,
+    pub seed: u64,
+}
+
+impl Chunks {
+    pub fn amount(&self) -> usize {
+        self.chunks_x * self.chunks_y
+    }
+
+    /**
+     * Pixel boundaries of chunk xy
+     * returns (x_from, x_to, y_from, y_to), x_to and y_to are exclusive
+     */
+    pub fn boundaries(&self, xy: &[u32; 2]) -> (usize, usize, usize, usize) {
+        let x = xy[0] as usize;
+        let y = xy[1] as usize;
+        (
+            x * self.chunk_size_x,
+            ((x + 1) * self.chunk_size_x).min(self.width_x),
+            y * self.chunk_size_y,
+            ((y + 1) * self.chunk_size_y).min(self.height_y),
+        )
+    }
+
+    /**
+     * Creates x,y pairs of all chunks
+     */
+    pub fn coordinates(&self) -> Vec<[u32; 2]> {
+        let mut coordinates_xy: Vec<[u32; 2]> = Vec::with_capacity(self.amount());
+        for x in 0..self.chunks_x {
+            for y in 0..self.chunks_y {
+                coordinates_xy.push([x as u32, y as u32]);
+            }
+        }
+        coordinates_xy
+    }
+
+    /**
+     * Creates x,y pairs for calculation.
+     * Then shuffles them, it looks better when rendering
+     * The order is the same for the same seed
+     */
+    pub fn shuffled_coordinates(&self) -> Vec<[u32; 2]> {
+        let mut coordinates_xy = self.coordinates();
+        coordinates_xy.shuffle(&mut StdRng::seed_from_u64(self.seed));
+        coordinates_xy
+    }
+}
+
+// size of one chunk along a side of length pixels
+fn chunk_size(layout: ChunkLayout, length: usize) -> usize {
+    match layout {
+        ChunkLayout::Count(count) => length.div_ceil(count.max(1)),
+        ChunkLayout::Size(size) => size.min(length),
+    }
+    .max(1)
+}
+
+pub fn init(config: &FractalConfig) -> Chunks {
+    let chunk_size_x = chunk_size(config.chunk_layout, config.width_x);
+    let chunk_size_y = chunk_size(config.chunk_layout, config.height_y);
+    Chunks {
+        width_x: config.width_x,
+        height_y: config.height_y,
+        chunk_size_x,
+        chunk_size_y,
+        chunks_x: config.width_x.div_ceil(chunk_size_x),
+        chunks_y: config.height_y.div_ceil(chunk_size_y),
+        seed: config.seed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunks::init;
+    use crate::chunks::ChunkLayout::{Count, Size};
+    use crate::fractal::init_trivial_static_config;
+
+    #[test]
+    fn test_init_count() {
+        let mut conf = init_trivial_static_config();
+        conf.width_x = 1366;
+        conf.height_y = 768;
+        conf.chunk_layout = Count(20);
+
+        let chunks = init(&conf);
+
+        assert_eq!(chunks.chunk_size_x, 69);
+        assert_eq!(chunks.chunk_size_y, 39);
+        assert_eq!(chunks.chunks_x, 20);
+        assert_eq!(chunks.chunks_y, 20);
+        assert_eq!(chunks.boundaries(&[19, 19]), (1311, 1366, 741, 768));
+    }
+
+    #[test]
+    fn test_init_size() {
+        let mut conf = init_trivial_static_config();
+        conf.width_x = 4096;
+        conf.height_y = 2160;
+        conf.chunk_layout = Size(256);
+
+        let chunks = init(&conf);
+
+        assert_eq!(chunks.chunks_x, 16);
+        assert_eq!(chunks.chunks_y, 9);
+        assert_eq!(chunks.boundaries(&[15, 8]), (3840, 4096, 2048, 2160));
+    }
+
+    #[test]
+    fn test_coordinates_cover_image() {
+        let mut conf = init_trivial_static_config();
+        conf.width_x = 53;
+        conf.height_y = 7;
+        conf.chunk_layout = Count(20);
+
+        let chunks = init(&conf);
+        let mut covered = vec![vec![0; conf.height_y]; conf.width_x];
+        for xy in chunks.shuffled_coordinates() {
+            let (x_from, x_to, y_from, y_to) = chunks.boundaries(&xy);
+            for column in &mut covered[x_from..x_to] {
+                for c in &mut column[y_from..y_to] {
+                    *c += 1;
+                }
+            }
+        }
+
+        assert_eq!(chunks.amount(), 18 * 7);
+        assert!(covered.iter().flatten().all(|c| *c == 1));
+    }
+
+    #[test]
+    fn test_shuffled_coordinates_seed() {
+        let mut conf = init_trivial_static_config();
+        let first = init(&conf).shuffled_coordinates();
+
+        assert_eq!(init(&conf).shuffled_coordinates(), first);
+        conf.seed = 1;
+        assert_ne!(init(&conf).shuffled_coordinates(), first);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::chunks::ChunkLayout;
+use crate::chunks::ChunkLayout::Count;
+use crate::constants::MAGNIFICATION_WIDTH_RE;
+use crate::fractal::FractalCalculationType::{
+    DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot,
+    StaticSpectralImageEuler,
+};
+use crate::fractal::MandelbrotRendering::Shuffled;
+use crate::fractal::OrbitType::{Finite, Infinite};
+use crate::fractal::{FractalCalculationType, FractalConfig, MandelbrotRendering, OrbitType};
+use crate::palettes::PaletteName;
+use crate::palettes::PaletteName::{BlueToWhiteCircleUp, GrayToBlackCircleDown, Nothing};
+use crate::path_store::PathEncoding::AbsoluteF64;
+use crate::path_store::PathStorage::Memory;
+use crate::path_store::{PathEncoding, PathStorage};
+use crate::resolution_multiplier::ResolutionMultiplier;
+use crate::resolution_multiplier::ResolutionMultiplier::{Single, Square2};
+
+/**
+ * Zoom of the displayed area
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zoom {
+    // width of the complex plane shown by the image
+    Width(f64),
+    // magnification 1 shows MAGNIFICATION_WIDTH_RE
+    Magnification(f64),
+}
+
+/**
+ * Builds FractalConfig from defaults of its FractalCalculationType
+ * Only values different from the defaults need to be set.
+ */
+pub struct ConfigBuilder {
+    config: FractalConfig,
+}
+
+impl ConfigBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.config.name = name.to_string();
+        self
+    }
+
+    pub fn orbits(mut self, orbits: OrbitType) -> Self {
+        self.config.orbits = orbits;
+        self
+    }
+
+    pub fn iterations(mut self, iteration_min: u32, iteration_max: u32) -> Self {
+        self.config.iteration_min = iteration_min;
+        self.config.iteration_max = iteration_max;
+        self
+    }
+
+    pub fn resolution_multiplier(mut self, resolution_multiplier: ResolutionMultiplier) -> Self {
+        self.config.resolution_multiplier = resolution_multiplier;
+        self
+    }
+
+    pub fn mandelbrot_rendering(mut self, mandelbrot_rendering: MandelbrotRendering) -> Self {
+        self.config.mandelbrot_rendering = mandelbrot_rendering;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    pub fn palette(mut self, palette: PaletteName) -> Self {
+        self.config.palette = palette;
+        self
+    }
+
+    pub fn palette_zero(mut self, palette_zero: PaletteName) -> Self {
+        self.config.palette_zero = palette_zero;
+        self
+    }
+
+    /**
+     * Image size in pixels
+     */
+    pub fn size(mut self, width_x: usize, height_y: usize) -> Self {
+        self.config.width_x = width_x;
+        self.config.height_y = height_y;
+        self
+    }
+
+    pub fn chunk_layout(mut self, chunk_layout: ChunkLayout) -> Self {
+        self.config.chunk_layout = chunk_layout;
+        self
+    }
+
+    pub fn center(mut self, center_re: f64, center_im: f64) -> Self {
+        self.config.center_re = center_re;
+        self.config.center_im = center_im;
+        self
+    }
+
+    pub fn zoom(mut self, zoom: Zoom) -> Self {
+        self.config.width_re = match zoom {
+            Zoom::Width(width_re) => width_re,
+            Zoom::Magnification(magnification) => MAGNIFICATION_WIDTH_RE / magnification,
+        };
+        self
+    }
+
+    /**
+     * Zoom adjusts iteration limits by at most this much per frame
+     */
+    pub fn update(mut self, update_min: u32, update_max: u32) -> Self {
+        self.config.update_min = update_min;
+        self.config.update_max = update_max;
+        self
+    }
+
+    /**
+     * Zoom doesn't raise iteration_max above this, configured iteration_max above it is kept
+     */
+    pub fn iteration_max_limit(mut self, iteration_max_limit: u32) -> Self {
+        self.config.iteration_max_limit = iteration_max_limit;
+        self
+    }
+
+    pub fn path_encoding(mut self, path_encoding: PathEncoding) -> Self {
+        self.config.path_encoding = path_encoding;
+        self
+    }
+
+    pub fn path_storage(mut self, path_storage: PathStorage) -> Self {
+        self.config.path_storage = path_storage;
+        self
+    }
+
+    pub fn paths_memory_max_mb(mut self, paths_memory_max_mb: usize) -> Self {
+        self.config.paths_memory_max_mb = paths_memory_max_mb;
+        self
+    }
+
+    pub fn stats_file(mut self, stats_file: &str) -> Self {
+        self.config.stats_file = Some(stats_file.to_string());
+        self
+    }
+
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.config.diagnostics = diagnostics;
+        self
+    }
+
+    pub fn build(self) -> FractalConfig {
+        self.config
+    }
+}
+
+/**
+ * Defaults of the calculation type
+ */
+pub fn init(name: &str, fractal_calc_type: FractalCalculationType) -> ConfigBuilder {
+    let common = FractalConfig {
+        name: name.to_string(),
+        orbits: Finite,
+        fractal_calc_type,
+        iteration_min: 42,
+        iteration_max: 14800,
+        resolution_multiplier: Single,
+        mandelbrot_rendering: Shuffled,
+        seed: 0,
+        palette: BlueToWhiteCircleUp,
+        palette_zero: Nothing,
+        width_x: 1280,
+        height_y: 720,
+        chunk_layout: Count(20),
+        width_re: MAGNIFICATION_WIDTH_RE,
+        center_re: -0.5,
+        center_im: 0.0,
+        update_max: 150,
+        update_min: 0,
+        iteration_max_limit: 100_000,
+        path_encoding: AbsoluteF64,
+        path_storage: Memory,
+        paths_memory_max_mb: 0,
+        stats_file: None,
+        diagnostics: false,
+    };
+    let config = match fractal_calc_type {
+        StaticImageNebula => common,
+        // compact path store of long zooms is chosen by path_encoding() and paths_memory_max_mb()
+        DynamicSequenceNebula => FractalConfig {
+            resolution_multiplier: Square2,
+            ..common
+        },
+        StaticImageMandelbrot | StaticSequenceMandelbrot => FractalConfig {
+            iteration_min: 0,
+            iteration_max: 2500,
+            palette_zero: GrayToBlackCircleDown,
+            ..common
+        },
+        StaticSpectralImageEuler => FractalConfig {
+            orbits: Infinite,
+            iteration_max: 80000,
+            width_x: 1920,
+            height_y: 1080,
+            width_re: 4.0,
+            center_re: 0.0,
+            ..common
+        },
+    };
+    ConfigBuilder { config }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config_builder::init;
+    use crate::config_builder::Zoom::{Magnification, Width};
+    use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticImageMandelbrot};
+    use crate::path_store::PathEncoding::{AbsoluteF64, CenterOffsetF32};
+    use crate::path_store::PathStorage::Disk;
+
+    #[test]
+    fn test_defaults() {
+        let zoom = init("Zoom", DynamicSequenceNebula).build();
+        let mandelbrot = init("Mandelbrot", StaticImageMandelbrot).build();
+
+        assert!(zoom.is_dynamic());
+        assert_eq!(zoom.path_encoding, AbsoluteF64);
+        assert_eq!(zoom.paths_memory_max_mb, 0);
+        assert_eq!(zoom.iteration_max_limit, 100_000);
+        assert!(mandelbrot.is_mandelbrot());
+        assert_eq!(mandelbrot.iteration_min, 0);
+        assert_eq!(mandelbrot.width_re, 3.5);
+    }
+
+    #[test]
+    fn test_setters() {
+        let name = format!("Nebula {}", 7);
+        let config = init("Nebula", DynamicSequenceNebula)
+            .name(&name)
+            .center(-0.1, 0.2)
+            .zoom(Magnification(10.0))
+            .path_storage(Disk("/tmp/paths".to_string()))
+            .path_encoding(CenterOffsetF32)
+            .paths_memory_max_mb(4096)
+            .iteration_max_limit(50_000)
+            .stats_file("stats.csv")
+            .build();
+
+        assert_eq!(config.name, "Nebula 7");
+        assert_eq!((config.center_re, config.center_im), (-0.1, 0.2));
+        assert_eq!(config.width_re, 0.35);
+        assert_eq!(config.path_storage, Disk("/tmp/paths".to_string()));
+        assert_eq!(config.path_encoding, CenterOffsetF32);
+        assert_eq!(config.paths_memory_max_mb, 4096);
+        assert_eq!(config.iteration_max_limit, 50_000);
+        assert_eq!(config.stats_file, Some("stats.csv".to_string()));
+
+        let config = init("Nebula", DynamicSequenceNebula)
+            .zoom(Width(0.5))
+            .build();
+        assert_eq!(config.width_re, 0.5);
+    }
+}
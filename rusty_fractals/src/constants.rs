@@ -10,6 +10,25 @@ pub const PATH: &str = "/Fractals/";
 // to remove noise, ignore pixels with this value or less as 0
 pub const COLOURING_THRESHOLD: u32 = 3;
 
+// Paths of dynamic zoom stored on disk are split into segment files of this many path elements
+// 256 MB for f64 elements
+pub const PATHS_SEGMENT_POINTS: usize = 16 * 1024 * 1024;
+
+// Progressive Mandelbrot rendering starts with one sample per 16 x 16 pixels
+pub const PROGRESSIVE_STEP_MAX: usize = 16;
+
+// Mariani–Silver rectangles this small or smaller are calculated pixel by pixel
+pub const MARIANI_SILVER_MIN_SIZE: usize = 4;
+
+// Estimate of calculation cost calculates this many random origins
+pub const ESTIMATE_SAMPLE_ORIGINS: usize = 1000;
+
+// Print ETA of running calculation at most this often
+pub const ETA_PRINT_INTERVAL_MS: u64 = 1000;
+
+// Magnification 1 shows this width of the complex plane, the whole Mandelbrot set
+pub const MAGNIFICATION_WIDTH_RE: f64 = 3.5;
+
 // Delete paths shorter than this
 // Remembered paths got shorter as some of their elements moved out of displayed Area
 pub const MINIMUM_PATH_LENGTH: u32 = 4;
@@ -21,6 +40,40 @@ pub const CALCULATION_BOUNDARY: u32 = 4;
 // 0.98 is a good choice for 25fps and moderate speed
 pub const ZOOM: f64 = 0.98;
 
+// Zoom skips a failed frame, but stops after this many failed frames in a row
+pub const ZOOM_FAILED_FRAMES_MAX: u32 = 3;
+
+// Web map tiles have this many pixels along each side
+pub const TILE_SIZE: usize = 256;
+
+// Deepest zoom of web map tiles, f64 runs out of precision not much deeper
+pub const TILE_ZOOM_MAX: u32 = 40;
+
+// Nebula tiles are cut from one precomputed grid of this many pixels along each side
+pub const NEBULA_TILE_GRID_WIDTH: usize = 2048;
+
+// Nebula tile grid is calculated with at most this many iterations, so that the first tile doesn't take hours
+pub const NEBULA_TILE_GRID_ITERATION_MAX: u32 = 20_000;
+
+// Tile server drops a connection which doesn't send its request within this many seconds
+pub const TILE_READ_TIMEOUT_SECONDS: u64 = 10;
+
+// Deep Zoom tiles have this many pixels along each side, and overlap their neighbours by DZI_OVERLAP pixels
+pub const DZI_TILE_SIZE: u32 = 254;
+pub const DZI_OVERLAP: u32 = 1;
+
+// Tiled render ranks the inside of Mandelbrot set by quad, rounded to this many steps
+pub const TILED_QUAD_STEPS: u32 = 4096;
+
+// Tiled Nebula keeps this many path elements of each tile in memory before it appends them to the tile spill file
+pub const TILED_SPILL_BUFFER: usize = 65536;
+
+// Coordinator of distributed render hands frame of a silent worker to another worker after this many seconds
+pub const WORKER_TIMEOUT_SECONDS: u64 = 60;
+
+// Worker of distributed render reports progress of its frame this often, see WORKER_TIMEOUT_SECONDS
+pub const WORKER_HEARTBEAT_SECONDS: u64 = 10;
+
 // take result data snapshot for comparison at well coloured frame
 pub const TAKE_MEASURES_AT_FRAME: u32 = 20;
 
@@ -0,0 +1,126 @@
+use crate::info;
+use std::sync::{Condvar, Mutex};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ControlState {
+    Running,
+    // calculation threads wait at the next check
+    Paused,
+    // calculation skips all remaining work and finishes with partial results
+    Cancelled,
+}
+
+/**
+ * Cooperative cancellation, pause and resume of running calculation
+ * Calculation checks it between chunks and between zoom frames
+ * Shared by Machine, Application and any other controller
+ */
+pub struct Control {
+    state: Mutex<ControlState>,
+    changed: Condvar,
+}
+
+impl Control {
+    pub fn state(&self) -> ControlState {
+        *self.state.lock().unwrap()
+    }
+
+    pub fn pause(&self) {
+        self.change(ControlState::Running, ControlState::Paused);
+    }
+
+    pub fn resume(&self) {
+        self.change(ControlState::Paused, ControlState::Running);
+    }
+
+    pub fn toggle_pause(&self) {
+        if self.state() == ControlState::Paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /**
+     * Cancel is final, also wakes up paused calculation
+     */
+    pub fn cancel(&self) {
+        info!("cancel()");
+        *self.state.lock().unwrap() = ControlState::Cancelled;
+        self.changed.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state() == ControlState::Cancelled
+    }
+
+    /**
+     * Blocks while paused
+     * returns true if calculation should continue, false if it was cancelled
+     */
+    pub fn checkpoint(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while *state == ControlState::Paused {
+            state = self.changed.wait(state).unwrap();
+        }
+        *state == ControlState::Running
+    }
+
+    fn change(&self, from: ControlState, to: ControlState) {
+        let mut state = self.state.lock().unwrap();
+        if *state == from {
+            info!("{:?} -> {:?}", from, to);
+            *state = to;
+            self.changed.notify_all();
+        }
+    }
+}
+
+pub fn init() -> Control {
+    Control {
+        state: Mutex::new(ControlState::Running),
+        changed: Condvar::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::control::init;
+    use crate::control::ControlState::{Cancelled, Paused, Running};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pause_resume() {
+        let control = Arc::new(init());
+        control.pause();
+        assert_eq!(control.state(), Paused);
+
+        let c = control.clone();
+        let calculation = thread::spawn(move || c.checkpoint());
+        thread::sleep(Duration::from_millis(20));
+        assert!(!calculation.is_finished());
+
+        control.resume();
+        assert!(calculation.join().unwrap());
+        assert_eq!(control.state(), Running);
+    }
+
+    #[test]
+    fn test_cancel_paused() {
+        let control = Arc::new(init());
+        control.pause();
+
+        let c = control.clone();
+        let calculation = thread::spawn(move || c.checkpoint());
+        control.cancel();
+
+        assert!(!calculation.join().unwrap());
+        // cancel is final
+        control.resume();
+        control.toggle_pause();
+        assert_eq!(control.state(), Cancelled);
+        assert!(!control.checkpoint());
+    }
+}
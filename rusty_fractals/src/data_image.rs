@@ -1,8 +1,14 @@
-use crate::area::Area;
+use crate::area::{Area, AreaDataCopy};
+use crate::chunks;
+use crate::chunks::Chunks;
 use crate::constants::{MINIMUM_PATH_LENGTH, NEIGHBOURS};
 use crate::data_px;
 use crate::data_px::DataPx;
+use crate::diagnostics;
+use crate::diagnostics::Diagnostics;
 use crate::fractal::FractalConfig;
+use crate::path_store;
+use crate::path_store::PathStore;
 use crate::pixel_states::DomainElementState::{
     ActiveNew, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort,
     HibernatedDeepBlack,
@@ -13,8 +19,10 @@ use crate::pixel_states::{
 };
 use crate::resolution_multiplier::ResolutionMultiplier;
 use crate::resolution_multiplier::ResolutionMultiplier::Square2;
+use crate::{debug, error, warn};
 use image::Rgb;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::io;
+use std::sync::{Mutex, MutexGuard};
 use ResolutionMultiplier::{Single, Square101, Square11, Square3, Square5, Square51, Square9};
 
 pub struct DataImage {
@@ -22,21 +30,26 @@ pub struct DataImage {
     pub height_y: usize,
     pub is_dynamic: bool,
     pub is_mandelbrot: bool,
+    // domain split to chunks, shared by calculation and Stats
+    pub chunks: Chunks,
     /*
      * static data for image
      */
     pub pixels: Vec<Vec<Mutex<Option<DataPx>>>>,
-//    pub pixels3: Vec<Vec<Mutex<Option<DataPx3>>>>,
+    //    pub pixels3: Vec<Vec<Mutex<Option<DataPx3>>>>,
     /*
      * dynamic data for zoom video
      * As zoom progress, points [re,im] are projected to new pixels [px,py] until they migrate out of the tiny area.
      * Elements outside the tiny result_area are removed. Very short (calculation) paths are also removed.
      * All elements on paths are already inside result_area because they are filtered like that during the calculation.
+     * Paths are stored compactly in one arena, see PathStore.
      */
-    pub paths: Arc<Mutex<Vec<Vec<[f64; 2]>>>>,
+    pub paths: Mutex<PathStore>,
     // show one patch during calculation with pixel wrap
     // only for static image calculation, otherwise just get the longest path
     pub show_path: Mutex<Vec<[f64; 2]>>,
+    // per pixel cost and path yield, only if configured
+    pub diagnostics: Option<Diagnostics>,
 }
 
 impl DataImage {
@@ -65,17 +78,16 @@ impl DataImage {
      * retrieve the longest path for dynamic sequence calculation
      */
     pub fn the_longest_path_copy(&self) -> Option<Vec<[f64; 2]>> {
-        println!("the_longest_path_copy()");
+        debug!("the_longest_path_copy()");
 
-        let paths_lock = self.paths.lock().unwrap();
-        paths_lock.iter().max_by_key(|v| v.len()).cloned()
+        self.paths.lock().unwrap().the_longest_path_copy()
     }
 
     /**
      * retrieve saved path for static image wrap calculation
      */
     pub fn a_saved_path(&self) -> Option<Vec<[f64; 2]>> {
-        println!("a_saved_path");
+        debug!("a_saved_path");
 
         let ret = Some(self.show_path.lock().unwrap().clone());
 
@@ -84,52 +96,57 @@ impl DataImage {
         ret
     }
 
-    pub fn translate_one_path_to_point_grid_now(&self, path: Vec<[f64; 2]>, area: &Area) {
+    pub fn translate_one_path_to_point_grid_now(&self, path: Vec<[f64; 2]>, area: &AreaDataCopy) {
         for [re, im] in path {
             let (x, y) = area.point_to_pixel(re, im);
             self.add(x, y);
         }
     }
 
-    pub fn translate_all_paths_to_point_grid(&self, area: &Area) {
-        println!("translate_all_paths_to_point_grid()");
-        let all = self.paths.lock().unwrap().to_owned();
-        for path in all {
-            for [re, im] in path {
-                let (x, y) = area.point_to_pixel(re, im);
-                self.add(x, y);
-            }
-        }
+    pub fn translate_all_paths_to_point_grid(&self, area: &AreaDataCopy) {
+        debug!("translate_all_paths_to_point_grid()");
+        self.paths.lock().unwrap().par_for_each_point(|re, im| {
+            let (x, y) = area.point_to_pixel(re, im);
+            self.add(x, y);
+        });
     }
 
     /**
      * save any path
      * verify path length before saving
      */
-    pub fn save_path(&self, path: Vec<[f64; 2]>) {
-        self.paths.lock().unwrap().push(path);
-    }
-
-    pub fn remove_elements_outside(&self, area: &Area) {
-        println!("remove_elements_outside()");
-        // all paths
-        let all = self.paths.lock().unwrap().to_owned();
-
-        // remove elements outside Area
-        for i in 0..all.len() {
-            self.paths
-                .lock()
-                .unwrap()
-                .get_mut(i)
-                .unwrap()
-                .retain(|el| area.contains(el[0], el[1]))
+    pub fn save_path(&self, path: Vec<[f64; 2]>) -> io::Result<()> {
+        let full = self.paths.lock().unwrap().append(&path);
+        // full arena is sealed or evicted while the other calculation threads keep saving their paths
+        match full {
+            Some(full) => {
+                let finished = full.finish();
+                self.paths.lock().unwrap().attach(finished)
+            }
+            None => Ok(()),
         }
+    }
+
+    /**
+     * returns the amount of removed paths
+     */
+    pub fn remove_elements_outside(&self, area: &AreaDataCopy) -> io::Result<usize> {
+        debug!("remove_elements_outside()");
+        let mut paths = self.paths.lock().unwrap();
+        let paths_before = paths.paths_amount();
+
+        // remove elements outside Area and short paths
+        paths.retain_inside(area, MINIMUM_PATH_LENGTH)?;
 
-        // remove short paths
-        self.paths
-            .lock()
-            .unwrap()
-            .retain(|path| path.len() as u32 > MINIMUM_PATH_LENGTH);
+        debug!("paths:         {}", paths.paths_amount());
+        debug!("paths points:  {}", paths.points_amount());
+        debug!("paths memory:  {} MB", paths.memory_bytes() / (1024 * 1024));
+        debug!("paths disk:    {} MB", paths.disk_bytes() / (1024 * 1024));
+        debug!(
+            "evicted paths: {} ({} points)",
+            paths.evicted_paths, paths.evicted_points
+        );
+        Ok(paths_before - paths.paths_amount())
     }
 
     pub fn clear_all_px_data(&self) {
@@ -152,22 +169,19 @@ impl DataImage {
     pub fn mo_px_at(&self, x: usize, y: usize) -> MutexGuard<Option<DataPx>> {
         if let Some(row) = self.pixels.get(x) {
             if let Some(cell) = row.get(y) {
-                match cell.lock() {
-                    Ok(guard) => guard, // Return the lock if successful
-                    Err(_) => {
-                        // Poisoned lock
-                        println!("Failed to acquire lock for pixel at ({}, {}). The mutex might be poisoned.", x, y);
-                        panic!("Failed to acquire lock for pixel at ({}, {}).", x, y);
-                    }
-                }
+                // pixel of a chunk which panicked is still usable, the panic is reported by its chunk
+                cell.lock().unwrap_or_else(|poisoned| {
+                    warn!("Pixel lock at ({}, {}) poisoned, recovered", x, y);
+                    poisoned.into_inner()
+                })
             } else {
                 // y index out of bounds
-                println!("Failed to get pixel at column {} in row {}.", y, x);
+                error!("Failed to get pixel at column {} in row {}.", y, x);
                 panic!("Pixel column index {} out of bounds.", y);
             }
         } else {
             // x index out of bounds
-            println!("Failed to get row {} in pixels.", x);
+            error!("Failed to get row {} in pixels.", x);
             panic!("Pixel row index {} out of bounds.", x);
         }
     }
@@ -203,7 +217,7 @@ impl DataImage {
                 op.replace(px);
             }
             Err(e) => {
-                println!("move_px_to_new_position(): {}", e);
+                error!("move_px_to_new_position(): {}", e);
             }
         }
     }
@@ -291,6 +305,23 @@ impl DataImage {
         }
     }
 
+    // copy already calculated result, value is pixel value, not iterator
+    pub fn fill_pixel_mandelbrot(
+        &self,
+        x: usize,
+        y: usize,
+        value: u32,
+        quad: f64,
+        state: DomainElementState,
+    ) {
+        let mut mo_px = self.mo_px_at(x, y);
+        let p = mo_px.as_mut().unwrap();
+        p.quad = quad;
+        p.quid = 1.0 / quad;
+        p.state = state;
+        p.value = value;
+    }
+
     // for Nebula like fractals
     pub fn set_pixel_state(&self, x: usize, y: usize, state: DomainElementState) {
         let mut mo_px = self.mo_px_at(x, y);
@@ -301,7 +332,7 @@ impl DataImage {
     }
 
     pub fn recalculate_pixels_states(&self) {
-        println!("recalculate_pixels_states()");
+        debug!("recalculate_pixels_states()");
         for y in 0..self.height_y {
             for x in 0..self.width_x {
                 let mut mo_px = self.mo_px_at(x, y);
@@ -332,31 +363,17 @@ impl DataImage {
     }
 
     pub fn best_four_chunks_value(&self) -> u32 {
-        println!("best_four_chunks_value()");
-        let chunk_size_x = self.width_x / 20;
-        let chunk_size_y = self.height_y / 20;
+        debug!("best_four_chunks_value()");
         let mut values: Vec<u32> = Vec::new();
-        for x in 0..20 {
-            for y in 0..20 {
-                values.push(self.chunk_value(
-                    x * chunk_size_x,
-                    (x + 1) * chunk_size_x,
-                    y * chunk_size_y,
-                    (y + 1) * chunk_size_y,
-                ));
-            }
+        for xy in self.chunks.coordinates() {
+            let (x_from, x_to, y_from, y_to) = self.chunks.boundaries(&xy);
+            values.push(self.chunk_value(x_from, x_to, y_from, y_to));
         }
         values.sort_by(|first, second| second.cmp(first));
 
-        let mut sum = 0;
-        for i in 0..4 {
-            let v = values.get(i);
-            match v {
-                Some(v) => sum += v,
-                None => panic!(),
-            }
-        }
-        println!("best_four_chunks_value() sum: {}", sum);
+        // less than four chunks for very small images
+        let sum = values.iter().take(4).sum();
+        debug!("best_four_chunks_value() sum: {}", sum);
         sum
     }
 
@@ -402,7 +419,7 @@ impl DataImage {
         ret
     }
 
-    pub fn move_to_new_position(&self, x: usize, y: usize, area: &Area) {
+    pub fn move_to_new_position(&self, x: usize, y: usize, area: &AreaDataCopy) {
         let mut mo_px = self.mo_px_at(x, y);
         let p = mo_px.as_mut().unwrap();
         // There was already zoom in, the new area is smaller
@@ -460,24 +477,49 @@ impl DataImage {
     pub fn is_dynamic(&self) -> bool {
         self.is_dynamic
     }
+
+    pub fn clear_diagnostics(&self) {
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.clear();
+        }
+    }
+
+    pub fn record_diagnostics(&self, x: usize, y: usize, iterations: u32, path_points: u32) {
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.record(x, y, iterations, path_points);
+        }
+    }
 }
 
 pub fn init(conf: &FractalConfig, area: &Area) -> DataImage {
     let wx = area.data.lock().unwrap().width_x;
     let hy = area.data.lock().unwrap().height_y;
+    let area_copy = area.copy_data();
     DataImage {
         width_x: wx,
         height_y: hy,
         is_dynamic: conf.is_dynamic(),
         is_mandelbrot: conf.is_mandelbrot(),
+        chunks: chunks::init(conf),
         pixels: init_domain(area),
-        paths: Arc::new(Mutex::new(Vec::new())),
+        paths: Mutex::new(path_store::init(
+            conf.path_encoding,
+            &conf.path_storage,
+            conf.paths_memory_max_mb,
+            area_copy.center_re,
+            area_copy.center_im,
+        )),
         show_path: Mutex::new(vec![]),
+        diagnostics: if conf.diagnostics {
+            Some(diagnostics::init(wx, hy))
+        } else {
+            None
+        },
     }
 }
 
 fn init_domain(area: &Area) -> Vec<Vec<Mutex<Option<DataPx>>>> {
-    println!("init_domain()");
+    debug!("init_domain()");
     let mut vx = Vec::new();
 
     let wx = area.data.lock().unwrap().width_x;
@@ -540,7 +582,7 @@ mod tests {
     use crate::area::Area;
     use std::sync::LazyLock;
 
-    static CONF: FractalConfig = init_trivial_dynamic_config();
+    static CONF: LazyLock<FractalConfig> = LazyLock::new(init_trivial_dynamic_config);
     static AREA: LazyLock<Area> = LazyLock::new(|| area::init(&CONF));
 
     fn element_at(w: &Vec<[f64; 2]>, index: usize) -> (f64, f64) {
@@ -562,9 +604,11 @@ mod tests {
     fn test_the_longest_path_copy() {
         let dynamic = init(&CONF, &AREA);
 
-        dynamic.save_path(vec![[0.0, 0.0], [0.0, 0.0]]);
-        dynamic.save_path(vec![[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]]);
-        dynamic.save_path(vec![[0.0, 0.0], [0.0, 0.0]]);
+        dynamic.save_path(vec![[0.0, 0.0], [0.0, 0.0]]).unwrap();
+        dynamic
+            .save_path(vec![[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]])
+            .unwrap();
+        dynamic.save_path(vec![[0.0, 0.0], [0.0, 0.0]]).unwrap();
 
         let longest = dynamic.the_longest_path_copy();
 
@@ -593,18 +637,16 @@ mod tests {
         // full path  should be removed
         let short = vec![[0.0, 0.0], [0.0, 0.0]];
 
-        dynamic.save_path(path);
-        dynamic.save_path(short);
+        dynamic.save_path(path).unwrap();
+        dynamic.save_path(short).unwrap();
 
         // execute test
-        dynamic.remove_elements_outside(&AREA);
+        dynamic.remove_elements_outside(&AREA.copy_data()).unwrap();
 
         // get test data
         let result_all = dynamic.paths.lock().unwrap();
-        assert_eq!(result_all.len(), 1);
-
-        let remaining_path = result_all.get(0).unwrap();
-        assert_eq!(remaining_path.len(), 8);
+        assert_eq!(result_all.paths_amount(), 1);
+        assert_eq!(result_all.path_length(0), 8);
     }
 
     #[test]
@@ -618,6 +660,22 @@ mod tests {
         assert_eq!(px2.unwrap().state, ActiveNew);
     }
 
+    #[test]
+    fn test_best_four_chunks_value_edge_chunk() {
+        let mut conf = init_trivial_dynamic_config();
+        conf.width_x = 23;
+        conf.height_y = 21;
+        let area = area::init(&conf);
+        let data = init(&conf, &area);
+
+        // the last pixel is in the smaller edge chunk
+        data.add(22, 20);
+        data.add(22, 20);
+        data.add(0, 0);
+
+        assert_eq!(data.best_four_chunks_value(), 3);
+    }
+
     #[test]
     fn test_wrap_3() {
         // prepare test
@@ -0,0 +1,189 @@
+use crate::data_image::{colour_for_state, DataImage};
+use crate::info;
+use image::{Rgb, RgbImage};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/**
+ * Per pixel diagnostics of the frame being calculated, recorded when FractalConfig.diagnostics is on
+ * Wrap origins are recorded to the pixel they belong to.
+ */
+pub struct Diagnostics {
+    width_x: usize,
+    height_y: usize,
+    // iterations calculated for origins of the pixel, good paths are calculated twice
+    cost: Vec<AtomicU64>,
+    // path elements within area, contributed to the Nebula by origins of the pixel
+    yields: Vec<AtomicU64>,
+}
+
+impl Diagnostics {
+    pub fn record(&self, x: usize, y: usize, iterations: u32, path_points: u32) {
+        let i = y * self.width_x + x;
+        self.cost[i].fetch_add(iterations as u64, Ordering::Relaxed);
+        self.yields[i].fetch_add(path_points as u64, Ordering::Relaxed);
+    }
+
+    pub fn cost_at(&self, x: usize, y: usize) -> u64 {
+        self.cost[y * self.width_x + x].load(Ordering::Relaxed)
+    }
+
+    pub fn yield_at(&self, x: usize, y: usize) -> u64 {
+        self.yields[y * self.width_x + x].load(Ordering::Relaxed)
+    }
+
+    /**
+     * Calculation of new frame starts
+     */
+    pub fn clear(&self) {
+        for v in self.cost.iter().chain(self.yields.iter()) {
+            v.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn cost_image(&self) -> RgbImage {
+        heat_image(self.width_x, self.height_y, |x, y| self.cost_at(x, y))
+    }
+
+    pub fn yield_image(&self) -> RgbImage {
+        heat_image(self.width_x, self.height_y, |x, y| self.yield_at(x, y))
+    }
+}
+
+pub fn init(width_x: usize, height_y: usize) -> Diagnostics {
+    Diagnostics {
+        width_x,
+        height_y,
+        cost: (0..width_x * height_y).map(|_| AtomicU64::new(0)).collect(),
+        yields: (0..width_x * height_y).map(|_| AtomicU64::new(0)).collect(),
+    }
+}
+
+/**
+ * DomainElementState of each pixel, in the colours used to paint calculation progress
+ */
+pub fn state_image(data_image: &DataImage) -> RgbImage {
+    let mut image = RgbImage::new(data_image.width_x as u32, data_image.height_y as u32);
+    for (x, y, px) in image.enumerate_pixels_mut() {
+        *px = colour_for_state(data_image.state_at(x as usize, y as usize));
+    }
+    image
+}
+
+/**
+ * Saves cost, yield and state images of the frame, if diagnostics are recorded
+ * name_0001_cost.png, name_0001_yield.png, name_0001_state.png
+ */
+pub fn save(data_image: &DataImage, name: &str, frame: u32) -> io::Result<()> {
+    if let Some(diagnostics) = &data_image.diagnostics {
+        let prefix = format!("{}_{:04}", file_name(name), frame);
+        info!("save diagnostics {}_*.png", prefix);
+        let images = [
+            ("cost", diagnostics.cost_image()),
+            ("yield", diagnostics.yield_image()),
+            ("state", state_image(data_image)),
+        ];
+        for (kind, image) in images {
+            image
+                .save(format!("{}_{}.png", prefix, kind))
+                .map_err(io::Error::other)?;
+        }
+    }
+    Ok(())
+}
+
+// "Fat Star" -> "fat_star"
+fn file_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/**
+ * Logarithmic scale, the maximum value is white, zero is black
+ */
+fn heat_image<V>(width_x: usize, height_y: usize, value_at: V) -> RgbImage
+where
+    V: Fn(usize, usize) -> u64,
+{
+    let max = (0..height_y)
+        .flat_map(|y| (0..width_x).map(move |x| (x, y)))
+        .map(|(x, y)| value_at(x, y))
+        .max()
+        .unwrap_or(0);
+    let log_max = (max as f64).ln_1p();
+    let mut image = RgbImage::new(width_x as u32, height_y as u32);
+    for (x, y, px) in image.enumerate_pixels_mut() {
+        let value = value_at(x as usize, y as usize);
+        let t = if log_max > 0.0 {
+            (value as f64).ln_1p() / log_max
+        } else {
+            0.0
+        };
+        *px = heat(t);
+    }
+    image
+}
+
+/**
+ * False colour for t in 0..1, black, blue, red, yellow, white
+ */
+fn heat(t: f64) -> Rgb<u8> {
+    const STOPS: [[f64; 3]; 5] = [
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 255.0],
+        [255.0, 0.0, 0.0],
+        [255.0, 255.0, 0.0],
+        [255.0, 255.0, 255.0],
+    ];
+    let t = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let i = (t as usize).min(STOPS.len() - 2);
+    let f = t - i as f64;
+    let [a, b] = [STOPS[i], STOPS[i + 1]];
+    Rgb([
+        (a[0] + (b[0] - a[0]) * f).round() as u8,
+        (a[1] + (b[1] - a[1]) * f).round() as u8,
+        (a[2] + (b[2] - a[2]) * f).round() as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{file_name, heat, init};
+    use image::Rgb;
+
+    #[test]
+    fn test_record() {
+        let diagnostics = init(4, 3);
+        diagnostics.record(3, 2, 100, 0);
+        diagnostics.record(3, 2, 50, 20);
+        diagnostics.record(0, 1, 10, 0);
+
+        assert_eq!(diagnostics.cost_at(3, 2), 150);
+        assert_eq!(diagnostics.yield_at(3, 2), 20);
+        assert_eq!(diagnostics.yield_at(0, 1), 0);
+
+        let cost = diagnostics.cost_image();
+        assert_eq!(cost.dimensions(), (4, 3));
+        assert_eq!(*cost.get_pixel(3, 2), Rgb([255, 255, 255]));
+        assert_eq!(*cost.get_pixel(1, 1), Rgb([0, 0, 0]));
+
+        diagnostics.clear();
+        assert_eq!(diagnostics.cost_at(3, 2), 0);
+    }
+
+    #[test]
+    fn test_heat() {
+        assert_eq!(heat(0.0), Rgb([0, 0, 0]));
+        assert_eq!(heat(0.25), Rgb([0, 0, 255]));
+        assert_eq!(heat(0.5), Rgb([255, 0, 0]));
+        assert_eq!(heat(1.0), Rgb([255, 255, 255]));
+        assert_eq!(heat(7.0), Rgb([255, 255, 255]));
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("Fat Star"), "fat_star");
+    }
+}
@@ -0,0 +1,581 @@
+use crate::constants::{WORKER_HEARTBEAT_SECONDS, WORKER_TIMEOUT_SECONDS, ZOOM};
+use crate::error::FractalError::{Config, Panicked, Worker};
+use crate::error::Result;
+use crate::formulas::Formula;
+use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticSequenceMandelbrot};
+use crate::fractal::FractalConfig;
+use crate::progress;
+use crate::progress::ProgressEvent;
+use crate::{debug, error, info, warn};
+use image::ImageFormat;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// how often the coordinator checks for new workers
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/**
+ * Zoom video of independent frames rendered by worker processes on the same machine
+ * Coordinator hands out frames to the workers which connect to it,
+ * and saves the frames they send back as {frames_dir}/{frame:05}.png
+ * Frames are independent, frame N is the area of the config zoomed in N - 1 times, with the configured iteration limits.
+ * DynamicSequenceNebula zooms, e.g. nebula_side and nebula_top, aren't covered. Their frames build on paths
+ * of the previous frames, so they need workers which calculate chunks of origins and send back paths
+ * or accumulation grids to the coordinator's zoom. That mode is left to a follow-up request.
+ * StaticSequenceMandelbrot frames don't adjust iteration limits as the local zoom does.
+ * Worker reports progress of its frame every WORKER_HEARTBEAT_SECONDS, however long the frame takes.
+ * Frame of a lost worker, disconnected or silent for longer than worker_timeout, is handed out again.
+ * Frames already saved in frames_dir aren't rendered again, a stopped video continues where it stopped.
+ *
+ * Protocol, a line each, PNG of the frame follows the RESULT line
+ * worker:      WORKER <formula>
+ * coordinator: FRAME <frame> <width_x> <height_y> <width_re> <center_re> <center_im> | DONE | ERROR <message>
+ * worker:      PROGRESS <frame> <last progress event as JSON, or {}>, repeated while rendering
+ * worker:      RESULT <frame> <png bytes> | FAILED <frame> <message>
+ */
+pub struct Coordinator {
+    name: String,
+    config: FractalConfig,
+    frames_dir: PathBuf,
+    pub worker_timeout: Duration,
+    jobs: Mutex<Jobs>,
+    // signals a frame finished or returned for another worker
+    jobs_changed: Condvar,
+}
+
+struct Jobs {
+    pending: VecDeque<u32>,
+    running: usize,
+    failed: Vec<u32>,
+    // connected workers, coordinator waits until they all get DONE
+    workers: usize,
+}
+
+/**
+ * Area of one frame
+ */
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    pub frame: u32,
+    pub width_x: usize,
+    pub height_y: usize,
+    pub width_re: f64,
+    pub center_re: f64,
+    pub center_im: f64,
+}
+
+impl Frame {
+    /**
+     * Config of the formula with area of this frame, without statistics and diagnostics
+     */
+    pub fn config(&self, config: &FractalConfig) -> FractalConfig {
+        let mut frame_config = config.clone();
+        frame_config.width_x = self.width_x;
+        frame_config.height_y = self.height_y;
+        frame_config.width_re = self.width_re;
+        frame_config.center_re = self.center_re;
+        frame_config.center_im = self.center_im;
+        frame_config.stats_file = None;
+        frame_config.diagnostics = false;
+        frame_config
+    }
+}
+
+/**
+ * Coordinator of frames 1 to frames of the formula with the config
+ */
+pub fn init(
+    name: &str,
+    config: &FractalConfig,
+    frames: u32,
+    frames_dir: &Path,
+) -> Result<Coordinator> {
+    match config.fractal_calc_type {
+        DynamicSequenceNebula => {
+            return Err(Config(format!(
+                "{} is DynamicSequenceNebula, its frames depend on the previous frames, distributed rendering of it isn't supported yet, zoom it locally",
+                name
+            )));
+        }
+        StaticSequenceMandelbrot => {
+            warn!(
+                "init() {} frames are rendered with iteration limits {} to {}, without adjustment of local zoom",
+                name, config.iteration_min, config.iteration_max
+            );
+        }
+        _ => {}
+    }
+    let pending = (1..=frames)
+        .filter(|frame| !frame_file(frames_dir, *frame).exists())
+        .collect::<VecDeque<_>>();
+    info!(
+        "init() {} frames of {}, {} already saved",
+        frames,
+        name,
+        frames as usize - pending.len()
+    );
+    Ok(Coordinator {
+        name: name.to_string(),
+        config: config.clone(),
+        frames_dir: frames_dir.to_path_buf(),
+        worker_timeout: Duration::from_secs(WORKER_TIMEOUT_SECONDS),
+        jobs: Mutex::new(Jobs {
+            pending,
+            running: 0,
+            failed: Vec::new(),
+            workers: 0,
+        }),
+        jobs_changed: Condvar::new(),
+    })
+}
+
+/**
+ * Only local workers can connect, port 0 picks a free port
+ */
+pub fn bind(port: u16) -> io::Result<TcpListener> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+}
+
+impl Coordinator {
+    /**
+     * Accepts workers until all frames are finished, each worker in its own thread
+     * Frames which workers failed to render are returned as Worker error.
+     */
+    pub fn run(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        info!("run() {}", listener.local_addr()?);
+        fs::create_dir_all(&self.frames_dir)?;
+        listener.set_nonblocking(true)?;
+        while !self.is_finished() {
+            match listener.accept() {
+                Ok((stream, address)) => {
+                    info!("worker {} connected", address);
+                    stream.set_nonblocking(false)?;
+                    let coordinator = self.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = coordinator.coordinate(stream) {
+                            warn!("worker {} lost: {}", address, e);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        // workers which connected meanwhile get DONE, instead of closed connection
+        while let Ok((stream, address)) = listener.accept() {
+            stream.set_nonblocking(false)?;
+            if let Err(e) = self.coordinate(stream) {
+                warn!("worker {} lost: {}", address, e);
+            }
+        }
+        let failed = &self.jobs.lock().unwrap().failed;
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(Worker(format!("frames {:?} failed", failed)))
+        }
+    }
+
+    /**
+     * Area of the frame, zoomed in frame - 1 times
+     */
+    pub fn frame(&self, frame: u32) -> Frame {
+        Frame {
+            frame,
+            width_x: self.config.width_x,
+            height_y: self.config.height_y,
+            width_re: self.config.width_re * ZOOM.powi(frame as i32 - 1),
+            center_re: self.config.center_re,
+            center_im: self.config.center_im,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.pending.is_empty() && jobs.running == 0 && jobs.workers == 0
+    }
+
+    // hands out frames to one worker, until all frames are finished
+    fn coordinate(&self, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(self.worker_timeout))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let hello = read_line(&mut reader)?;
+        if hello.strip_prefix("WORKER ") != Some(self.name.as_str()) {
+            writeln!(writer, "ERROR coordinator renders {}", self.name)?;
+            return Err(Worker(format!(
+                "expected WORKER {}, got {}",
+                self.name, hello
+            )));
+        }
+        self.jobs.lock().unwrap().workers += 1;
+        let result = self.hand_out(&mut reader, &mut writer);
+        self.jobs.lock().unwrap().workers -= 1;
+        result
+    }
+
+    fn hand_out(&self, reader: &mut BufReader<TcpStream>, writer: &mut TcpStream) -> Result<()> {
+        while let Some(frame) = self.next_frame() {
+            if let Err(e) = self.delegate(frame, reader, writer) {
+                self.finish(frame, |jobs| jobs.pending.push_front(frame));
+                return Err(e);
+            }
+        }
+        writeln!(writer, "DONE")?;
+        Ok(())
+    }
+
+    // waits for a frame, also for frames of workers which may still get lost
+    fn next_frame(&self) -> Option<u32> {
+        let mut jobs = self.jobs.lock().unwrap();
+        loop {
+            if let Some(frame) = jobs.pending.pop_front() {
+                jobs.running += 1;
+                return Some(frame);
+            }
+            if jobs.running == 0 {
+                return None;
+            }
+            jobs = self.jobs_changed.wait(jobs).unwrap();
+        }
+    }
+
+    fn finish(&self, frame: u32, update: impl FnOnce(&mut Jobs)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.running -= 1;
+        update(&mut jobs);
+        debug!(
+            "frame {} done, {} pending, {} running",
+            frame,
+            jobs.pending.len(),
+            jobs.running
+        );
+        self.jobs_changed.notify_all();
+    }
+
+    fn delegate(
+        &self,
+        frame: u32,
+        reader: &mut BufReader<TcpStream>,
+        writer: &mut TcpStream,
+    ) -> Result<()> {
+        writeln!(writer, "{}", frame_line(&self.frame(frame)))?;
+        // read timeout applies to each line, progress keeps the worker alive
+        let mut line = read_line(reader)?;
+        while let Some(progress) = line.strip_prefix("PROGRESS ") {
+            debug!("progress {}", progress);
+            line = read_line(reader)?;
+        }
+        let parts: Vec<&str> = line.splitn(3, ' ').collect();
+        match parts[..] {
+            ["RESULT", f, length] if f == frame.to_string() => {
+                let length = length
+                    .parse()
+                    .map_err(|_| Worker(format!("invalid length {}", length)))?;
+                let mut png = vec![0u8; length];
+                reader.read_exact(&mut png)?;
+                image::load_from_memory_with_format(&png, ImageFormat::Png)
+                    .map_err(|e| Worker(format!("invalid frame {}: {}", frame, e)))?;
+                write_file(&frame_file(&self.frames_dir, frame), &png)?;
+                self.finish(frame, |_| {});
+                Ok(())
+            }
+            ["FAILED", f, message] if f == frame.to_string() => {
+                error!("frame {} failed: {}", frame, message);
+                self.finish(frame, |jobs| jobs.failed.push(frame));
+                Ok(())
+            }
+            _ => Err(Worker(format!("unexpected {}", line))),
+        }
+    }
+}
+
+/**
+ * Renders frames of the formula handed out by the coordinator on localhost port, until it has no more frames
+ * returns amount of rendered frames
+ */
+pub fn work(formula: &Formula, port: u16) -> Result<u32> {
+    work_with_heartbeat(formula, port, Duration::from_secs(WORKER_HEARTBEAT_SECONDS))
+}
+
+fn work_with_heartbeat(formula: &Formula, port: u16, heartbeat: Duration) -> Result<u32> {
+    let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    writeln!(writer, "WORKER {}", formula.name)?;
+    let mut rendered = 0;
+    loop {
+        let line = read_line(&mut reader)?;
+        if line == "DONE" {
+            info!("work() rendered {} frames", rendered);
+            return Ok(rendered);
+        }
+        if let Some(message) = line.strip_prefix("ERROR ") {
+            return Err(Worker(message.to_string()));
+        }
+        let frame =
+            parse_frame_line(&line).ok_or_else(|| Worker(format!("unexpected {}", line)))?;
+        info!("work() frame {}", frame.frame);
+        match render_frame(formula, &frame, &mut writer, heartbeat)? {
+            Ok(png) => {
+                writeln!(writer, "RESULT {} {}", frame.frame, png.len())?;
+                writer.write_all(&png)?;
+                rendered += 1;
+            }
+            Err(e) => {
+                let message = e.to_string().replace('\n', " ");
+                writeln!(writer, "FAILED {} {}", frame.frame, message)?;
+            }
+        }
+    }
+}
+
+/**
+ * Renders the frame to PNG in another thread, meanwhile reports its progress every heartbeat
+ * Error of the render is returned in Ok, error of the connection in Err.
+ */
+fn render_frame(
+    formula: &Formula,
+    frame: &Frame,
+    writer: &mut impl Write,
+    heartbeat: Duration,
+) -> io::Result<Result<Vec<u8>>> {
+    let config = frame.config(&formula.config);
+    let (sender, events) = channel::<ProgressEvent>();
+    let listener = progress::channel_listener(sender);
+    thread::scope(|scope| {
+        let render = scope.spawn(move || {
+            formula
+                .render(&config, Some(listener))
+                .and_then(|render| encode_png(&render.image))
+        });
+        let mut last: Option<ProgressEvent> = None;
+        let mut reported = Instant::now();
+        // events end when the render drops the listener
+        loop {
+            match events.recv_timeout(heartbeat) {
+                Ok(event) => last = Some(event),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if reported.elapsed() >= heartbeat {
+                let event = last.as_ref().map_or("{}".to_string(), |e| e.to_json());
+                writeln!(writer, "PROGRESS {} {}", frame.frame, event)?;
+                reported = Instant::now();
+            }
+        }
+        Ok(render
+            .join()
+            .unwrap_or_else(|_| Err(Panicked(format!("frame {}", frame.frame)))))
+    })
+}
+
+fn frame_file(frames_dir: &Path, frame: u32) -> PathBuf {
+    frames_dir.join(format!("{:05}.png", frame))
+}
+
+// f64 is written by Display with all digits needed to read it back exactly
+fn frame_line(frame: &Frame) -> String {
+    format!(
+        "FRAME {} {} {} {} {} {}",
+        frame.frame,
+        frame.width_x,
+        frame.height_y,
+        frame.width_re,
+        frame.center_re,
+        frame.center_im
+    )
+}
+
+fn parse_frame_line(line: &str) -> Option<Frame> {
+    let parts: Vec<&str> = line.split(' ').collect();
+    match parts[..] {
+        ["FRAME", frame, width_x, height_y, width_re, center_re, center_im] => Some(Frame {
+            frame: frame.parse().ok()?,
+            width_x: width_x.parse().ok()?,
+            height_y: height_y.parse().ok()?,
+            width_re: width_re.parse().ok()?,
+            center_re: center_re.parse().ok()?,
+            center_im: center_im.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+// closed connection is an error, the protocol always ends by DONE or ERROR
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn encode_png(image: &image::RgbImage) -> Result<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(io::Error::other)?;
+    Ok(png.into_inner())
+}
+
+// written under temporary name first, a frame file always holds a whole frame
+fn write_file(file: &Path, data: &[u8]) -> io::Result<()> {
+    let temporary = file.with_extension("png.tmp");
+    fs::write(&temporary, data)?;
+    fs::rename(&temporary, file)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config_builder;
+    use crate::config_builder::Zoom::Width;
+    use crate::distributed::{
+        bind, frame_file, frame_line, init, parse_frame_line, read_line, work, work_with_heartbeat,
+    };
+    use crate::error::FractalError;
+    use crate::formulas::formula;
+    use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticImageMandelbrot};
+    use crate::fractal::{init_trivial_fractal, FractalMath};
+    use crate::mem::Mem;
+    use crate::palettes::PaletteName::{BlueToWhiteCircleUp, GrayToBlackCircleDown};
+    use std::io::{BufReader, Write};
+    use std::net::{Ipv4Addr, TcpStream};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::{fs, thread};
+
+    #[test]
+    fn test_frame_line() {
+        let config = config_builder::init("Frame", StaticImageMandelbrot)
+            .size(60, 40)
+            .zoom(Width(3.0))
+            .center(-0.5, 0.1)
+            .build();
+        let coordinator = init("frame", &config, 0, &std::env::temp_dir()).unwrap();
+        let frame = coordinator.frame(3);
+        assert_eq!(frame.width_re, 3.0 * 0.98 * 0.98);
+        assert_eq!(parse_frame_line(&frame_line(&frame)), Some(frame));
+        assert_eq!(parse_frame_line("FRAME 3 60 40 3.0 -0.5"), None);
+
+        let frame_config = coordinator.frame(1).config(&config);
+        assert_eq!(frame_config, config);
+
+        let mut zoom = config.clone();
+        zoom.fractal_calc_type = DynamicSequenceNebula;
+        let dynamic = init("zoom", &zoom, 10, &std::env::temp_dir());
+        assert!(matches!(dynamic, Err(FractalError::Config(_))));
+    }
+
+    // worker which takes a frame and never renders it
+    fn lost_worker(port: u16) -> TcpStream {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        writeln!(stream, "WORKER mandelbrot").unwrap();
+        let line = read_line(&mut BufReader::new(stream.try_clone().unwrap())).unwrap();
+        assert!(line.starts_with("FRAME "));
+        stream
+    }
+
+    #[test]
+    fn test_run() {
+        let config = config_builder::init("Mandelbrot", StaticImageMandelbrot)
+            .iterations(0, 100)
+            .size(32, 24)
+            .zoom(Width(4.0))
+            .palette(BlueToWhiteCircleUp)
+            .palette_zero(GrayToBlackCircleDown)
+            .build();
+        let mandelbrot = Arc::new(formula(
+            "mandelbrot",
+            config.clone(),
+            init_trivial_fractal(),
+        ));
+        let dir = std::env::temp_dir().join("rusty_fractals_test_distributed");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut coordinator = init("mandelbrot", &config, 5, &dir).unwrap();
+        coordinator.worker_timeout = Duration::from_millis(300);
+        let coordinator = Arc::new(coordinator);
+        let listener = bind(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let running = coordinator.clone();
+        let coordinator_thread = thread::spawn(move || running.run(listener));
+
+        // frame of disconnected worker and of silent worker are handed out again
+        drop(lost_worker(port));
+        let _silent = lost_worker(port);
+        let nebula = formula("nebula", config.clone(), init_trivial_fractal());
+        assert!(work(&nebula, port).is_err());
+
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                let mandelbrot = mandelbrot.clone();
+                thread::spawn(move || work(&mandelbrot, port).unwrap())
+            })
+            .collect();
+        let rendered: u32 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+        assert_eq!(rendered, 5);
+        coordinator_thread.join().unwrap().unwrap();
+
+        for frame in 1..=5 {
+            assert!(frame_file(&dir, frame).exists());
+        }
+        let frame = image::open(frame_file(&dir, 3)).unwrap().into_rgb8();
+        let expected = mandelbrot
+            .render(&coordinator.frame(3).config(&config), None)
+            .unwrap();
+        assert_eq!(frame, expected.image);
+
+        // saved frames aren't rendered again
+        let coordinator = Arc::new(init("mandelbrot", &config, 5, &dir).unwrap());
+        coordinator.run(bind(0).unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Mandelbrot which takes a while
+    struct SlowMandelbrot;
+
+    impl FractalMath<Mem> for SlowMandelbrot {
+        fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+            thread::sleep(Duration::from_millis(2));
+            m.square();
+            m.plus(origin_re, origin_im);
+        }
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        let config = config_builder::init("Mandelbrot", StaticImageMandelbrot)
+            .iterations(0, 100)
+            .size(4, 3)
+            .zoom(Width(4.0))
+            .palette(BlueToWhiteCircleUp)
+            .palette_zero(GrayToBlackCircleDown)
+            .build();
+        let slow = formula("mandelbrot", config.clone(), SlowMandelbrot);
+        let dir = std::env::temp_dir().join("rusty_fractals_test_heartbeat");
+        let _ = fs::remove_dir_all(&dir);
+
+        // frame takes longer than the timeout, progress keeps the worker alive
+        let mut coordinator = init("mandelbrot", &config, 1, &dir).unwrap();
+        coordinator.worker_timeout = Duration::from_millis(100);
+        let coordinator = Arc::new(coordinator);
+        let listener = bind(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let coordinator_thread = thread::spawn(move || coordinator.run(listener));
+
+        let heartbeat = Duration::from_millis(20);
+        assert_eq!(work_with_heartbeat(&slow, port, heartbeat).unwrap(), 1);
+        coordinator_thread.join().unwrap().unwrap();
+        assert!(frame_file(&dir, 1).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
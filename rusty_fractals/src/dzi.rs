@@ -0,0 +1,297 @@
+use crate::constants::{DZI_OVERLAP, DZI_TILE_SIZE};
+use crate::error::Result;
+use crate::info;
+use image::RgbImage;
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/**
+ * Deep Zoom image pyramid, for browsing of print size images in deep zoom viewers
+ * {name}.dzi describes the image, {name}_files/{level}/{column}_{row}.png are the tiles.
+ * The last level is the full image, each level is half of the next one, level 0 is 1 x 1 px.
+ */
+pub fn export(image: &RgbImage, dzi_file: &Path) -> Result<()> {
+    let (width, height) = image.dimensions();
+    let mut pyramid = init(dzi_file, width, height)?;
+    for row in image.as_raw().chunks_exact(width as usize * 3) {
+        pyramid.row(row)?;
+    }
+    pyramid.finish()
+}
+
+/**
+ * Deep Zoom image pyramid written from rows of the full image, for images larger than memory, see tiled
+ * Each level holds only the rows of its current row of tiles.
+ */
+pub struct Pyramid {
+    // the full image first
+    levels: Vec<Level>,
+}
+
+struct Level {
+    dir: PathBuf,
+    width: u32,
+    height: u32,
+    // rows from band_y to received, RGB
+    band: Vec<u8>,
+    band_y: u32,
+    received: u32,
+    // the next row of tiles to save
+    tile_row: u32,
+    // upper row of the next row of half level
+    upper: Option<Vec<u8>>,
+}
+
+/**
+ * Writes the descriptor, rows of the full image are expected from the top
+ */
+pub fn init(dzi_file: &Path, width: u32, height: u32) -> Result<Pyramid> {
+    let max_level = max_level(width, height);
+    info!(
+        "init() {} {} x {}, {} levels",
+        dzi_file.display(),
+        width,
+        height,
+        max_level + 1
+    );
+    if let Some(dir) = dzi_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(dzi_file, descriptor(width, height))?;
+
+    let files_dir = files_dir(dzi_file);
+    let mut levels = Vec::new();
+    let (mut level_width, mut level_height) = (width, height);
+    for level in (0..=max_level).rev() {
+        levels.push(Level {
+            dir: files_dir.join(level.to_string()),
+            width: level_width,
+            height: level_height,
+            band: Vec::new(),
+            band_y: 0,
+            received: 0,
+            tile_row: 0,
+            upper: None,
+        });
+        (level_width, level_height) = (level_width.div_ceil(2), level_height.div_ceil(2));
+    }
+    Ok(Pyramid { levels })
+}
+
+impl Pyramid {
+    /**
+     * The next row of the full image, RGB
+     */
+    pub fn row(&mut self, row: &[u8]) -> Result<()> {
+        self.add(0, row.to_vec())
+    }
+
+    /**
+     * Saves the last rows of tiles, after all rows of the full image
+     */
+    pub fn finish(mut self) -> Result<()> {
+        for i in 0..self.levels.len() {
+            // odd last row of the level is halved alone
+            if let Some(upper) = self.levels[i].upper.take() {
+                let half = half_row(&upper, None, self.levels[i].width);
+                self.add(i + 1, half)?;
+            }
+        }
+        Ok(())
+    }
+
+    // row of level i, its half rows go to the smaller levels
+    fn add(&mut self, mut i: usize, mut row: Vec<u8>) -> Result<()> {
+        let last = self.levels.len() - 1;
+        loop {
+            let level = &mut self.levels[i];
+            level.add(&row)?;
+            if i == last {
+                return Ok(());
+            }
+            match level.upper.take() {
+                None => {
+                    level.upper = Some(row);
+                    return Ok(());
+                }
+                Some(upper) => {
+                    row = half_row(&upper, Some(&row), level.width);
+                    i += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Level {
+    fn add(&mut self, row: &[u8]) -> Result<()> {
+        self.band.extend_from_slice(row);
+        self.received += 1;
+        let rows = self.height.div_ceil(DZI_TILE_SIZE);
+        while self.tile_row < rows {
+            let (y, tile_height) = tile_span(self.tile_row, self.height);
+            if y + tile_height > self.received {
+                break;
+            }
+            self.save_tile_row(y, tile_height)?;
+            self.tile_row += 1;
+            // rows before the next row of tiles aren't needed
+            let next_y = if self.tile_row < rows {
+                tile_span(self.tile_row, self.height).0
+            } else {
+                self.received
+            };
+            let row_bytes = self.width as usize * 3;
+            self.band
+                .drain(..(next_y - self.band_y) as usize * row_bytes);
+            self.band_y = next_y;
+        }
+        Ok(())
+    }
+
+    /**
+     * Tiles overlap their neighbours by DZI_OVERLAP pixels
+     */
+    fn save_tile_row(&self, y: u32, tile_height: u32) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let row_bytes = self.width as usize * 3;
+        let columns = self.width.div_ceil(DZI_TILE_SIZE);
+        (0..columns)
+            .into_par_iter()
+            .try_for_each(|column| -> Result<()> {
+                let (x, tile_width) = tile_span(column, self.width);
+                let mut pixels = Vec::with_capacity((tile_width * tile_height) as usize * 3);
+                for band_row in y - self.band_y..y - self.band_y + tile_height {
+                    let start = band_row as usize * row_bytes + x as usize * 3;
+                    pixels.extend_from_slice(&self.band[start..start + tile_width as usize * 3]);
+                }
+                RgbImage::from_raw(tile_width, tile_height, pixels)
+                    .expect("could not create tile")
+                    .save(self.dir.join(format!("{}_{}.png", column, self.tile_row)))
+                    .map_err(io::Error::other)?;
+                Ok(())
+            })
+    }
+}
+
+/**
+ * Level of the full image, the first level with 1 x 1 px is 0
+ */
+pub fn max_level(width: u32, height: u32) -> u32 {
+    let size = width.max(height).max(1);
+    u32::BITS - (size - 1).leading_zeros()
+}
+
+fn descriptor(width: u32, height: u32) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" Format=\"png\" Overlap=\"{}\" TileSize=\"{}\">\n\
+         \x20 <Size Width=\"{}\" Height=\"{}\"/>\n\
+         </Image>\n",
+        DZI_OVERLAP, DZI_TILE_SIZE, width, height
+    )
+}
+
+// nebula.dzi -> nebula_files
+fn files_dir(dzi_file: &Path) -> PathBuf {
+    let name = dzi_file.file_stem().unwrap_or_default().to_string_lossy();
+    dzi_file.with_file_name(format!("{}_files", name))
+}
+
+// start and length of tile along a side of length pixels
+fn tile_span(tile: u32, length: u32) -> (u32, u32) {
+    let start = (tile * DZI_TILE_SIZE).saturating_sub(DZI_OVERLAP);
+    let end = ((tile + 1) * DZI_TILE_SIZE + DZI_OVERLAP).min(length);
+    (start, end - start)
+}
+
+/**
+ * Row of image of half size, each pixel is average of 2 x 2 pixels of the upper and lower row
+ * Odd last column and row average only the pixels they have.
+ */
+fn half_row(upper: &[u8], lower: Option<&[u8]>, width: u32) -> Vec<u8> {
+    let half_width = width.div_ceil(2) as usize;
+    let mut row = vec![0u8; half_width * 3];
+    let rows = [Some(upper), lower];
+    for x in 0..half_width {
+        let mut sum = [0u32; 3];
+        let mut count = 0;
+        for source in rows.iter().flatten() {
+            for sx in 2 * x..(2 * x + 2).min(width as usize) {
+                for c in 0..3 {
+                    sum[c] += source[sx * 3 + c] as u32;
+                }
+                count += 1;
+            }
+        }
+        for c in 0..3 {
+            row[x * 3 + c] = ((sum[c] + count / 2) / count) as u8;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dzi::{export, half_row, max_level, tile_span};
+    use image::{Rgb, RgbImage};
+    use std::fs;
+
+    #[test]
+    fn test_max_level() {
+        assert_eq!(max_level(1, 1), 0);
+        assert_eq!(max_level(2, 1), 1);
+        assert_eq!(max_level(600, 300), 10);
+        assert_eq!(max_level(1024, 1024), 10);
+        assert_eq!(max_level(50_000, 30_000), 16);
+    }
+
+    #[test]
+    fn test_tile_span() {
+        assert_eq!(tile_span(0, 600), (0, 255));
+        assert_eq!(tile_span(1, 600), (253, 256));
+        assert_eq!(tile_span(2, 600), (507, 93));
+        assert_eq!(tile_span(0, 100), (0, 100));
+    }
+
+    #[test]
+    fn test_half_row() {
+        let upper = [100, 0, 0, 0, 0, 0, 10, 20, 31];
+        let lower = [0, 0, 0, 0, 200, 0, 0, 0, 0];
+
+        let half = half_row(&upper, Some(&lower), 3);
+        assert_eq!(half, vec![25, 50, 0, 5, 10, 16]);
+        // odd last row averages only its pixels
+        assert_eq!(half_row(&upper, None, 3), vec![50, 0, 0, 10, 20, 31]);
+    }
+
+    #[test]
+    fn test_export() {
+        let image = RgbImage::from_fn(600, 300, |x, y| Rgb([x as u8, y as u8, 7]));
+        let dir = std::env::temp_dir().join("rusty_fractals_test_dzi");
+        let _ = fs::remove_dir_all(&dir);
+        export(&image, &dir.join("nebula.dzi")).unwrap();
+
+        let descriptor = fs::read_to_string(dir.join("nebula.dzi")).unwrap();
+        assert!(descriptor.contains("TileSize=\"254\""));
+        assert!(descriptor.contains("<Size Width=\"600\" Height=\"300\"/>"));
+
+        let files = dir.join("nebula_files");
+        assert_eq!(fs::read_dir(&files).unwrap().count(), 11);
+        assert_eq!(fs::read_dir(files.join("10")).unwrap().count(), 3 * 2);
+        let tile = image::open(files.join("10/1_1.png")).unwrap().into_rgb8();
+        assert_eq!(tile.dimensions(), (256, 47));
+        assert_eq!(tile.get_pixel(0, 0), image.get_pixel(253, 253));
+        let tile = image::open(files.join("9/1_0.png")).unwrap().into_rgb8();
+        // average of pixels 508, 509 of rows 0, 1
+        assert_eq!(tile.get_pixel(1, 0), &Rgb([253, 1, 7]));
+        // level 8 is 600 / 4 x 300 / 4 px
+        let tile = image::open(files.join("8/0_0.png")).unwrap().into_rgb8();
+        assert_eq!(tile.dimensions(), (150, 75));
+        let tile = image::open(files.join("0/0_0.png")).unwrap().into_rgb8();
+        assert_eq!(tile.dimensions(), (1, 1));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::io;
+
+/**
+ * Recoverable errors of fractal calculation
+ * Zoom reports a failed frame and continues with the next one, see ZOOM_FAILED_FRAMES_MAX
+ */
+#[derive(Debug)]
+pub enum FractalError {
+    // configuration which the calculation can't use
+    Config(String),
+    // perfect colouring didn't paint every pixel exactly once
+    Colouring { painted: usize, pixels: usize },
+    // zoom didn't move any pixel to the next frame, or didn't create any new one
+    Zoom { moved: usize, created: usize },
+    Io(io::Error),
+    // render thread panicked, with the panic message
+    Panicked(String),
+    // plugin library which couldn't be loaded
+    Plugin(String),
+    // worker of distributed render which broke the protocol, or frames the workers failed to render
+    Worker(String),
+}
+
+pub type Result<T> = std::result::Result<T, FractalError>;
+
+impl fmt::Display for FractalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FractalError::Config(message) => write!(f, "config: {}", message),
+            FractalError::Colouring { painted, pixels } => {
+                write!(f, "colouring painted {} of {} pixels", painted, pixels)
+            }
+            FractalError::Zoom { moved, created } => {
+                write!(f, "zoom moved {} and created {} pixels", moved, created)
+            }
+            FractalError::Io(e) => write!(f, "io: {}", e),
+            FractalError::Panicked(message) => write!(f, "panicked: {}", message),
+            FractalError::Plugin(message) => write!(f, "plugin: {}", message),
+            FractalError::Worker(message) => write!(f, "worker: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FractalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FractalError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FractalError {
+    fn from(e: io::Error) -> Self {
+        FractalError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::FractalError;
+    use std::error::Error;
+    use std::io;
+
+    #[test]
+    fn test_display() {
+        let e = FractalError::Zoom {
+            moved: 0,
+            created: 400,
+        };
+        assert_eq!(e.to_string(), "zoom moved 0 and created 400 pixels");
+
+        let e: FractalError = io::Error::new(io::ErrorKind::NotFound, "stats.csv").into();
+        assert_eq!(e.to_string(), "io: stats.csv");
+        assert!(e.source().is_some());
+    }
+}
@@ -0,0 +1,335 @@
+use crate::constants::{ESTIMATE_SAMPLE_ORIGINS, ETA_PRINT_INTERVAL_MS, NEIGHBOURS};
+use crate::fractal::{FractalMath, MemType};
+use crate::fractal_stats::FrameRecord;
+use crate::machine::Machine;
+use crate::path_store::{index_bytes, point_bytes};
+use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use crate::{debug, info};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/**
+ * Predicted cost of Nebula calculation, of a static image or of one zoom frame
+ * Extrapolated from a quick sample of random origins calculated with the configured FractalMath
+ * Zoom estimates each frame again, as its area and iteration limits change.
+ */
+pub struct Estimate {
+    pub origins_sampled: usize,
+    // first pass and wrap origins
+    pub origins_total: u64,
+    // origins which produce a good path
+    pub paths_total: u64,
+    // path elements within area, of good paths
+    pub path_length_average: f64,
+    // how many iterations of good paths are within area
+    pub in_area_ratio: f64,
+    pub iterations_total: u64,
+    // all path elements accumulated into image
+    pub points_total: u64,
+    // remembered paths of dynamic data, 0 for static image
+    pub path_memory_bytes: u64,
+    pub duration: Duration,
+}
+
+impl Estimate {
+    pub fn print(&self) {
+        info!("------------------------------------");
+        info!("Estimate from sample origins: {}", self.origins_sampled);
+        info!("Origins to calculate:        {}", self.origins_total);
+        info!("Good paths:                  {}", self.paths_total);
+        info!(
+            "Path length average:         {:.1}",
+            self.path_length_average
+        );
+        info!("In area ratio:               {:.3}", self.in_area_ratio);
+        info!("Iterations:                  {}", self.iterations_total);
+        info!("Path points:                 {}", self.points_total);
+        info!(
+            "Paths memory:                {} MB",
+            self.path_memory_bytes / (1024 * 1024)
+        );
+        info!(
+            "Time:                        {}",
+            format_duration(self.duration)
+        );
+        info!("------------------------------------");
+    }
+
+    /**
+     * Time of the next zoom frame, the previous frame took this much longer or shorter than its estimate
+     */
+    pub fn frame_duration(&self, previous: &Estimate, record: &FrameRecord) -> Duration {
+        if previous.duration.is_zero() {
+            return self.duration;
+        }
+        let measured = record.calculate + record.wrap;
+        self.duration
+            .mul_f64(measured.as_secs_f64() / previous.duration.as_secs_f64())
+    }
+}
+
+pub fn estimate<F, M>(machine: &Machine<F, M>) -> Estimate
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    debug!("estimate()");
+    let area = machine.area.copy_data();
+
+    let mut rng = StdRng::seed_from_u64(machine.data_image.chunks.seed);
+    let origins: Vec<[f64; 2]> = (0..ESTIMATE_SAMPLE_ORIGINS)
+        .map(|_| {
+            [
+                rng.gen_range(area.border_low_re..area.border_high_re),
+                rng.gen_range(area.border_low_im..area.border_high_im),
+            ]
+        })
+        .collect();
+
+    // sample is calculated by the whole pool, as the calculation will be
+    let start = Instant::now();
+    let results: Vec<(u32, u32)> = origins
+        .par_iter()
+        .map(|[re, im]| machine.path_length(&area, *re, *im))
+        .collect();
+    let elapsed = start.elapsed();
+
+    let sampled = results.len() as f64;
+    let sample_iterations: f64 = results.iter().map(|(i, _)| *i as f64).sum();
+    let good: Vec<&(u32, u32)> = results
+        .iter()
+        .filter(|(iterator, length)| machine.path_test(*length, *iterator))
+        .collect();
+    let good_iterations: f64 = good.iter().map(|(i, _)| *i as f64).sum();
+    let good_length: f64 = good.iter().map(|(_, l)| *l as f64).sum();
+    let good_ratio = good.len() as f64 / sampled;
+
+    // wrap calculates more origins within pixels close to good pixels
+    // neighbourhoods of good pixels overlap, so this is an upper estimate
+    let pixels = (machine.width_x * machine.height_y) as f64;
+    let wrap_origins = if machine.resolution_multiplier == Single {
+        0.0
+    } else {
+        machine
+            .data_image
+            .wrap(0.0, 0.0, machine.resolution_multiplier, area.plank)
+            .len() as f64
+    };
+    let neighbourhood = (2 * NEIGHBOURS as usize + 1).pow(2) as f64;
+    let horizon_ratio = (good_ratio * neighbourhood).min(1.0);
+    let origins_total = pixels + pixels * horizon_ratio * wrap_origins;
+    let paths_total = origins_total * good_ratio;
+
+    let path_length_average = good_length / good.len().max(1) as f64;
+    let points_total = paths_total * path_length_average;
+    // good paths are calculated twice, the second time they are recorded
+    let iterations_total = origins_total * (sample_iterations + good_iterations) / sampled;
+
+    let path_memory_bytes = if machine.data_image.is_dynamic() {
+        let encoding = machine.data_image.paths.lock().unwrap().encoding;
+        points_total as usize * point_bytes(encoding) + index_bytes(paths_total as usize)
+    } else {
+        0
+    };
+
+    let duration = if sample_iterations > 0.0 {
+        elapsed.mul_f64(iterations_total / sample_iterations)
+    } else {
+        Duration::ZERO
+    };
+
+    Estimate {
+        origins_sampled: results.len(),
+        origins_total: origins_total as u64,
+        paths_total: paths_total as u64,
+        path_length_average,
+        in_area_ratio: good_length / good_iterations.max(1.0),
+        iterations_total: iterations_total as u64,
+        points_total: points_total as u64,
+        path_memory_bytes: path_memory_bytes as u64,
+        duration,
+    }
+}
+
+/**
+ * Live estimate of the remaining time of running calculation, from finished chunks
+ */
+pub struct Eta {
+    started: Mutex<Instant>,
+    last_print: Mutex<Instant>,
+    total: AtomicUsize,
+    done: AtomicUsize,
+}
+
+impl Eta {
+    /**
+     * Calculation of total chunks started
+     */
+    pub fn start(&self, total: usize) {
+        *self.started.lock().unwrap() = Instant::now();
+        *self.last_print.lock().unwrap() = Instant::now();
+        self.total.store(total, Ordering::Relaxed);
+        self.done.store(0, Ordering::Relaxed);
+    }
+
+    /**
+     * Prints ETA at most once per ETA_PRINT_INTERVAL_MS
+     */
+    pub fn chunk_done(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut last_print = self.last_print.lock().unwrap();
+        if last_print.elapsed() >= Duration::from_millis(ETA_PRINT_INTERVAL_MS) {
+            *last_print = Instant::now();
+            if let Some(remaining) = self.remaining() {
+                info!(
+                    "ETA {}, {} of {} chunks",
+                    format_duration(remaining),
+                    done,
+                    self.total.load(Ordering::Relaxed)
+                );
+            }
+        }
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        let done = self.done.load(Ordering::Relaxed);
+        let total = self.total.load(Ordering::Relaxed);
+        if done == 0 || done > total {
+            return None;
+        }
+        let elapsed = self.started.lock().unwrap().elapsed();
+        Some(elapsed.mul_f64((total - done) as f64 / done as f64))
+    }
+}
+
+pub fn init_eta() -> Eta {
+    Eta {
+        started: Mutex::new(Instant::now()),
+        last_print: Mutex::new(Instant::now()),
+        total: AtomicUsize::new(0),
+        done: AtomicUsize::new(0),
+    }
+}
+
+// 2d 04h, 3h 07m, 5m 12s, 9s
+pub fn format_duration(duration: Duration) -> String {
+    let s = duration.as_secs();
+    let (d, h, m) = (s / 86400, s / 3600 % 24, s / 60 % 60);
+    if d > 0 {
+        format!("{}d {:02}h", d, h)
+    } else if h > 0 {
+        format!("{}h {:02}m", h, m)
+    } else if m > 0 {
+        format!("{}m {:02}s", m, s % 60)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::estimator::{estimate, format_duration, init_eta};
+    use crate::fractal::FractalCalculationType::StaticImageNebula;
+    use crate::fractal::{init_trivial_fractal, init_trivial_static_config};
+    use crate::fractal_stats::FrameRecord;
+    use crate::machine;
+    use crate::palettes::PaletteName::BlueToWhiteCircleUp;
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_estimate() {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = StaticImageNebula;
+        conf.width_x = 60;
+        conf.height_y = 40;
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_min = 20;
+        conf.iteration_max = 500;
+        conf.resolution_multiplier = Square3;
+        conf.palette = BlueToWhiteCircleUp;
+        let machine = machine::init(&conf, init_trivial_fractal());
+
+        let estimate = estimate(&machine);
+        machine.calculate_nebula_static_image().unwrap();
+
+        let pixels = (conf.width_x * conf.height_y) as u64;
+        assert!(estimate.origins_total > pixels);
+        assert!(estimate.origins_total <= pixels * 9);
+        assert!(estimate.paths_total > 0);
+        assert!(estimate.in_area_ratio > 0.0 && estimate.in_area_ratio <= 1.0);
+        assert_eq!(estimate.path_memory_bytes, 0);
+
+        // all points end up in the image, prediction is of the same order
+        let points: u64 = (0..conf.height_y)
+            .flat_map(|y| (0..conf.width_x).map(move |x| (x, y)))
+            .map(|(x, y)| machine.data_image.value_at(x, y) as u64)
+            .sum();
+        assert!(estimate.points_total > points / 4);
+        assert!(estimate.points_total < points * 4);
+    }
+
+    #[test]
+    fn test_frame_duration() {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = StaticImageNebula;
+        let machine = machine::init(&conf, init_trivial_fractal());
+        let mut previous = estimate(&machine);
+        let mut next = estimate(&machine);
+        previous.duration = Duration::from_secs(10);
+        next.duration = Duration::from_secs(12);
+        let record = FrameRecord {
+            calculate: Duration::from_secs(15),
+            wrap: Duration::from_secs(5),
+            ..FrameRecord::default()
+        };
+
+        // previous frame took twice its estimate
+        assert_eq!(
+            next.frame_duration(&previous, &record),
+            Duration::from_secs(24)
+        );
+        previous.duration = Duration::ZERO;
+        assert_eq!(
+            next.frame_duration(&previous, &record),
+            Duration::from_secs(12)
+        );
+    }
+
+    #[test]
+    fn test_eta() {
+        let eta = init_eta();
+        eta.start(4);
+        assert_eq!(eta.remaining(), None);
+
+        thread::sleep(Duration::from_millis(20));
+        eta.chunk_done();
+
+        let remaining = eta.remaining().unwrap();
+        assert!(remaining >= Duration::from_millis(60));
+        eta.chunk_done();
+        eta.chunk_done();
+        eta.chunk_done();
+        assert_eq!(eta.remaining(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(9)), "9s");
+        assert_eq!(format_duration(Duration::from_secs(312)), "5m 12s");
+        assert_eq!(
+            format_duration(Duration::from_secs(3 * 3600 + 7 * 60)),
+            "3h 07m"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86400 + 4 * 3600)),
+            "2d 04h"
+        );
+    }
+}
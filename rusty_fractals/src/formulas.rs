@@ -0,0 +1,296 @@
+use crate::constants::{PHOENIX_INIT_C, PHOENIX_INIT_P};
+use crate::error::Result;
+use crate::fractal::{FractalConfig, FractalMath, MemType};
+use crate::mem::Mem;
+use crate::mem_collatz::MemCollatz;
+use crate::mem_phoenix::MemPhoenix;
+use crate::presets;
+use crate::progress::ProgressListener;
+use crate::render;
+use crate::render::Render;
+use crate::tiled;
+use std::path::Path;
+use std::sync::Arc;
+
+/**
+ * f = z^2 + c
+ * Nebula, Infinity, Science and Mandelbrot
+ */
+pub struct Nebula;
+
+impl FractalMath<Mem> for Nebula {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+}
+
+pub struct Lotus;
+
+impl FractalMath<Mem> for Lotus {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.conjugation();
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+}
+
+pub struct FatStar;
+
+impl FractalMath<Mem> for FatStar {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.conjugation();
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+}
+
+/**
+ * f(f(z)) : f = z^2 + c
+ */
+pub struct NebulaOfNebula;
+
+impl FractalMath<Mem> for NebulaOfNebula {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        let r = m.re;
+        let i = m.im;
+        let or = origin_re;
+        let oi = origin_im;
+        m.re = r * r * r * r - 6.0 * r * r * i * i + i * i * i * i + 2.0 * r * r * or
+            - 2.0 * i * i * or
+            - 4.0 * r * i * oi
+            + or * or
+            - oi * oi
+            + or
+            - r;
+        m.im = 4.0 * r * r * r * i - 4.0 * r * i * i * i + 4.0 * r * i * or + 2.0 * r * r * oi
+            - 2.0 * i * i * oi
+            + 2.0 * or * oi
+            + oi
+            - i;
+    }
+}
+
+pub struct CollatzConjecture;
+
+impl FractalMath<MemCollatz> for CollatzConjecture {
+    fn math(&self, m: &mut MemCollatz, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.collatz_conjecture();
+        m.plus(origin_re, origin_im);
+    }
+}
+
+pub struct CollatzConjectureMandelbrot;
+
+impl FractalMath<MemCollatz> for CollatzConjectureMandelbrot {
+    fn math(&self, mc: &mut MemCollatz, origin_re: f64, origin_im: f64) {
+        mc.square();
+        mc.plus_collatz(origin_re, origin_im);
+    }
+}
+
+pub struct Head;
+
+impl FractalMath<MemPhoenix> for Head {
+    fn math(&self, mp: &mut MemPhoenix, origin_re: f64, origin_im: f64) {
+        mp.square();
+        mp.m.re += PHOENIX_INIT_C;
+        mp.m.re += PHOENIX_INIT_P * mp.prev_prev_re;
+        mp.m.im += PHOENIX_INIT_P * mp.prev_prev_im;
+        // previous iteration values
+        mp.prev_prev_re = mp.prev_re;
+        mp.prev_prev_im = mp.prev_im;
+        mp.prev_re = mp.m.re;
+        mp.prev_im = mp.m.im;
+        mp.plus(origin_re, origin_im);
+    }
+}
+
+type Renderer =
+    dyn Fn(&FractalConfig, Option<Arc<dyn ProgressListener>>) -> Result<Render> + Send + Sync;
+type TiledRenderer = dyn Fn(&FractalConfig, usize, &Path) -> Result<()> + Send + Sync;
+
+/**
+ * Fractal math with its default config
+ * Hides the memory type, so that formulas of all memory types can be registered together.
+ */
+pub struct Formula {
+    pub name: String,
+    pub config: FractalConfig,
+    renderer: Box<Renderer>,
+    tiled_renderer: Box<TiledRenderer>,
+}
+
+impl Formula {
+    /**
+     * Render any config with this formula, e.g. the default config with different size
+     */
+    pub fn render(
+        &self,
+        config: &FractalConfig,
+        listener: Option<Arc<dyn ProgressListener>>,
+    ) -> Result<Render> {
+        (self.renderer)(config, listener)
+    }
+
+    /**
+     * Render config larger than memory to .png image or .dzi pyramid, see tiled
+     */
+    pub fn render_tiled(
+        &self,
+        config: &FractalConfig,
+        tile_size: usize,
+        image: &Path,
+    ) -> Result<()> {
+        (self.tiled_renderer)(config, tile_size, image)
+    }
+}
+
+pub fn formula<F, M>(name: &str, config: FractalConfig, fractal: F) -> Formula
+where
+    F: FractalMath<M> + 'static,
+    M: MemType<M> + 'static,
+{
+    let fractal = Arc::new(fractal);
+    let tiled_fractal = fractal.clone();
+    Formula {
+        name: name.to_string(),
+        config,
+        renderer: Box::new(move |config, listener| {
+            render::render_with_progress(config, fractal.clone(), listener)
+        }),
+        tiled_renderer: Box::new(move |config, tile_size, image| {
+            tiled::render(config, tiled_fractal.clone(), tile_size, image)
+        }),
+    }
+}
+
+/**
+ * Formulas by name, in order of registration
+ */
+pub struct Registry {
+    formulas: Vec<Formula>,
+}
+
+impl Registry {
+    /**
+     * Formula of already registered name replaces the previous one
+     */
+    pub fn register(&mut self, formula: Formula) {
+        match self.formulas.iter().position(|f| f.name == formula.name) {
+            Some(index) => self.formulas[index] = formula,
+            None => self.formulas.push(formula),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Formula> {
+        self.formulas.iter().find(|f| f.name == name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.formulas.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.formulas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formulas.is_empty()
+    }
+
+    pub fn formulas(&self) -> &[Formula] {
+        &self.formulas
+    }
+}
+
+pub fn init() -> Registry {
+    Registry {
+        formulas: Vec::new(),
+    }
+}
+
+/**
+ * Formulas of the presets, by preset name
+ * Euler isn't registered, Machine doesn't calculate it.
+ */
+pub fn init_builtin() -> Registry {
+    let mut registry = init();
+    for name in presets::PRESETS {
+        let config = presets::by_name(name)
+            .expect("could not find preset")
+            .build();
+        let formula = match name {
+            "collatz_conjecture" | "collatz_conjecture_orbits" => {
+                formula(name, config, CollatzConjecture)
+            }
+            "collatz_mandelbrot" => formula(name, config, CollatzConjectureMandelbrot),
+            "fat_star" | "fat_star_magnific" | "fat_star_tentacle" => {
+                formula(name, config, FatStar)
+            }
+            "head" => formula(name, config, Head),
+            "lotus" => formula(name, config, Lotus),
+            "mandelbrot_of_mandelbrot" | "nebula_of_nebula" => {
+                formula(name, config, NebulaOfNebula)
+            }
+            "euler" => continue,
+            _ => formula(name, config, Nebula),
+        };
+        registry.register(formula);
+    }
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formulas::{formula, init, init_builtin, FatStar, Lotus};
+    use crate::fractal::{init_trivial_fractal, init_trivial_static_config, FractalMath};
+    use crate::mem::Mem;
+    use crate::palettes::PaletteName::{BlueToWhiteCircleUp, GrayToBlackCircleDown};
+    use crate::presets::PRESETS;
+    use crate::render::render;
+
+    #[test]
+    fn test_math() {
+        let mut m = Mem { re: 1.0, im: 0.5 };
+        Lotus.math(&mut m, 0.1, 0.1);
+        assert_eq!(m.re, 0.85);
+        assert_eq!(m.im, -0.9);
+
+        let mut m = Mem { re: 1.0, im: 0.5 };
+        FatStar.math(&mut m, 0.1, 0.1);
+        assert_eq!(m.re, -0.4375 + 0.1);
+        assert_eq!(m.im, -1.5 + 0.1);
+    }
+
+    #[test]
+    fn test_init_builtin() {
+        let registry = init_builtin();
+        assert_eq!(registry.len(), PRESETS.len() - 1);
+        assert!(registry.get("euler").is_none());
+        assert_eq!(registry.get("head").unwrap().config.name, "Head");
+        assert_eq!(registry.names()[0], "collatz_conjecture");
+    }
+
+    #[test]
+    fn test_register() {
+        let mut conf = init_trivial_static_config();
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_max = 100;
+        conf.palette = BlueToWhiteCircleUp;
+        conf.palette_zero = GrayToBlackCircleDown;
+
+        let mut registry = init();
+        registry.register(formula("trivial", conf.clone(), Lotus));
+        registry.register(formula("trivial", conf.clone(), init_trivial_fractal()));
+        assert_eq!(registry.names(), vec!["trivial"]);
+
+        let formula = registry.get("trivial").unwrap();
+        let result = formula.render(&formula.config, None).unwrap();
+        let expected = render(&conf, init_trivial_fractal()).unwrap();
+        assert_eq!(result.values.values, expected.values.values);
+    }
+}
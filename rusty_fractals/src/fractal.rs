@@ -1,33 +1,53 @@
+use crate::chunks::ChunkLayout;
+use crate::chunks::ChunkLayout::Count;
 use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticSequenceMandelbrot};
+use crate::fractal::MandelbrotRendering::Shuffled;
 use crate::fractal::OrbitType::Finite;
 use crate::mem::Mem;
 use crate::palettes::PaletteName;
 use crate::palettes::PaletteName::Nothing;
+use crate::path_store::PathEncoding::AbsoluteF64;
+use crate::path_store::PathStorage::Memory;
+use crate::path_store::{PathEncoding, PathStorage};
 use crate::resolution_multiplier::ResolutionMultiplier;
 use crate::resolution_multiplier::ResolutionMultiplier::Single;
 use std::cmp::PartialEq;
+use std::sync::Arc;
 use FractalCalculationType::StaticImageMandelbrot;
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct FractalConfig {
     // fractal config
-    pub name: &'static str,
+    pub name: String,
     pub orbits: OrbitType, // fractal::finite_orbits / infinite_orbits
     pub fractal_calc_type: FractalCalculationType,
     // calculation config
     pub iteration_min: u32,
     pub iteration_max: u32,
     pub resolution_multiplier: ResolutionMultiplier,
+    pub mandelbrot_rendering: MandelbrotRendering, // only for Mandelbrot like fractals
+    pub seed: u64, // same seed, same order of chunks and any other random sampling
     pub palette: PaletteName,
     pub palette_zero: PaletteName,
     // area config
     pub width_x: usize,
     pub height_y: usize,
+    pub chunk_layout: ChunkLayout, // domain split to chunks calculated in parallel
     pub width_re: f64,
     pub center_re: f64,
     pub center_im: f64,
     // calculation update config
     pub update_max: u32,
     pub update_min: u32,
+    pub iteration_max_limit: u32, // zoom doesn't raise iteration_max above it
+    // dynamic paths config
+    pub path_encoding: PathEncoding,
+    pub path_storage: PathStorage,
+    pub paths_memory_max_mb: usize, // 0 for no limit, the least-contributing paths are evicted above it
+    // per frame statistics, .csv file as CSV, other files as JSON Lines
+    pub stats_file: Option<String>,
+    // record per pixel cost and path yield, save them as false colour images with each frame
+    pub diagnostics: bool,
 }
 
 impl FractalConfig {
@@ -44,7 +64,8 @@ impl FractalConfig {
 /**
 - Orbit types for nebula fractals
 */
-#[derive(PartialEq, Clone, Copy)]
+#[repr(C)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum OrbitType {
     // Ignore orbits for Mandelbrot like calculations
     Ignore,
@@ -55,6 +76,23 @@ pub enum OrbitType {
     Infinite,
 }
 
+/**
+ * Order in which pixels of Mandelbrot like fractals are calculated
+ */
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MandelbrotRendering {
+    // All pixels at full resolution, chunks in random order
+    Shuffled,
+    // Coarse sub-grid first, then refine by powers of two
+    // Already calculated samples are reused by finer levels
+    Progressive,
+    // Mariani–Silver subdivision of each chunk
+    // Rectangles with uniform border are filled without calculation of their inside
+    MarianiSilver,
+    // Mariani–Silver, but calculate also the filled pixels and report mismatches
+    MarianiSilverVerified,
+}
+
 /**
  * Mandelbrot fractal
  * - for each domain element, count the calculations
@@ -65,7 +103,8 @@ pub enum OrbitType {
  * Euler fractal
  * - split primes, Fibonacci's and other calculation sequences to RGB spectra
  */
-#[derive(PartialEq, Clone, Copy)]
+#[repr(C)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum FractalCalculationType {
     /** Nebula fractals
      * - drop calculation path to px grid immediately
@@ -92,6 +131,18 @@ pub trait FractalMath<M>: Sync + Send {
     fn math(&self, m: &mut M, origin_re: f64, origin_im: f64);
 }
 
+/**
+ * Shared fractal, e.g. a registered formula rendered more times
+ */
+impl<M, F> FractalMath<M> for Arc<F>
+where
+    F: FractalMath<M> + ?Sized,
+{
+    fn math(&self, m: &mut M, origin_re: f64, origin_im: f64) {
+        (**self).math(m, origin_re, origin_im);
+    }
+}
+
 pub trait MemType<M>: Sync + Send {
     fn new(re: f64, im: f64) -> M;
     fn quad(&self) -> f64;
@@ -117,44 +168,57 @@ pub fn init_trivial_fractal() -> TrivialFractal {
 }
 
 /**
- * The smallest possible set to calculate upon is 20 x 20 = 400 px, because of chunks
+ * 20 x 20 = 400 px, split to 20 x 20 chunks
  */
 pub fn init_trivial_static_config() -> FractalConfig {
     FractalConfig {
-        name: "Static",
+        name: "Static".to_string(),
         orbits: Finite,
         fractal_calc_type: StaticImageMandelbrot,
         iteration_min: 1,
         iteration_max: 3, // path length too short = 0,1, convergent = 2, divergent = 3
         resolution_multiplier: Single,
+        mandelbrot_rendering: Shuffled,
+        seed: 0,
 
         palette: Nothing,
         palette_zero: Nothing,
 
         width_x: 20, // 1 chunk is 1 px
         height_y: 20,
+        chunk_layout: Count(20),
         width_re: 1.0,
         center_re: 0.0,
         center_im: 0.0,
 
         update_max: 1,
         update_min: 0,
+        iteration_max_limit: u32::MAX,
+
+        path_encoding: AbsoluteF64,
+        path_storage: Memory,
+        paths_memory_max_mb: 0,
+        stats_file: None,
+        diagnostics: false,
     }
 }
 
-pub const fn init_trivial_dynamic_config() -> FractalConfig {
+pub fn init_trivial_dynamic_config() -> FractalConfig {
     FractalConfig {
-        name: "Dynamic",
+        name: "Dynamic".to_string(),
         fractal_calc_type: DynamicSequenceNebula,
         iteration_min: 1,
         iteration_max: 3, // path length too short = 0,1, convergent = 2, divergent = 3
         resolution_multiplier: Single,
+        mandelbrot_rendering: Shuffled,
+        seed: 0,
 
         palette: Nothing,
         palette_zero: Nothing,
 
         width_x: 20, // 1 chunk is 1 px
         height_y: 20,
+        chunk_layout: Count(20),
         width_re: 1.0,
         center_re: 0.0,
         center_im: 0.0,
@@ -162,6 +226,13 @@ pub const fn init_trivial_dynamic_config() -> FractalConfig {
         orbits: Finite,
         update_max: 1,
         update_min: 0,
+        iteration_max_limit: u32::MAX,
+
+        path_encoding: AbsoluteF64,
+        path_storage: Memory,
+        paths_memory_max_mb: 0,
+        stats_file: None,
+        diagnostics: false,
     }
 }
 
@@ -1,28 +1,309 @@
 use chrono::Utc;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::sync::{LazyLock, Mutex, RwLock};
 
 const TIME_FORMAT: &str = "%Y.%m.%d %H:%M:%S.%f";
 
-pub fn now(message: &str) {
-    println!("{}: {}", no(), message);
+// levels, default and per module, e.g. "info,rusty_fractals::palette_utils=trace,rusty_fractals::area=warn"
+const LOG_ENV: &str = "FRACTAL_LOG";
+// log is also appended to this file
+const LOG_FILE_ENV: &str = "FRACTAL_LOG_FILE";
+// "x,y" of the pixel to trace
+const TRACE_PIXEL_ENV: &str = "FRACTAL_TRACE_PIXEL";
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+fn parse_level(name: &str) -> Option<Level> {
+    match name.trim().to_lowercase().as_str() {
+        "error" => Some(Level::Error),
+        "warn" => Some(Level::Warn),
+        "info" => Some(Level::Info),
+        "debug" => Some(Level::Debug),
+        "trace" => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+struct Logger {
+    level: Level,
+    // module path and its level, the longest matching module path applies
+    modules: Vec<(String, Level)>,
+    file: Option<Mutex<LogFile>>,
+    trace_pixel: Option<[usize; 2]>,
+}
+
+// log goes on without the file when writing fails, the failure is reported once
+struct LogFile {
+    file: File,
+    failed: bool,
+}
+
+impl LogFile {
+    fn write_line(&mut self, line: &str) {
+        if let Err(e) = writeln!(self.file, "{}", line) {
+            if !self.failed {
+                eprintln!("could not write log file: {}", e);
+                self.failed = true;
+            }
+        }
+    }
 }
 
-const X: usize = 249;
-const Y: usize = 248;
+impl Logger {
+    fn level_for(&self, module: &str) -> Level {
+        self.modules
+            .iter()
+            .filter(|(m, _)| module == m || module.starts_with(&format!("{}::", m)))
+            .max_by_key(|(m, _)| m.len())
+            .map_or(self.level, |(_, level)| *level)
+    }
 
-pub fn only(x: usize, y: usize, message: &str) {
-    if x == X {
-        if y == Y {
-            println!("* {}: {}", no(), message);
+    fn configure(&mut self, spec: &str) {
+        for part in spec.split(',').filter(|p| !p.trim().is_empty()) {
+            match part.split_once('=') {
+                Some((module, level)) => match parse_level(level) {
+                    Some(level) => {
+                        let module = module.trim().to_string();
+                        self.modules.retain(|(m, _)| *m != module);
+                        self.modules.push((module, level));
+                    }
+                    None => eprintln!("unknown log level: {}", part),
+                },
+                None => match parse_level(part) {
+                    Some(level) => self.level = level,
+                    None => eprintln!("unknown log level: {}", part),
+                },
+            }
         }
     }
 }
 
-fn no() -> String {
+static LOGGER: LazyLock<RwLock<Logger>> = LazyLock::new(|| {
+    let mut logger = Logger {
+        level: Level::Info,
+        modules: Vec::new(),
+        file: None,
+        trace_pixel: None,
+    };
+    if let Ok(spec) = std::env::var(LOG_ENV) {
+        logger.configure(&spec);
+    }
+    if let Ok(path) = std::env::var(LOG_FILE_ENV) {
+        match open_file(&path) {
+            Ok(file) => logger.file = Some(Mutex::new(file)),
+            Err(e) => eprintln!("could not open log file {}: {}", path, e),
+        }
+    }
+    if let Ok(xy) = std::env::var(TRACE_PIXEL_ENV) {
+        logger.trace_pixel = parse_pixel(&xy);
+    }
+    RwLock::new(logger)
+});
+
+/**
+ * Same format as FRACTAL_LOG environment variable
+ */
+pub fn configure(spec: &str) {
+    LOGGER.write().unwrap().configure(spec);
+}
+
+pub fn set_level(level: Level) {
+    LOGGER.write().unwrap().level = level;
+}
+
+pub fn log_to_file(path: &str) -> io::Result<()> {
+    let file = open_file(path)?;
+    LOGGER.write().unwrap().file = Some(Mutex::new(file));
+    Ok(())
+}
+
+/**
+ * Log messages about calculation of this pixel, None stops tracing
+ */
+pub fn trace_pixel(pixel: Option<[usize; 2]>) {
+    LOGGER.write().unwrap().trace_pixel = pixel;
+}
+
+pub fn is_traced(x: usize, y: usize) -> bool {
+    LOGGER.read().unwrap().trace_pixel == Some([x, y])
+}
+
+pub fn enabled(level: Level, module: &str) -> bool {
+    level <= LOGGER.read().unwrap().level_for(module)
+}
+
+/**
+ * Use the log macros, they don't format disabled messages
+ */
+pub fn log(level: Level, module: &str, message: fmt::Arguments) {
+    let line = format!("{} {:5} {}: {}", now(), level, module, message);
+    println!("{}", line);
+    if let Some(file) = &LOGGER.read().unwrap().file {
+        file.lock().unwrap().write_line(&line);
+    }
+}
+
+/**
+ * Messages about the traced pixel are logged regardless of level
+ */
+pub fn log_pixel(x: usize, y: usize, module: &str, message: fmt::Arguments) {
+    log(
+        Level::Trace,
+        module,
+        format_args!("[{}, {}] {}", x, y, message),
+    );
+}
+
+fn open_file(path: &str) -> io::Result<LogFile> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(LogFile {
+        file,
+        failed: false,
+    })
+}
+
+fn parse_pixel(xy: &str) -> Option<[usize; 2]> {
+    let (x, y) = xy.split_once(',')?;
+    Some([x.trim().parse().ok()?, y.trim().parse().ok()?])
+}
+
+fn now() -> String {
     Utc::now().format(TIME_FORMAT).to_string()
 }
 
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::fractal_log::enabled($level, module_path!()) {
+            $crate::fractal_log::log($level, module_path!(), format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::fractal_log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::fractal_log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::fractal_log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::fractal_log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::fractal_log::Level::Trace, $($arg)+) };
+}
+
+/**
+ * trace_pixel!(x, y, "message {}", value) logs only for the traced pixel
+ */
+#[macro_export]
+macro_rules! trace_pixel {
+    ($x:expr, $y:expr, $($arg:tt)+) => {
+        if $crate::fractal_log::is_traced($x, $y) {
+            $crate::fractal_log::log_pixel($x, $y, module_path!(), format_args!($($arg)+));
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::fractal_log::Level::{Debug, Error, Info, Trace, Warn};
+    use crate::fractal_log::{open_file, parse_level, parse_pixel, Logger};
+
+    fn logger(spec: &str) -> Logger {
+        let mut logger = Logger {
+            level: Info,
+            modules: Vec::new(),
+            file: None,
+            trace_pixel: None,
+        };
+        logger.configure(spec);
+        logger
+    }
+
     #[test]
     fn test_it() {}
+
+    #[test]
+    fn test_level_for() {
+        let logger = logger("warn,rusty_fractals::palette=trace,rusty_fractals=debug");
+
+        assert_eq!(logger.level, Warn);
+        assert_eq!(logger.level_for("fractal_nebula"), Warn);
+        assert_eq!(logger.level_for("rusty_fractals"), Debug);
+        assert_eq!(logger.level_for("rusty_fractals::machine"), Debug);
+        assert_eq!(logger.level_for("rusty_fractals::palette"), Trace);
+        // not a sub module of palette
+        assert_eq!(logger.level_for("rusty_fractals::palette_utils"), Debug);
+    }
+
+    #[test]
+    fn test_configure_again() {
+        let mut logger = logger("rusty_fractals::area=trace");
+        logger.configure("error, rusty_fractals::area = warn, nonsense");
+
+        assert_eq!(logger.level, Error);
+        assert_eq!(logger.modules.len(), 1);
+        assert_eq!(logger.level_for("rusty_fractals::area"), Warn);
+    }
+
+    #[test]
+    fn test_log_file() {
+        let path = std::env::temp_dir().join("rusty_fractals_test_log_dir_x/log.txt");
+        assert!(open_file(path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_log_file_full() {
+        // full disk, the line is dropped without panic
+        let mut file = open_file("/dev/full").unwrap();
+        file.write_line("lost");
+        file.write_line("lost again");
+        assert!(file.failed);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_level("TRACE"), Some(Trace));
+        assert_eq!(parse_level("verbose"), None);
+        assert_eq!(parse_pixel("249, 248"), Some([249, 248]));
+        assert_eq!(parse_pixel("249"), None);
+        assert_eq!(format!("[{:5}]", Info), "[INFO ]");
+    }
 }
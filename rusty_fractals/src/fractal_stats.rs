@@ -1,17 +1,107 @@
 use crate::constants::TAKE_MEASURES_AT_FRAME;
 use crate::data_image::DataImage;
+use crate::iteration_limits::Feedback;
+use crate::pixel_states::DomainElementState;
+use crate::pixel_states::DomainElementState::{FinishedSuccess, FinishedTooLong, FinishedTooShort};
+use crate::{debug, error, info};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Mutex;
+use std::time::Duration;
 
 pub struct Stats {
     data: Mutex<StatsData>,
+    // new elements of current frame, counted by all calculation threads
+    new_elements_too_long: AtomicU32,
+    new_elements_too_short: AtomicU32,
+    new_elements_long: AtomicU32,
+    // frame records are appended to this file, .csv as CSV, otherwise as JSON Lines
+    file: Option<String>,
+}
+
+/**
+ * Calculation phases timed for each frame
+ * Colouring is timed by frame_coloured()
+ */
+#[derive(Clone, Copy, Debug)]
+pub enum Phase {
+    Calculate,
+    Wrap,
+    Translate,
+}
+
+/**
+ * Statistics of one calculated frame, static image is frame 1
+ */
+#[derive(Clone, Debug, Default)]
+pub struct FrameRecord {
+    pub frame: u32,
+    pub new_elements_too_long: u32,
+    pub new_elements_too_short: u32,
+    pub new_elements_long: u32,
+    pub paths_new: u32,
+    pub paths_new_points: u32,
+    // removed when zoomed out of the area, before this frame
+    pub paths_removed: u32,
+    pub paths_total: u32,
+    pub paths_evicted: u64,
+    pub pixels_value_total: u32,
+    pub pixels_value_best: u32,
+    pub calculate: Duration,
+    pub wrap: Duration,
+    pub translate: Duration,
+    pub colour: Duration,
 }
 
 impl Stats {
-    pub fn paths_new_points_amount_add(&self, path_length: usize) {
+    pub fn new_element(&self, state: DomainElementState) {
+        let counter = match state {
+            FinishedSuccess => &self.new_elements_long,
+            FinishedTooShort => &self.new_elements_too_short,
+            FinishedTooLong => &self.new_elements_too_long,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /**
+     * Good path was recorded
+     */
+    pub fn path_new(&self, path_length: usize) {
         let data = &mut self.data.lock().unwrap();
 
+        data.paths_new_amount += 1;
         data.paths_new_points_amount += path_length as u32;
     }
+
+    /**
+     * Number of the frame being calculated
+     */
+    pub fn frame(&self) -> u32 {
+        self.data.lock().unwrap().frame + 1
+    }
+
+    /**
+     * Path points recorded by the frame being calculated
+     */
+    pub fn points(&self) -> u64 {
+        self.data.lock().unwrap().paths_new_points_amount as u64
+    }
+
+    pub fn paths_removed(&self, amount: usize) {
+        self.data.lock().unwrap().paths_removed_amount += amount as u32;
+    }
+
+    pub fn phase_time(&self, phase: Phase, duration: Duration) {
+        let data = &mut self.data.lock().unwrap();
+        match phase {
+            Phase::Calculate => data.current.calculate += duration,
+            Phase::Wrap => data.current.wrap += duration,
+            Phase::Translate => data.current.translate += duration,
+        }
+    }
 }
 
 pub struct StatsData {
@@ -23,7 +113,12 @@ pub struct StatsData {
     // The amount of newly added paths is not the same as the amount of red elementLong
     paths_total_amount: u32,
 
+    paths_new_amount: u32,
     paths_new_points_amount: u32,
+    paths_removed_amount: u32,
+    // Paths evicted because paths memory cap was reached
+    paths_evicted_amount: u64,
+    paths_evicted_points: u64,
     pixels_value_total: u32,
     pixels_value_best: u32,
 
@@ -45,6 +140,11 @@ pub struct StatsData {
     pixels_value_best_measure: u32,
     pixels_value_best_tolerance: u32,
     average_path_length_measure: u32,
+
+    // phase times of the frame being calculated
+    current: FrameRecord,
+    frame: u32,
+    records: Vec<FrameRecord>,
 }
 
 impl Stats {
@@ -52,9 +152,9 @@ impl Stats {
     fn remember_this(&self, data_image: &DataImage) {
         let data = &mut self.data.lock().unwrap();
 
-        println!("new_elements_long  {}", data.new_elements_long);
-        println!("pixels_value_total {}", data.pixels_value_total);
-        println!("paths_total_amount {}", data.paths_total_amount);
+        info!("new_elements_long  {}", data.new_elements_long);
+        info!("pixels_value_total {}", data.pixels_value_total);
+        info!("paths_total_amount {}", data.paths_total_amount);
 
         data.new_elements_long_measure = data.new_elements_long;
         data.pixels_value_total_measure = data.pixels_value_total;
@@ -70,15 +170,17 @@ impl Stats {
         data.paths_total_amount_tolerance = (data.paths_total_amount_measure as f64 * 0.5) as u32;
         data.pixels_value_best_tolerance = (data.pixels_value_best_measure as f64 * 0.5) as u32;
 
-        println!("elementsLong_measure        {} ", data.new_elements_long_measure);
-        println!("pixels_value_total_measure  {} ", data.pixels_value_total_measure);
-        println!("pixels_value_best_measure   {} ", data.pixels_value_best_measure);
-        println!("paths_total_amount_measure  {} ", data.paths_total_amount_measure);
-        println!("average_path_length_measure {} ", data.average_path_length_measure);
+        info!("elementsLong_measure        {} ", data.new_elements_long_measure);
+        info!("pixels_value_total_measure  {} ", data.pixels_value_total_measure);
+        info!("pixels_value_best_measure   {} ", data.pixels_value_best_measure);
+        info!("paths_total_amount_measure  {} ", data.paths_total_amount_measure);
+        info!("average_path_length_measure {} ", data.average_path_length_measure);
     }
 
     #[rustfmt::skip]
     pub fn update(&self, data_image: &DataImage, it: u32) {
+        self.update_evicted(data_image);
+
         // Check if Stats should remember this iteration data for subsequent comparison
         if it == TAKE_MEASURES_AT_FRAME {
             self.remember_this(data_image);
@@ -130,12 +232,12 @@ impl Stats {
                     > data.new_elements_long_tolerance;
             }
 
-            println!("not_enough_pixels_total_value {}", data.not_enough_pixels_total_value);
-            println!("less_pixels_total_value       {}", data.less_pixels_total_value);
-            println!("less_pixels_best_value        {} ({} < {})", data.less_pixels_best_value, data.pixels_value_best, data.pixels_value_best_measure);
-            println!("too_many_pixels_total_value   {}", data.too_many_pixels_total_value);
-            println!("too_many_paths_total          {}", data.too_many_paths_total);
-            println!("not_enough_long_elements      {}", data.not_enough_long_elements);
+            debug!("not_enough_pixels_total_value {}", data.not_enough_pixels_total_value);
+            debug!("less_pixels_total_value       {}", data.less_pixels_total_value);
+            debug!("less_pixels_best_value        {} ({} < {})", data.less_pixels_best_value, data.pixels_value_best, data.pixels_value_best_measure);
+            debug!("too_many_pixels_total_value   {}", data.too_many_pixels_total_value);
+            debug!("too_many_paths_total          {}", data.too_many_paths_total);
+            debug!("not_enough_long_elements      {}", data.not_enough_long_elements);
 
             let average_path_length =
                 data.pixels_value_total as f64 / data.paths_total_amount as f64;
@@ -144,12 +246,34 @@ impl Stats {
             let domain_elements_to_new_calculation_path_points =
                 data.paths_new_points_amount as f64 / new_elements_all as f64;
 
-            println!("average_path_length                             {} ({})", average_path_length, data.average_path_length_measure);
-            println!("domain_elements_to_new_calculation_path_points: {}", domain_elements_to_new_calculation_path_points);
+            debug!("average_path_length                             {} ({})", average_path_length, data.average_path_length_measure);
+            debug!("domain_elements_to_new_calculation_path_points: {}", domain_elements_to_new_calculation_path_points);
+        }
+    }
+
+    fn update_evicted(&self, data_image: &DataImage) {
+        let paths = data_image.paths.lock().unwrap();
+        let data = &mut self.data.lock().unwrap();
+
+        if paths.evicted_paths > data.paths_evicted_amount {
+            info!(
+                "evicted paths since last frame: {} ({} points)",
+                paths.evicted_paths - data.paths_evicted_amount,
+                paths.evicted_points - data.paths_evicted_points
+            );
         }
+        data.paths_evicted_amount = paths.evicted_paths;
+        data.paths_evicted_points = paths.evicted_points;
     }
 
-    pub fn clean(&mut self) {
+    /**
+     * Calculation of new frame starts, per frame counters are reset
+     */
+    pub fn clean(&self) {
+        self.new_elements_too_long.store(0, Ordering::Relaxed);
+        self.new_elements_too_short.store(0, Ordering::Relaxed);
+        self.new_elements_long.store(0, Ordering::Relaxed);
+
         let data = &mut self.data.lock().unwrap();
 
         data.new_elements_too_long = 0;
@@ -158,30 +282,128 @@ impl Stats {
         data.paths_total_amount = 0;
         data.pixels_value_total = 0;
         data.pixels_value_best = 0;
+        data.paths_new_amount = 0;
         data.paths_new_points_amount = 0;
+        data.current = FrameRecord::default();
+    }
+
+    /**
+     * Calculation of frame finished, colouring may follow
+     * returns the frame number
+     */
+    pub fn frame_done(&self, data_image: &DataImage) -> u32 {
+        let (paths_dynamic, paths_evicted) = {
+            let paths = data_image.paths.lock().unwrap();
+            (paths.paths_amount() as u32, paths.evicted_paths)
+        };
+        let pixels_value_total: u64 = (0..data_image.height_y)
+            .flat_map(|y| (0..data_image.width_x).map(move |x| (x, y)))
+            .map(|(x, y)| data_image.value_at(x, y) as u64)
+            .sum();
+        let pixels_value_total = pixels_value_total.min(u32::MAX as u64) as u32;
+        let pixels_value_best = data_image.best_four_chunks_value();
+
+        let data = &mut self.data.lock().unwrap();
+
+        data.new_elements_too_long = self.new_elements_too_long.load(Ordering::Relaxed);
+        data.new_elements_too_short = self.new_elements_too_short.load(Ordering::Relaxed);
+        data.new_elements_long = self.new_elements_long.load(Ordering::Relaxed);
+        // static image paths are not remembered
+        data.paths_total_amount = if data_image.is_dynamic() {
+            paths_dynamic
+        } else {
+            data.paths_new_amount
+        };
+        data.pixels_value_total = pixels_value_total;
+        data.pixels_value_best = pixels_value_best;
+        data.frame += 1;
+
+        let record = FrameRecord {
+            frame: data.frame,
+            new_elements_too_long: data.new_elements_too_long,
+            new_elements_too_short: data.new_elements_too_short,
+            new_elements_long: data.new_elements_long,
+            paths_new: data.paths_new_amount,
+            paths_new_points: data.paths_new_points_amount,
+            paths_removed: data.paths_removed_amount,
+            paths_total: data.paths_total_amount,
+            paths_evicted,
+            pixels_value_total,
+            pixels_value_best,
+            ..data.current.clone()
+        };
+        data.records.push(record);
+        // paths are removed between frames, after clean()
+        data.paths_removed_amount = 0;
+        data.frame
+    }
+
+    /**
+     * Frame was coloured, which completes its record
+     * Zoom colours the frame while the next frame is calculated.
+     */
+    pub fn frame_coloured(&self, frame: u32, duration: Duration) {
+        let data = &mut self.data.lock().unwrap();
+        if let Some(record) = data.records.iter_mut().find(|r| r.frame == frame) {
+            record.colour = duration;
+            if let Some(file) = &self.file {
+                // stats are a by-product, calculation continues without them
+                if let Err(e) = append_record(file, record) {
+                    error!("could not write stats file {}: {}", file, e);
+                }
+            }
+        }
+    }
+
+    /**
+     * Result of the last comparison to the measured frame, see update()
+     */
+    pub fn feedback(&self) -> Feedback {
+        let data = self.data.lock().unwrap();
+        Feedback {
+            not_enough_pixels: data.not_enough_pixels_best_value
+                || data.not_enough_pixels_total_value,
+            not_enough_long_elements: data.not_enough_long_elements,
+            too_many_pixels: data.too_many_pixels_total_value,
+            too_many_paths: data.too_many_paths_total,
+        }
+    }
+
+    pub fn last_record(&self) -> Option<FrameRecord> {
+        self.data.lock().unwrap().records.last().cloned()
+    }
+
+    pub fn records(&self) -> Vec<FrameRecord> {
+        self.data.lock().unwrap().records.clone()
     }
 
     pub fn print(&self) {
         let data = &mut self.data.lock().unwrap();
 
-        println!("new_elements_too_long   {}", data.new_elements_too_long);
-        println!("new_elements_too_short  {}", data.new_elements_too_short);
-        println!("new_elements_long       {}", data.new_elements_long);
-        println!("paths_total_amount      {}", data.paths_total_amount);
-        println!("pixels_value_total      {}", data.pixels_value_total);
-        println!("pixels_value_best       {}", data.pixels_value_best);
-        println!("paths_new_points_amount {}", data.paths_new_points_amount);
+        info!("new_elements_too_long   {}", data.new_elements_too_long);
+        info!("new_elements_too_short  {}", data.new_elements_too_short);
+        info!("new_elements_long       {}", data.new_elements_long);
+        info!("paths_total_amount      {}", data.paths_total_amount);
+        info!("pixels_value_total      {}", data.pixels_value_total);
+        info!("pixels_value_best       {}", data.pixels_value_best);
+        info!("paths_new_points_amount {}", data.paths_new_points_amount);
+        info!("paths_evicted_amount    {}", data.paths_evicted_amount);
+        info!("paths_evicted_points    {}", data.paths_evicted_points);
     }
 }
 
-pub fn init() -> Stats {
+pub fn init(file: Option<String>) -> Stats {
     Stats {
         data: Mutex::new(StatsData {
             new_elements_too_long: 0,
             new_elements_too_short: 0,
             new_elements_long: 0,
             paths_total_amount: 0,
+            paths_new_amount: 0,
             paths_new_points_amount: 0,
+            paths_removed_amount: 0,
+            paths_evicted_amount: 0,
+            paths_evicted_points: 0,
             pixels_value_total: 0,
             pixels_value_best: 0,
             not_enough_pixels_total_value: false,
@@ -200,12 +422,154 @@ pub fn init() -> Stats {
             pixels_value_best_measure: 0,
             pixels_value_best_tolerance: 0,
             average_path_length_measure: 0,
+            current: FrameRecord::default(),
+            frame: 0,
+            records: Vec::new(),
         }),
+        new_elements_too_long: AtomicU32::new(0),
+        new_elements_too_short: AtomicU32::new(0),
+        new_elements_long: AtomicU32::new(0),
+        file,
     }
 }
 
+const CSV_HEADER: &str = "frame,new_elements_too_long,new_elements_too_short,new_elements_long,\
+paths_new,paths_new_points,paths_removed,paths_total,paths_evicted,\
+pixels_value_total,pixels_value_best,calculate_ms,wrap_ms,translate_ms,colour_ms";
+
+// file of the previous run is replaced at frame 1
+fn append_record(file: &str, record: &FrameRecord) -> io::Result<()> {
+    let csv = file.ends_with(".csv");
+    let mut f = if record.frame == 1 {
+        let mut f = File::create(file)?;
+        if csv {
+            writeln!(f, "{}", CSV_HEADER)?;
+        }
+        f
+    } else {
+        OpenOptions::new().append(true).create(true).open(file)?
+    };
+    let line = if csv { to_csv(record) } else { to_json(record) };
+    writeln!(f, "{}", line)
+}
+
+fn ms(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn to_csv(r: &FrameRecord) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        r.frame,
+        r.new_elements_too_long,
+        r.new_elements_too_short,
+        r.new_elements_long,
+        r.paths_new,
+        r.paths_new_points,
+        r.paths_removed,
+        r.paths_total,
+        r.paths_evicted,
+        r.pixels_value_total,
+        r.pixels_value_best,
+        ms(r.calculate),
+        ms(r.wrap),
+        ms(r.translate),
+        ms(r.colour)
+    )
+}
+
+// same names as CSV header
+pub fn to_json(r: &FrameRecord) -> String {
+    let values = to_csv(r);
+    let fields: Vec<String> = CSV_HEADER
+        .split(',')
+        .zip(values.split(','))
+        .map(|(name, value)| format!("\"{}\":{}", name, value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::fractal::init_trivial_static_config;
+    use crate::fractal_stats::Phase::Calculate;
+    use crate::fractal_stats::{init, to_csv, to_json, FrameRecord, CSV_HEADER};
+    use crate::pixel_states::DomainElementState::{ActiveNew, FinishedSuccess, FinishedTooShort};
+    use crate::{area, data_image};
+    use std::fs;
+    use std::time::Duration;
+
     #[test]
     fn test_it() {}
+
+    #[test]
+    fn test_frame_done() {
+        let conf = init_trivial_static_config();
+        let area = area::init(&conf);
+        let data_image = data_image::init(&conf, &area);
+        data_image.mo_px_at(1, 1).as_mut().unwrap().value = 5;
+        let stats = init(None);
+
+        for frame in 1..3 {
+            stats.clean();
+            stats.new_element(FinishedSuccess);
+            stats.new_element(FinishedSuccess);
+            stats.new_element(FinishedTooShort);
+            stats.new_element(ActiveNew);
+            stats.path_new(frame);
+            stats.phase_time(Calculate, Duration::from_millis(3));
+            stats.phase_time(Calculate, Duration::from_millis(4));
+            assert_eq!(stats.frame_done(&data_image), frame as u32);
+            stats.frame_coloured(frame as u32, Duration::from_millis(2));
+        }
+
+        let records = stats.records();
+        assert_eq!(records.len(), 2);
+        let r = &records[1];
+        assert_eq!(r.frame, 2);
+        assert_eq!(r.new_elements_long, 2);
+        assert_eq!(r.new_elements_too_short, 1);
+        assert_eq!(r.new_elements_too_long, 0);
+        assert_eq!(r.paths_new, 1);
+        assert_eq!(r.paths_new_points, 2);
+        assert_eq!(r.paths_total, 1);
+        assert_eq!(r.pixels_value_total, 5);
+        assert_eq!(r.calculate, Duration::from_millis(7));
+        assert_eq!(r.colour, Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_export() {
+        let record = FrameRecord {
+            frame: 3,
+            paths_total: 10,
+            calculate: Duration::from_micros(1500),
+            ..FrameRecord::default()
+        };
+
+        let csv = to_csv(&record);
+        assert_eq!(csv, "3,0,0,0,0,0,0,10,0,0,0,1.500,0.000,0.000,0.000");
+        assert_eq!(CSV_HEADER.split(',').count(), csv.split(',').count());
+        let json = to_json(&record);
+        assert!(json.starts_with("{\"frame\":3,\"new_elements_too_long\":0,"));
+        assert!(json.ends_with(
+            "\"calculate_ms\":1.500,\"wrap_ms\":0.000,\"translate_ms\":0.000,\"colour_ms\":0.000}"
+        ));
+
+        let file = std::env::temp_dir().join("rusty_fractals_test_stats.csv");
+        let file = file.to_str().unwrap().to_string();
+        let stats = init(Some(file.clone()));
+        let conf = init_trivial_static_config();
+        let data_image = data_image::init(&conf, &area::init(&conf));
+        for frame in 1..4 {
+            stats.frame_done(&data_image);
+            stats.frame_coloured(frame, Duration::ZERO);
+        }
+        let text = fs::read_to_string(&file).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[3].starts_with("3,"));
+        fs::remove_file(file).unwrap();
+    }
 }
@@ -0,0 +1,234 @@
+use crate::fractal::FractalCalculationType::{
+    DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot,
+};
+use crate::fractal::{FractalConfig, FractalMath, MemType};
+use crate::info;
+use crate::path_store::PathStorage::Memory;
+use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use crate::{machine, value_grid};
+use std::fs;
+use std::path::Path;
+
+/**
+ * Golden grid regression harness
+ * Fractal crates render a small reference version of their config without window,
+ * and compare the value grid to the golden grid stored in the crate directory.
+ * Run tests with UPDATE_GOLDEN=1 to (re)create golden grids after an intended change.
+ */
+const GOLDEN_FILE: &str = "golden.grid";
+const GOLDEN_WIDTH: usize = 64;
+const GOLDEN_ITERATION_MAX: u32 = 2000;
+const GOLDEN_SEED: u64 = 1;
+
+/**
+ * Small and fast version of fractal config
+ * Same area, lower resolution and iteration_max, single frame, no pixel wrap.
+ * iteration_max stays well above iteration_min, so that long paths are still found.
+ * Long iteration_min, e.g. of Infinite orbits inside of the set, keeps its share of iteration_max.
+ */
+pub fn reference_config(mut config: FractalConfig) -> FractalConfig {
+    config.height_y = (GOLDEN_WIDTH * config.height_y / config.width_x).max(1);
+    config.width_x = GOLDEN_WIDTH;
+    let iteration_max = config
+        .iteration_max
+        .min(GOLDEN_ITERATION_MAX.max(config.iteration_min * 10));
+    if config.iteration_min * 10 > GOLDEN_ITERATION_MAX {
+        config.iteration_min = (config.iteration_min as u64 * iteration_max as u64
+            / config.iteration_max.max(1) as u64) as u32;
+    }
+    config.iteration_max = iteration_max;
+    config.resolution_multiplier = Single;
+    config.fractal_calc_type = match config.fractal_calc_type {
+        StaticSequenceMandelbrot => StaticImageMandelbrot,
+        DynamicSequenceNebula => StaticImageNebula,
+        other => other,
+    };
+    config.path_storage = Memory;
+    config.paths_memory_max_mb = 0;
+    config.stats_file = None;
+    config.diagnostics = false;
+    config.seed = GOLDEN_SEED;
+    config
+}
+
+/**
+ * Calculate fractal without window
+ * returns pixel values, row by row
+ */
+pub fn render_grid<F, M>(config: &FractalConfig, fractal: F) -> Vec<u32>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    let machine = machine::init(config, fractal);
+    machine
+        .execute_calculation()
+        .expect("could not calculate golden grid");
+
+    value_grid::snapshot(&machine.data_image, 1).values
+}
+
+/**
+ * Render reference version of config and compare it to golden grid in crate_dir
+ * Golden grid is written only with UPDATE_GOLDEN set, missing one fails the test.
+ */
+pub fn assert_golden<F, M>(crate_dir: &str, config: FractalConfig, fractal: F)
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    let config = reference_config(config);
+    let grid = render_grid(&config, fractal);
+    let file = Path::new(crate_dir).join(GOLDEN_FILE);
+    // golden grid of zeros wouldn't notice any change
+    assert!(
+        grid.iter().any(|v| *v > 0),
+        "reference grid of {} has no visible pixels",
+        config.name
+    );
+
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        info!("assert_golden() write {}", file.display());
+        fs::write(&file, to_text(config.width_x, config.height_y, &grid))
+            .expect("could not write golden grid");
+        return;
+    }
+    compare(&file, config.width_x, config.height_y, &grid);
+}
+
+fn compare(file: &Path, width_x: usize, height_y: usize, grid: &[u32]) {
+    assert!(
+        file.exists(),
+        "missing golden grid {}, create it with UPDATE_GOLDEN=1",
+        file.display()
+    );
+    let text = fs::read_to_string(file).expect("could not read golden grid");
+    let (golden_width_x, golden_height_y, golden) = from_text(&text);
+    assert_eq!(
+        (golden_width_x, golden_height_y),
+        (width_x, height_y),
+        "golden grid size changed, {}",
+        file.display()
+    );
+    let different = grid.iter().zip(&golden).filter(|(a, b)| a != b).count();
+    if different > 0 {
+        let i = grid.iter().zip(&golden).position(|(a, b)| a != b).unwrap();
+        panic!(
+            "{} of {} pixels differ from {}, first at [{}, {}]: {} instead of {}",
+            different,
+            grid.len(),
+            file.display(),
+            i % width_x,
+            i / width_x,
+            grid[i],
+            golden[i]
+        );
+    }
+}
+
+// first line is width and height, then one line of values for each row
+fn to_text(width_x: usize, height_y: usize, grid: &[u32]) -> String {
+    let mut text = format!("{} {}\n", width_x, height_y);
+    for row in grid.chunks(width_x) {
+        let values: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+        text.push_str(&values.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+fn from_text(text: &str) -> (usize, usize, Vec<u32>) {
+    let mut numbers = text
+        .split_whitespace()
+        .map(|n| n.parse::<u64>().expect("could not parse golden grid"));
+    let width_x = numbers.next().expect("golden grid is empty") as usize;
+    let height_y = numbers.next().expect("golden grid is empty") as usize;
+    let grid: Vec<u32> = numbers.map(|n| n as u32).collect();
+    assert_eq!(grid.len(), width_x * height_y, "golden grid is incomplete");
+    (width_x, height_y, grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::FractalCalculationType::StaticImageNebula;
+    use crate::fractal::OrbitType::Infinite;
+    use crate::fractal::{init_trivial_fractal, init_trivial_static_config, FractalConfig};
+    use crate::golden::{compare, from_text, reference_config, render_grid, to_text};
+    use crate::palettes::PaletteName::BlueToWhiteCircleUp;
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+
+    fn nebula_config() -> FractalConfig {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = StaticImageNebula;
+        conf.width_x = 40;
+        conf.height_y = 30;
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_min = 20;
+        conf.iteration_max = 500;
+        conf.resolution_multiplier = Square3;
+        conf.palette = BlueToWhiteCircleUp;
+        conf
+    }
+
+    fn render_with_threads(threads: usize, seed: u64) -> Vec<u32> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let mut conf = nebula_config();
+        conf.seed = seed;
+        pool.install(|| render_grid(&conf, init_trivial_fractal()))
+    }
+
+    #[test]
+    fn test_render_grid_thread_count() {
+        let single = render_with_threads(1, 7);
+
+        assert!(single.iter().any(|v| *v > 0));
+        assert_eq!(render_with_threads(4, 7), single);
+        assert_eq!(render_with_threads(3, 8), single);
+    }
+
+    #[test]
+    fn test_reference_config() {
+        let mut conf = nebula_config();
+        conf.width_x = 1280;
+        conf.height_y = 720;
+        conf.iteration_max = 80000;
+
+        let reference = reference_config(conf);
+
+        assert_eq!(reference.width_x, 64);
+        assert_eq!(reference.height_y, 36);
+        assert_eq!(reference.iteration_max, 2000);
+        assert_eq!(reference.iteration_min, 20);
+
+        let mut conf = nebula_config();
+        conf.orbits = Infinite;
+        conf.iteration_min = 3000;
+        conf.iteration_max = 180_000;
+
+        let reference = reference_config(conf);
+
+        assert_eq!(reference.iteration_max, 30_000);
+        assert_eq!(reference.iteration_min, 500);
+    }
+
+    #[test]
+    fn test_text() {
+        let grid = vec![0, 1, 2, 3, 4, 5];
+
+        let text = to_text(3, 2, &grid);
+
+        assert_eq!(text, "3 2\n0 1 2\n3 4 5\n");
+        assert_eq!(from_text(&text), (3, 2, grid));
+    }
+
+    #[test]
+    #[should_panic(expected = "missing golden grid")]
+    fn test_assert_golden_missing() {
+        let file = std::env::temp_dir().join("rusty_fractals_test_golden_missing.grid");
+        compare(&file, 3, 2, &[0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::fractal::FractalConfig;
+use crate::info;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/**
+ * Iteration limits of running calculation
+ * Zoom adjusts them between frames from Stats feedback,
+ * by at most update_max and update_min per frame.
+ * Adjusted limits stay within the configured iteration_min and iteration_max_limit,
+ * iteration_max goes back down towards the configured value when the image gets too dense.
+ */
+pub struct IterationLimits {
+    min: AtomicU32,
+    max: AtomicU32,
+    // configured iteration_min and iteration_max, adjustments don't go below them
+    min_initial: u32,
+    max_initial: u32,
+    // adjustments don't go above it
+    max_limit: u32,
+    update_min: u32,
+    update_max: u32,
+}
+
+/**
+ * How the last zoom frame compares to the frame measured by Stats
+ */
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Feedback {
+    // image got sparse
+    pub not_enough_pixels: bool,
+    pub not_enough_long_elements: bool,
+    // image got too dense
+    pub too_many_pixels: bool,
+    pub too_many_paths: bool,
+}
+
+impl IterationLimits {
+    pub fn min(&self) -> u32 {
+        self.min.load(Ordering::Relaxed)
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max.load(Ordering::Relaxed)
+    }
+
+    /**
+     * Sparse image needs longer calculations, as zoom goes deeper, and shorter paths are welcome again.
+     * Too dense image ignores more short paths, to keep its detail and the amount of paths.
+     */
+    pub fn adjust(&self, feedback: &Feedback) {
+        let (min, max) = (self.min(), self.max());
+        let (mut new_min, mut new_max) = (min, max);
+
+        if feedback.not_enough_pixels || feedback.not_enough_long_elements {
+            new_max = max.saturating_add(self.update_max).min(self.max_limit);
+            new_min = min.saturating_sub(self.update_min).max(self.min_initial);
+        } else if feedback.too_many_pixels || feedback.too_many_paths {
+            // path must fit in between
+            new_min = min
+                .saturating_add(self.update_min)
+                .min(max.saturating_sub(1))
+                .max(min);
+            new_max = max
+                .saturating_sub(self.update_max)
+                .max(self.max_initial)
+                .max(new_min.saturating_add(1));
+        }
+
+        if (new_min, new_max) != (min, max) {
+            info!(
+                "iteration_min {} -> {}, iteration_max {} -> {}",
+                min, new_min, max, new_max
+            );
+            self.min.store(new_min, Ordering::Relaxed);
+            self.max.store(new_max, Ordering::Relaxed);
+        }
+    }
+}
+
+pub fn init(config: &FractalConfig) -> IterationLimits {
+    IterationLimits {
+        min: AtomicU32::new(config.iteration_min),
+        max: AtomicU32::new(config.iteration_max),
+        min_initial: config.iteration_min,
+        max_initial: config.iteration_max,
+        max_limit: config.iteration_max_limit.max(config.iteration_max),
+        update_min: config.update_min,
+        update_max: config.update_max,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::init_trivial_static_config;
+    use crate::iteration_limits::{init, Feedback};
+
+    #[test]
+    fn test_adjust_sparse() {
+        let mut conf = init_trivial_static_config();
+        conf.iteration_min = 40;
+        conf.iteration_max = 1000;
+        conf.update_min = 30;
+        conf.update_max = 150;
+        let limits = init(&conf);
+        let sparse = Feedback {
+            not_enough_pixels: true,
+            ..Feedback::default()
+        };
+
+        limits.adjust(&Feedback::default());
+        assert_eq!((limits.min(), limits.max()), (40, 1000));
+
+        limits.adjust(&sparse);
+        limits.adjust(&sparse);
+        // min doesn't go below configured value
+        assert_eq!((limits.min(), limits.max()), (40, 1300));
+    }
+
+    #[test]
+    fn test_adjust_dense() {
+        let mut conf = init_trivial_static_config();
+        conf.iteration_min = 40;
+        conf.iteration_max = 100;
+        conf.update_min = 30;
+        conf.update_max = 150;
+        let limits = init(&conf);
+        let dense = Feedback {
+            too_many_paths: true,
+            ..Feedback::default()
+        };
+
+        limits.adjust(&dense);
+        assert_eq!((limits.min(), limits.max()), (70, 100));
+        limits.adjust(&dense);
+        limits.adjust(&dense);
+        // min stays below max
+        assert_eq!((limits.min(), limits.max()), (99, 100));
+
+        let sparse_and_dense = Feedback {
+            not_enough_long_elements: true,
+            too_many_pixels: true,
+            ..Feedback::default()
+        };
+        limits.adjust(&sparse_and_dense);
+        assert_eq!((limits.min(), limits.max()), (69, 250));
+    }
+
+    #[test]
+    fn test_adjust_limit() {
+        let mut conf = init_trivial_static_config();
+        conf.iteration_min = 40;
+        conf.iteration_max = 1000;
+        conf.update_min = 30;
+        conf.update_max = 150;
+        conf.iteration_max_limit = 1200;
+        let limits = init(&conf);
+        let sparse = Feedback {
+            not_enough_pixels: true,
+            ..Feedback::default()
+        };
+        let dense = Feedback {
+            too_many_pixels: true,
+            ..Feedback::default()
+        };
+
+        limits.adjust(&sparse);
+        limits.adjust(&sparse);
+        // max doesn't go above the limit
+        assert_eq!((limits.min(), limits.max()), (40, 1200));
+
+        limits.adjust(&dense);
+        assert_eq!((limits.min(), limits.max()), (70, 1050));
+        limits.adjust(&dense);
+        // max doesn't go below configured value
+        assert_eq!((limits.min(), limits.max()), (100, 1000));
+
+        // configured max above the limit is kept
+        conf.iteration_max_limit = 500;
+        let limits = init(&conf);
+        limits.adjust(&sparse);
+        assert_eq!((limits.min(), limits.max()), (40, 1000));
+    }
+}
@@ -1,13 +1,25 @@
 extern crate core;
 
 pub mod area;
+pub mod canvas;
+pub mod chunks;
+pub mod config_builder;
 pub mod constants;
+pub mod control;
 pub mod data_image;
 pub mod data_px;
+pub mod diagnostics;
+pub mod distributed;
+pub mod dzi;
+pub mod error;
+pub mod estimator;
 pub mod files;
+pub mod formulas;
 pub mod fractal;
 pub mod fractal_log;
 pub mod fractal_stats;
+pub mod golden;
+pub mod iteration_limits;
 pub mod machine;
 pub mod mathematician;
 pub mod mem;
@@ -16,8 +28,20 @@ pub mod mem_phoenix;
 pub mod palette;
 pub mod palette_utils;
 pub mod palettes;
+pub mod path_store;
+pub mod path_store_disk;
 pub mod perfect_colour_distribution;
 pub mod pixel_states;
+pub mod plugin;
+pub mod presets;
+pub mod progress;
+pub mod render;
+pub mod render_sink;
 pub mod resolution_multiplier;
+#[cfg(feature = "window")]
 pub mod rusty_tests;
+pub mod tile_server;
+pub mod tiled;
+pub mod value_grid;
+#[cfg(feature = "window")]
 pub mod application;
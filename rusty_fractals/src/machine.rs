@@ -1,30 +1,48 @@
-use crate::application::Application;
-use crate::area::Area;
-use crate::constants::CALCULATION_BOUNDARY;
+use crate::area::{Area, AreaDataCopy};
+use crate::canvas::Canvas;
+use crate::constants::{
+    CALCULATION_BOUNDARY, MARIANI_SILVER_MIN_SIZE, PROGRESSIVE_STEP_MAX, ZOOM_FAILED_FRAMES_MAX,
+};
+use crate::control::Control;
 use crate::data_image::DataImage;
 use crate::data_px::{active_new, hibernated_deep_black};
+use crate::error::FractalError::{Config, Panicked, Zoom};
+use crate::error::Result;
+use crate::estimator::{format_duration, Estimate, Eta};
 use crate::fractal::FractalCalculationType::StaticImageNebula;
+use crate::fractal::MandelbrotRendering::{MarianiSilver, MarianiSilverVerified, Progressive};
 use crate::fractal::{
-    init_trivial_static_config, FractalCalculationType, FractalConfig, FractalMath, MemType,
-    OrbitType, TrivialFractal,
+    init_trivial_static_config, FractalCalculationType, FractalConfig, FractalMath,
+    MandelbrotRendering, MemType, OrbitType, TrivialFractal,
 };
-use crate::fractal_log::now;
+use crate::fractal_stats::Phase::{Calculate, Translate, Wrap};
 use crate::fractal_stats::Stats;
+use crate::iteration_limits::IterationLimits;
 use crate::mem::Mem;
 use crate::palette::Palette;
 use crate::palettes::new_palette_by_name;
 use crate::perfect_colour_distribution::{
-    perfectly_colour_mandelbrot_values, perfectly_colour_nebula_values,
+    perfectly_colour_mandelbrot_values, perfectly_colour_nebula_grid,
+    perfectly_colour_nebula_values,
 };
 use crate::pixel_states::DomainElementState;
 use crate::pixel_states::DomainElementState::{FinishedSuccess, FinishedTooLong, FinishedTooShort};
+use crate::progress::{Progress, ProgressListener, Stage};
+use crate::render;
+use crate::render_sink::RenderSink;
 use crate::resolution_multiplier::ResolutionMultiplier;
-use crate::{area, data_image, fractal, fractal_stats, pixel_states};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use crate::value_grid::ValueGrid;
+use crate::{
+    area, control, data_image, diagnostics, estimator, fractal, fractal_stats, iteration_limits,
+    pixel_states, progress, value_grid,
+};
+use crate::{debug, error, info, trace_pixel};
 use rayon::prelude::*;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
 use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 use FractalCalculationType::{
     DynamicSequenceNebula, StaticImageMandelbrot, StaticSequenceMandelbrot,
@@ -33,17 +51,17 @@ use FractalCalculationType::{
 
 /**
  * Machine owns all data
- * Machine is owned by Application
+ * Machine is owned by Application, render or any other caller, more Machines may calculate at once.
  */
-pub struct Machine<'lt, F, M>
+pub struct Machine<F, M>
 where
-    F: FractalMath<M> + 'static,
-    M: MemType<M> + 'static,
+    F: FractalMath<M>,
+    M: MemType<M>,
 {
     /*
      * Fractal related values
      */
-    pub name: &'lt str,
+    pub name: String,
     pub fractal: F,
     pub fractal_calc_type: FractalCalculationType,
     // area config
@@ -61,18 +79,27 @@ where
     pub palette_zero: Palette,
     // calculation config
     pub orbits: OrbitType, // fractal::finite_orbits / infinite_orbits
-    pub iteration_min: u32,
-    pub iteration_max: u32,
-    pub update_max: u32,
-    pub update_min: u32,
+    // iteration_min and iteration_max, adjusted during zoom
+    pub iterations: IterationLimits,
     // calculation statistics for video zoom
     pub stats: Stats,
+    // pause, resume or cancel running calculation
+    pub control: Arc<Control>,
+    // remaining time of running calculation
+    pub eta: Eta,
+    // reports stages and chunks of running calculation
+    pub progress: Progress,
     //  nebula specific - use multiple numbers for each screen pixel
     pub resolution_multiplier: ResolutionMultiplier,
+    // mandelbrot specific
+    pub mandelbrot_rendering: MandelbrotRendering,
     /*
      * Application related values
      */
-    pub app_ref: Option<Arc<Mutex<Application<F, M>>>>,
+    // window, doesn't reference Machine back
+    pub canvas: Option<Arc<dyn Canvas>>,
+    // receives intermediate results, also without window
+    pub render_sink: Option<Arc<dyn RenderSink>>,
     /*
      * Machine (Self) related values
      */
@@ -80,7 +107,7 @@ where
     phantom_m_type: PhantomData<M>, // need to use M so compiler won't complain
 }
 
-pub fn init<F, M>(config: &FractalConfig, fractal: F) -> Machine<'static, F, M>
+pub fn init<F, M>(config: &FractalConfig, fractal: F) -> Machine<F, M>
 where
     F: FractalMath<M>,
     M: MemType<M>,
@@ -88,7 +115,7 @@ where
     let area: Area = area::init(config);
     Machine {
         fractal,
-        name: config.name,
+        name: config.name.clone(),
         fractal_calc_type: config.fractal_calc_type,
         data_image: data_image::init(config, &area),
         area,
@@ -99,65 +126,94 @@ where
         center_im: config.center_im,
         // mandelbrot fractals calculate from 0
         // nebula fractals include only calculations longer then min
-        iteration_min: config.iteration_min,
-        iteration_max: config.iteration_max,
+        iterations: iteration_limits::init(config),
         palette: new_palette_by_name(&config.palette),
         palette_zero: new_palette_by_name(&config.palette_zero),
 
         resolution_multiplier: config.resolution_multiplier,
+        mandelbrot_rendering: config.mandelbrot_rendering,
         orbits: OrbitType::Finite,
-        update_max: config.update_max,
-        update_min: config.update_min,
-        stats: fractal_stats::init(),
-        // application reference
-        app_ref: None,
+        stats: fractal_stats::init(config.stats_file.clone()),
+        control: Arc::new(control::init()),
+        eta: estimator::init_eta(),
+        progress: progress::init(),
+        // window
+        canvas: None,
+        render_sink: None,
         // machine values
         last_partial_refresh: Arc::new(Mutex::new(None)),
         phantom_m_type: PhantomData::default(),
     }
 }
 
-pub fn init_trivial() -> Machine<'static, TrivialFractal, Mem> {
+pub fn init_trivial() -> Machine<TrivialFractal, Mem> {
     let conf = init_trivial_static_config();
     let fractal = fractal::init_trivial_fractal();
 
     init(&conf, fractal)
 }
 
-impl<'lt, F, M> Machine<'lt, F, M>
+/**
+ * Zoom logs and skips a failed frame, returns true if the frame didn't fail
+ * The error stops the zoom only after ZOOM_FAILED_FRAMES_MAX failures in a row.
+ */
+fn skip_failed_frame(result: Result<()>, it: u32, failed_frames: &mut u32) -> Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            *failed_frames += 1;
+            error!("frame {} skipped: {}", it, e);
+            if *failed_frames >= ZOOM_FAILED_FRAMES_MAX {
+                return Err(e);
+            }
+            Ok(false)
+        }
+    }
+}
+
+impl<F, M> Machine<F, M>
 where
     F: FractalMath<M>,
     M: MemType<M>,
 {
-    pub fn set_application_ref(&mut self, app_ref: Arc<Mutex<Application<F, M>>>) {
-        self.app_ref = Some(app_ref);
+    pub fn set_canvas(&mut self, canvas: Arc<dyn Canvas>) {
+        self.canvas = Some(canvas);
+    }
+
+    pub fn set_render_sink(&mut self, render_sink: Arc<dyn RenderSink>) {
+        self.render_sink = Some(render_sink);
+    }
+
+    pub fn set_progress_listener(&mut self, listener: Arc<dyn ProgressListener>) {
+        self.progress.set_listener(listener);
     }
 
-    pub fn execute_calculation(&self) {
-        println!("trigger_calculation()");
+    pub fn execute_calculation(&self) -> Result<()> {
+        debug!("trigger_calculation()");
 
         let fractal_calculation = self.fractal_calc_type;
 
         match fractal_calculation {
             StaticImageNebula => {
                 // Fine fractal image
-                self.calculate_nebula_static_image();
+                estimator::estimate(self).print();
+                self.calculate_nebula_static_image()
             }
             DynamicSequenceNebula => {
-                // Fine fractal video
-                self.calculate_nebula_zoom();
+                // Fine fractal video, each frame is estimated
+                self.calculate_nebula_zoom()
             }
             StaticImageMandelbrot => {
                 // Hard fractal image
-                self.calculate_mandelbrot();
+                self.calculate_mandelbrot()
             }
             StaticSequenceMandelbrot => {
                 // Hard fractal video
-                self.calculate_mandelbrot_zoom();
-            }
-            StaticSpectralImageEuler => {
-                todo!()
+                self.calculate_mandelbrot_zoom()
             }
+            StaticSpectralImageEuler => Err(Config(
+                "StaticSpectralImageEuler is not calculated by Machine".to_string(),
+            )),
         }
     }
 
@@ -168,104 +224,197 @@ where
     /**
      * Calculate whole Nebula fractal static image
      */
-    pub fn calculate_nebula_static_image(&self) {
-        println!("calculate_nebula_static_image()");
-
-        let coordinates_xy = shuffled_calculation_coordinates();
+    pub fn calculate_nebula_static_image(&self) -> Result<()> {
+        debug!("calculate_nebula_static_image()");
+        self.stats.clean();
+        self.data_image.clear_diagnostics();
+
+        let area = self.area.copy_data();
+        let coordinates_xy = self.data_image.chunks.shuffled_coordinates();
+        let passes = if self.resolution_multiplier == ResolutionMultiplier::Single {
+            1
+        } else {
+            2
+        };
+        self.eta.start(passes * coordinates_xy.len());
+        let frame = self.stats.frame();
+        let stage = self
+            .progress
+            .stage(frame, Stage::Nebula, coordinates_xy.len());
 
         // calculation for a center of each pixel
-        coordinates_xy.par_iter().for_each(|xy| {
+        coordinates_xy.par_iter().try_for_each(|xy| -> Result<()> {
+            if !self.control.checkpoint() {
+                return Ok(());
+            }
             // calculation
-            self.chunk_calculation(&xy, false);
+            self.chunk_calculation(&area, xy, false)?;
+            self.eta.chunk_done();
+            stage.chunk_done(|| self.stats.points(), || self.eta.remaining());
             // window refresh
             // need to paint full image to show progress from other unfinished chunks
             self.paint_partial_calculation_results_states_maybe();
-        });
+            Ok(())
+        })?;
 
         self.data_image.recalculate_pixels_states();
         self.paint_partial_calculation_results_states_now();
+        self.stats.phase_time(Calculate, stage.finish());
 
         // wrap
         // calculate for many other elements within the pixels
-        if self.resolution_multiplier != ResolutionMultiplier::Single {
-            println!("calculate_nebula_static_image() with wrap");
+        if self.resolution_multiplier != ResolutionMultiplier::Single
+            && !self.control.is_cancelled()
+        {
+            debug!("calculate_nebula_static_image() with wrap");
+            let stage = self
+                .progress
+                .stage(frame, Stage::Wrap, coordinates_xy.len());
             // previous calculation completed, calculate more elements
-            coordinates_xy.par_iter().for_each(|xy| {
+            coordinates_xy.par_iter().try_for_each(|xy| -> Result<()> {
+                if !self.control.checkpoint() {
+                    return Ok(());
+                }
                 // calculation
-                self.chunk_calculation_with_wrap(&xy, true);
+                self.chunk_calculation_with_wrap(&area, xy, true)?;
+                self.eta.chunk_done();
+                stage.chunk_done(|| self.stats.points(), || self.eta.remaining());
                 // window refresh
                 // need to paint full image to show progress from other unfinished chunks
                 self.paint_partial_calculation_results_states_with_paths(); // only every 100+ ms
-            });
+                Ok(())
+            })?;
+            self.stats.phase_time(Wrap, stage.finish());
         }
-        perfectly_colour_nebula_values(&self.data_image, &self.palette);
+        self.stats.frame_done(&self.data_image);
+        self.save_diagnostics(frame);
+
+        let stage = self.progress.stage(frame, Stage::Colour, 0);
+        perfectly_colour_nebula_values(&self.data_image, &self.palette)?;
 
         self.paint_final_calculation_result_colors();
+        self.stats.frame_coloured(frame, stage.finish());
+        Ok(())
     }
 
     /**
      * Calculate a Nebula fractal dynamic data image for infinite zoom
      */
-    pub fn calculate_nebula_dynamic_data(&self) {
-        println!("calculate_nebula_dynamic_data()");
+    pub fn calculate_nebula_dynamic_data(&self) -> Result<()> {
+        self.calculate_nebula_dynamic_values()?;
+        let frame = self.stats.frame_done(&self.data_image);
+        self.save_diagnostics(frame);
+
+        let stage = self.progress.stage(frame, Stage::Colour, 0);
+        perfectly_colour_nebula_values(&self.data_image, &self.palette)?;
+
+        self.paint_final_calculation_result_colors();
+        self.stats.frame_coloured(frame, stage.finish());
+        Ok(())
+    }
+
+    /**
+     * Calculate values of dynamic data image, without colouring
+     */
+    pub fn calculate_nebula_dynamic_values(&self) -> Result<()> {
+        debug!("calculate_nebula_dynamic_values()");
+        self.stats.clean();
+        self.data_image.clear_diagnostics();
 
         self.paint_pixel_states_now();
 
-        let coordinates_xy = shuffled_calculation_coordinates();
+        let area = self.area.copy_data();
+        let coordinates_xy = self.data_image.chunks.shuffled_coordinates();
+        let passes = if self.resolution_multiplier == ResolutionMultiplier::Single {
+            1
+        } else {
+            2
+        };
+        self.eta.start(passes * coordinates_xy.len());
+        let frame = self.stats.frame();
+        let stage = self
+            .progress
+            .stage(frame, Stage::Nebula, coordinates_xy.len());
 
         // calculation for a center of each pixel
-        coordinates_xy.par_iter().for_each(|xy| {
+        coordinates_xy.par_iter().try_for_each(|xy| -> Result<()> {
+            if !self.control.checkpoint() {
+                return Ok(());
+            }
             // calculation
-            self.chunk_calculation(&xy, false);
+            self.chunk_calculation(&area, xy, false)?;
+            self.eta.chunk_done();
+            stage.chunk_done(|| self.stats.points(), || self.eta.remaining());
             // window refresh
             // need to paint full image to show progress from other unfinished chunks
             self.paint_partial_calculation_results_states_maybe();
-        });
+            Ok(())
+        })?;
 
         self.data_image.recalculate_pixels_states();
         self.paint_partial_calculation_results_states_now();
+        self.stats.phase_time(Calculate, stage.finish());
 
         // wrap
         // calculate for many other elements within the pixels
-        if self.resolution_multiplier != ResolutionMultiplier::Single {
-            println!("calculate_nebula_dynamic_data() with wrap");
+        if self.resolution_multiplier != ResolutionMultiplier::Single
+            && !self.control.is_cancelled()
+        {
+            debug!("calculate_nebula_dynamic_values() with wrap");
+            let stage = self
+                .progress
+                .stage(frame, Stage::Wrap, coordinates_xy.len());
             // previous calculation completed, calculate more elements
-            coordinates_xy.par_iter().for_each(|xy| {
+            coordinates_xy.par_iter().try_for_each(|xy| -> Result<()> {
+                if !self.control.checkpoint() {
+                    return Ok(());
+                }
                 // calculation
                 // TODO really true?
-                self.chunk_calculation_with_wrap(&xy, true);
+                self.chunk_calculation_with_wrap(&area, xy, true)?;
+                self.eta.chunk_done();
+                stage.chunk_done(|| self.stats.points(), || self.eta.remaining());
                 // window refresh
                 // need to paint full image to show progress from other unfinished chunks
                 self.paint_partial_calculation_results_states_with_paths();
                 // only every 100+ ms
-            });
+                Ok(())
+            })?;
+            self.stats.phase_time(Wrap, stage.finish());
         }
 
         // reflect dynamic [re, im] paths to screen [x, y] px data
+        let stage = self.progress.stage(frame, Stage::Translate, 0);
         self.clear_all_px_data();
-        self.translate_all_paths_to_point_grid();
-
-        perfectly_colour_nebula_values(&self.data_image, &self.palette);
-
-        self.paint_final_calculation_result_colors();
+        self.translate_all_paths_to_point_grid(&area);
+        self.stats.phase_time(Translate, stage.finish());
+        Ok(())
     }
 
-    // in sequence executes as 20x20 parallel for each image part/chunk
-    fn chunk_calculation(&self, xy: &[u32; 2], save_path: bool) {
+    // in sequence executes in parallel for each image part/chunk
+    fn chunk_calculation(&self, area: &AreaDataCopy, xy: &[u32; 2], save_path: bool) -> Result<()> {
         let (x_from, x_to, y_from, y_to) = self.chunk_boundaries(xy);
         for x in x_from..x_to {
             for y in y_from..y_to {
-                self.calculate_path_xy(x, y, save_path);
+                self.calculate_path_xy(area, x, y, save_path)?;
             }
         }
+        Ok(())
     }
 
-    fn chunk_calculation_with_wrap(&self, xy: &[u32; 2], save_path: bool) {
+    fn chunk_calculation_with_wrap(
+        &self,
+        area: &AreaDataCopy,
+        xy: &[u32; 2],
+        save_path: bool,
+    ) -> Result<()> {
         if self.resolution_multiplier == ResolutionMultiplier::Single {
-            panic!()
+            return Err(Config(
+                "wrap needs resolution multiplier other than Single".to_string(),
+            ));
         }
         let (x_from, x_to, y_from, y_to) = self.chunk_boundaries(xy);
-        let plank = self.area.plank();
+        let plank = area.plank;
         for x in x_from..x_to {
             for y in y_from..y_to {
                 if self.data_image.is_on_mandelbrot_horizon(x, y) {
@@ -278,21 +427,56 @@ where
                     );
                     // within the same pixel
                     for [re, im] in wrap {
-                        self.calculate_path(re, im, save_path);
+                        let (iterator, path_length) =
+                            self.calculate_path(area, re, im, save_path)?;
+                        self.record_diagnostics(x, y, iterator, path_length);
                     }
                 }
             }
         }
+        Ok(())
     }
 
-    fn calculate_path_xy(&self, x: usize, y: usize, save_path: bool) {
+    fn calculate_path_xy(
+        &self,
+        area: &AreaDataCopy,
+        x: usize,
+        y: usize,
+        save_path: bool,
+    ) -> Result<()> {
         let (state, origin_re, origin_im) = self.data_image.state_origin_at(x, y);
         if pixel_states::is_active_new(state) {
             // calculate
-            let (iterator, path_length) = self.calculate_path(origin_re, origin_im, save_path);
+            let (iterator, path_length) =
+                self.calculate_path(area, origin_re, origin_im, save_path)?;
+            self.record_diagnostics(x, y, iterator, path_length);
 
             let state = self.state_from_path_length(iterator, path_length);
             self.data_image.set_pixel_state(x, y, state);
+            self.stats.new_element(state);
+            trace_pixel!(
+                x,
+                y,
+                "{:?}, iterator {}, path length {}",
+                state,
+                iterator,
+                path_length
+            );
+        }
+        Ok(())
+    }
+
+    /**
+     * Good paths are calculated twice, the second time they are recorded
+     */
+    fn record_diagnostics(&self, x: usize, y: usize, iterator: u32, path_length: u32) {
+        if self.data_image.diagnostics.is_some() {
+            if self.path_test(path_length, iterator) {
+                self.data_image
+                    .record_diagnostics(x, y, 2 * iterator, path_length);
+            } else {
+                self.data_image.record_diagnostics(x, y, iterator, 0);
+            }
         }
     }
 
@@ -300,63 +484,63 @@ where
         self.area.move_target(x, y);
     }
 
-    pub fn zoom_in_recalculate_pixel_positions(&self) {
+    pub fn zoom_in_recalculate_pixel_positions(&self) -> Result<()> {
         self.area.zoom_in();
         self.paint_partial_calculation_results_states_now();
 
-        self.recalculate_pixels_positions_for_next_calculation();
+        let result = self.recalculate_pixels_positions_for_next_calculation();
 
         self.paint_partial_calculation_results_states_now();
+        result
     }
 
     pub fn zoom_in(&self) {
-        println!("zoom_in()");
+        debug!("zoom_in()");
         self.area.zoom_in();
     }
 
     // This is called after calculation finished, a zoom-in was called and new area measures recalculated
-    pub fn recalculate_pixels_positions_for_next_calculation(&self) {
-        println!("recalculate_pixels_positions_for_next_calculation()");
+    // All pixels have a position afterwards, even if zoom didn't move or didn't create any.
+    pub fn recalculate_pixels_positions_for_next_calculation(&self) -> Result<()> {
+        debug!("recalculate_pixels_positions_for_next_calculation()");
         // Scan all elements : old positions from previous calculation
         // Some elements will be moved to new positions
         // For all the moved elements, subsequent calculations will be skipped.
-        let area = &self.area;
-        let cre = area.data.lock().unwrap().center_re;
-        let cim = area.data.lock().unwrap().center_im;
+        let area = self.area.copy_data();
 
-        let (cx, cy) = area.point_to_pixel(cre, cim);
+        let (cx, cy) = area.point_to_pixel(area.center_re, area.center_im);
 
-        now("1. move top left to center");
+        debug!("1. move top left to center");
         for y in 0..cy {
             for x in 0..cx {
-                self.data_image.move_to_new_position(x, y, area);
+                self.data_image.move_to_new_position(x, y, &area);
             }
         }
-        now("2. move top right to center");
+        debug!("2. move top right to center");
         for y in 0..cy {
             for x in (cx..self.width_x).rev() {
-                self.data_image.move_to_new_position(x, y, area);
+                self.data_image.move_to_new_position(x, y, &area);
             }
         }
-        now("3. move bottom left to center");
+        debug!("3. move bottom left to center");
         for y in (cy..self.height_y).rev() {
             for x in 0..cx {
-                self.data_image.move_to_new_position(x, y, area);
+                self.data_image.move_to_new_position(x, y, &area);
             }
         }
-        now("4. move bottom right to center");
+        debug!("4. move bottom right to center");
         for y in (cy..self.height_y).rev() {
             for x in (cx..self.width_x).rev() {
-                self.data_image.move_to_new_position(x, y, area);
+                self.data_image.move_to_new_position(x, y, &area);
             }
         }
         // Create new elements on positions where no px moved to
-        now("fill empty places");
+        debug!("fill empty places");
         let mut c_moved = 0;
         let mut c_created = 0;
 
-        let res = area.screen_to_domain_re_copy();
-        let ims = area.screen_to_domain_im_copy();
+        let res = self.area.screen_to_domain_re_copy();
+        let ims = self.area.screen_to_domain_im_copy();
 
         for y in 0..self.height_y {
             for x in 0..self.width_x {
@@ -378,82 +562,84 @@ where
                 }
             }
         }
-        println!("moved:     {}", c_moved);
-        println!("created:   {}", c_created);
-        assert!(c_moved > 0);
-        assert!(c_created > 0);
+        debug!("moved:     {}", c_moved);
+        debug!("created:   {}", c_created);
+        if c_moved == 0 || c_created == 0 {
+            return Err(Zoom {
+                moved: c_moved,
+                created: c_created,
+            });
+        }
+        Ok(())
     }
 
     /**
-     * domain on which the image is calculate is split to 20 x 20 = 400 chunks
-     * this method returns numbers from 0 to 20
+     * domain on which the image is calculate is split to chunks, see ChunkLayout
+     * this method returns pixel boundaries of chunk xy
      */
     pub fn chunk_boundaries(&self, xy: &[u32; 2]) -> (usize, usize, usize, usize) {
-        let chunk_size_x = (self.width_x / 20) as u32;
-        let chunk_size_y = (self.height_y / 20) as u32;
-        (
-            (xy[0] * chunk_size_x) as usize,
-            ((xy[0] + 1) * chunk_size_x) as usize,
-            (xy[1] * chunk_size_y) as usize,
-            ((xy[1] + 1) * chunk_size_y) as usize,
-        )
+        self.data_image.chunks.boundaries(xy)
     }
 
     pub fn path_test(&self, length: u32, iterator: u32) -> bool {
+        let (iteration_min, iteration_max) = (self.iterations.min(), self.iterations.max());
         if self.orbits == OrbitType::Finite {
             // only the edges of mandelbrot set
-            length > self.iteration_min && iterator < self.iteration_max
+            length > iteration_min && iterator < iteration_max
         } else {
             // also contains the inside of mandelbrot set
-            length > self.iteration_min && iterator == self.iteration_max
+            length > iteration_min && iterator == iteration_max
         }
     }
 
-    pub fn calculate_path(
-        &self,
-        origin_re: f64,
-        origin_im: f64,
-        save_show_path: bool,
-    ) -> (u32, u32) {
+    /**
+     * Iterate origin without recording its path
+     * returns the amount of iterations and of path elements within area
+     */
+    pub fn path_length(&self, area: &AreaDataCopy, origin_re: f64, origin_im: f64) -> (u32, u32) {
         let cb = CALCULATION_BOUNDARY as f64;
 
+        let iteration_max = self.iterations.max();
         let mut m = M::new(origin_re, origin_im);
 
         let mut iterator = 0;
         let mut length = 0;
-        while m.quad() < cb && iterator < self.iteration_max {
+        while m.quad() < cb && iterator < iteration_max {
             // Investigate if this is a good calculation path
             // Don't create path data yet. Too many origins don't produce good data
             // Most of the long and expensive calculations end up inside Mandelbrot set, useless
             // It is 1.68x faster to calculate path twice, and to record exclusively the good paths
             self.fractal.math(&mut m, origin_re, origin_im);
-            if self.area.contains(m.re(), m.im()) {
+            if area.contains(m.re(), m.im()) {
                 // this becomes important for zoom, when only a small amount
                 // of calculation path elements is contained withing tiny area
                 length += 1;
             }
             iterator += 1;
         }
+        (iterator, length)
+    }
+
+    pub fn calculate_path(
+        &self,
+        area: &AreaDataCopy,
+        origin_re: f64,
+        origin_im: f64,
+        save_show_path: bool,
+    ) -> Result<(u32, u32)> {
+        let (iterator, length) = self.path_length(area, origin_re, origin_im);
 
         if self.path_test(length, iterator)
         // This origin produced good data
         // Record the calculation path
         {
-            let mut m = M::new(origin_re, origin_im);
-
-            let mut path: Vec<[f64; 2]> = Vec::new();
-            for _ in 0..iterator {
-                self.fractal.math(&mut m, origin_re, origin_im);
-                if self.area.contains(m.re(), m.im()) {
-                    path.push([m.re(), m.im()]);
-                }
-            }
+            let path = self.path(area, origin_re, origin_im, iterator);
 
             // if iteration_max increased, ignore possible extension of previous calculation paths
             // path elements are going to migrate out of the screen very soon
             // removed last_iteration, last_visited_re, last_visited_im
 
-            self.stats.paths_new_points_amount_add(*&path.len());
+            self.stats.path_new(path.len());
 
             if save_show_path {
                 self.data_image.remember_show_path_maybe(&path);
@@ -462,22 +648,44 @@ where
             // save path only for wrap calculation of static image, when data are static, so I can't just get the longest path
             if self.data_image.is_dynamic() {
                 // move path to dynamic data
-                self.data_image.save_path(path);
+                self.data_image.save_path(path)?;
             } else {
                 self.data_image
-                    .translate_one_path_to_point_grid_now(path, &self.area);
+                    .translate_one_path_to_point_grid_now(path, area);
             }
         }
-        (iterator, length)
+        Ok((iterator, length))
+    }
+
+    /**
+     * Path elements within area of the first iterations of origin
+     */
+    pub fn path(
+        &self,
+        area: &AreaDataCopy,
+        origin_re: f64,
+        origin_im: f64,
+        iterations: u32,
+    ) -> Vec<[f64; 2]> {
+        let mut m = M::new(origin_re, origin_im);
+
+        let mut path: Vec<[f64; 2]> = Vec::new();
+        for _ in 0..iterations {
+            self.fractal.math(&mut m, origin_re, origin_im);
+            if area.contains(m.re(), m.im()) {
+                path.push([m.re(), m.im()]);
+            }
+        }
+        path
     }
 
     pub fn state_from_path_length(&self, iterator: u32, path_length: u32) -> DomainElementState {
         // path length considered only within Area
-        if path_length < self.iteration_min {
+        if path_length < self.iterations.min() {
             // 0 to min-1
             return FinishedTooShort;
         }
-        if iterator == self.iteration_max {
+        if iterator == self.iterations.max() {
             // divergent calculation
             // some of the path elements may be outside of Area
             return FinishedTooLong;
@@ -490,41 +698,130 @@ where
      * Methods for infinite zoom video calculations
      * ------------------------------------------ */
 
-    pub fn calculate_nebula_zoom(&self) {
-        println!("calculate_nebula_zoom()");
+    /**
+     * Zoom video is calculated in two stages
+     * Colouring stage colours, paints and exports frame N from snapshot of its values,
+     * while the rayon pool already calculates frame N+1 in the live DataImage.
+     * Failed colouring stops the zoom with its error.
+     */
+    pub fn calculate_nebula_zoom(&self) -> Result<()> {
+        debug!("calculate_nebula_zoom()");
+        // calculation runs at most one frame ahead of colouring
+        let (frames, colouring) = sync_channel::<ValueGrid>(1);
+        thread::scope(|scope| {
+            let colouring = scope.spawn(move || -> Result<()> {
+                for grid in colouring {
+                    self.colour_frame(&grid)?;
+                }
+                Ok(())
+            });
+            let calculated = self.calculate_nebula_zoom_frames(frames);
+            // colouring stage finishes frames already sent
+            let coloured = colouring
+                .join()
+                .unwrap_or_else(|panic| Err(Panicked(render::panic_message(panic))));
+            coloured.and(calculated)
+        })
+    }
+
+    // frames are sent to colouring stage until it stops receiving them
+    fn calculate_nebula_zoom_frames(&self, frames: SyncSender<ValueGrid>) -> Result<()> {
+        let mut failed_frames = 0;
+        // estimate of the last calculated frame
+        let mut previous: Option<Estimate> = None;
         for it in 1.. {
-            println!("{}:", it);
-            self.calculate_nebula_dynamic_data();
+            info!("{}:", it);
+            let estimate = estimator::estimate(self);
+            match (&previous, self.stats.last_record()) {
+                (Some(previous), Some(record)) => info!(
+                    "Estimate of next frame: {}",
+                    format_duration(estimate.frame_duration(previous, &record))
+                ),
+                _ => estimate.print(),
+            }
+            let calculated = self.calculate_nebula_dynamic_values();
+            previous = None;
+            if skip_failed_frame(calculated, it, &mut failed_frames)? {
+                failed_frames = 0;
+                previous = Some(estimate);
+                let frame = self.stats.frame_done(&self.data_image);
+                self.save_diagnostics(frame);
+
+                let grid = value_grid::snapshot(&self.data_image, frame);
+                if frames.send(grid).is_err() {
+                    info!("calculate_nebula_zoom() colouring stopped");
+                    break;
+                }
+            }
+
+            // wait here if paused, stop zoom with partial result if cancelled
+            if !self.control.checkpoint() {
+                info!("calculate_nebula_zoom() cancelled");
+                break;
+            }
 
             // prepare next frame
             self.zoom_in();
 
-            self.recalculate_pixels_positions_for_next_calculation();
+            let recalculated = self.recalculate_pixels_positions_for_next_calculation();
+            skip_failed_frame(recalculated, it, &mut failed_frames)?;
 
             // remove dynamic data which zoomed out of displayed Area
-            self.remove_elements_outside();
+            let removed = self.remove_elements_outside();
+            skip_failed_frame(removed, it, &mut failed_frames)?;
 
             self.paint_partial_calculation_results_states_now();
 
             self.stats.update(&self.data_image, it);
+            self.iterations.adjust(&self.stats.feedback());
         }
+        Ok(())
     }
 
-    pub fn calculate_mandelbrot_zoom(&self) {
-        println!("calculate_mandelbrot_zoom()");
+    /**
+     * Colouring stage of zoom video
+     */
+    fn colour_frame(&self, grid: &ValueGrid) -> Result<()> {
+        let stage = self.progress.stage(grid.frame, Stage::Colour, 0);
+        let image = perfectly_colour_nebula_grid(grid, &self.palette)?;
+        if let Some(canvas) = &self.canvas {
+            canvas.paint_frame(&image);
+        }
+        self.stats.frame_coloured(grid.frame, stage.finish());
+        if let Some(render_sink) = &self.render_sink {
+            render_sink.frame(&image, grid.frame);
+        }
+        Ok(())
+    }
+
+    pub fn calculate_mandelbrot_zoom(&self) -> Result<()> {
+        info!("calculate_mandelbrot_zoom()");
+        let mut failed_frames = 0;
         for it in 1.. {
-            println!("{}:", it);
-            self.calculate_mandelbrot();
+            info!("{}:", it);
+            let calculated = self.calculate_mandelbrot();
+            if skip_failed_frame(calculated, it, &mut failed_frames)? {
+                failed_frames = 0;
+            }
+
+            // wait here if paused, stop zoom with partial result if cancelled
+            if !self.control.checkpoint() {
+                info!("calculate_mandelbrot_zoom() cancelled");
+                break;
+            }
 
             // prepare next frame
             self.zoom_in();
 
-            self.recalculate_pixels_positions_for_next_calculation();
+            let recalculated = self.recalculate_pixels_positions_for_next_calculation();
+            skip_failed_frame(recalculated, it, &mut failed_frames)?;
 
             self.paint_partial_calculation_results_states_now();
 
             self.stats.update(&self.data_image, it);
+            self.iterations.adjust(&self.stats.feedback());
         }
+        Ok(())
     }
 
     /* ------------------------------------------
@@ -534,64 +831,257 @@ where
     /**
      * Whole Mandelbrot calculation
      */
-    pub fn calculate_mandelbrot(&self) {
-        println!("calculate_mandelbrot()");
-        let coordinates_xy: Vec<[u32; 2]> = shuffled_calculation_coordinates();
+    pub fn calculate_mandelbrot(&self) -> Result<()> {
+        debug!("calculate_mandelbrot()");
+        self.stats.clean();
+        self.data_image.clear_diagnostics();
+        if self.mandelbrot_rendering == Progressive {
+            return self.calculate_mandelbrot_progressive();
+        }
+        let coordinates_xy: Vec<[u32; 2]> = self.data_image.chunks.shuffled_coordinates();
+        self.eta.start(coordinates_xy.len());
+        let frame = self.stats.frame();
+        let stage = self
+            .progress
+            .stage(frame, Stage::Mandelbrot, coordinates_xy.len());
+        let verify = self.mandelbrot_rendering == MarianiSilverVerified;
+        let subdivide = self.mandelbrot_rendering == MarianiSilver || verify;
+        let filled = AtomicU64::new(0);
+        let mismatched = AtomicU64::new(0);
 
         coordinates_xy.par_iter().for_each(|xy| {
+            if !self.control.checkpoint() {
+                return;
+            }
             // calculation
-            self.chunk_calculation_mandelbrot(xy);
+            if subdivide {
+                let (f, m) = self.chunk_calculation_mariani_silver(xy, verify);
+                filled.fetch_add(f, Ordering::Relaxed);
+                mismatched.fetch_add(m, Ordering::Relaxed);
+            } else {
+                self.chunk_calculation_mandelbrot(xy, 1);
+            }
+            self.eta.chunk_done();
+            stage.chunk_done(|| 0, || self.eta.remaining());
             // window refresh
             self.paint_partial_calculation_results_states_maybe();
         });
+        if subdivide {
+            debug!("mariani_silver filled:     {}", filled.into_inner());
+        }
+        if verify {
+            debug!("mariani_silver mismatched: {}", mismatched.into_inner());
+        }
         self.data_image.recalculate_pixels_states();
-        perfectly_colour_mandelbrot_values(&self.data_image, &self.palette, &self.palette_zero);
+        self.stats.phase_time(Calculate, stage.finish());
+        self.stats.frame_done(&self.data_image);
+        self.save_diagnostics(frame);
+
+        let stage = self.progress.stage(frame, Stage::Colour, 0);
+        perfectly_colour_mandelbrot_values(&self.data_image, &self.palette, &self.palette_zero)?;
         self.paint_final_calculation_result_colors();
+        self.stats.frame_coloured(frame, stage.finish());
+        Ok(())
     }
 
-    fn chunk_calculation_mandelbrot(&self, xy: &[u32; 2]) {
+    /**
+     * Progressive Mandelbrot calculation
+     * First calculate one pixel of each 16 x 16 square, then halve the step until all pixels are calculated.
+     * Pixels calculated by coarser levels are not calculated again.
+     */
+    pub fn calculate_mandelbrot_progressive(&self) -> Result<()> {
+        debug!("calculate_mandelbrot_progressive()");
+        let coordinates_xy: Vec<[u32; 2]> = self.data_image.chunks.shuffled_coordinates();
+        let frame = self.stats.frame();
+
+        let mut step = PROGRESSIVE_STEP_MAX;
+        let mut colour = Duration::ZERO;
+        loop {
+            debug!("refinement level {}", step);
+            // each refinement level is reported as a Mandelbrot stage
+            let stage = self
+                .progress
+                .stage(frame, Stage::Mandelbrot, coordinates_xy.len());
+            coordinates_xy.par_iter().for_each(|xy| {
+                if !self.control.checkpoint() {
+                    return;
+                }
+                self.chunk_calculation_mandelbrot(xy, step);
+                stage.chunk_done(|| 0, || None);
+            });
+
+            // cancelled calculation ends with the last, partially calculated level
+            let last = step == 1 || self.control.is_cancelled();
+            if last {
+                self.data_image.recalculate_pixels_states();
+            }
+            self.stats.phase_time(Calculate, stage.finish());
+
+            let stage = self.progress.stage(frame, Stage::Colour, 0);
+            perfectly_colour_mandelbrot_values(
+                &self.data_image,
+                &self.palette,
+                &self.palette_zero,
+            )?;
+            self.refinement_level_done(step);
+            colour += stage.finish();
+            if last {
+                break;
+            }
+            step /= 2;
+        }
+        self.stats.frame_done(&self.data_image);
+        self.save_diagnostics(frame);
+        self.stats.frame_coloured(frame, colour);
+        Ok(())
+    }
+
+    // calculates only pixels [x, y] with x and y divisible by step
+    fn chunk_calculation_mandelbrot(&self, xy: &[u32; 2], step: usize) {
         let (x_from, x_to, y_from, y_to) = self.chunk_boundaries(xy);
-        for x in x_from..x_to {
-            for y in y_from..y_to {
-                // state before calculation
-                let (state, origin_re, origin_im) = self.data_image.state_origin_at(x, y);
-
-                if pixel_states::is_active_new(state) {
-                    /*
-                     * calculation
-                     */
-                    let (iterator, quad) = self.calculate_mandelbrot_path(origin_re, origin_im);
-                    // result
-                    let state = self.state_from_path_length(iterator, iterator);
-                    self.data_image.set_pixel_mandelbrot(
-                        x,
-                        y,
-                        iterator,
-                        quad,
-                        state,
-                        self.iteration_max,
-                    );
+        for x in (x_from..x_to).filter(|x| x % step == 0) {
+            for y in (y_from..y_to).filter(|y| y % step == 0) {
+                self.calculate_mandelbrot_xy(x, y);
+            }
+        }
+    }
+
+    /**
+     * Calculate pixel if it wasn't calculated yet
+     * returns pixel value and state
+     */
+    fn calculate_mandelbrot_xy(&self, x: usize, y: usize) -> (u32, DomainElementState) {
+        // state before calculation
+        let (state, origin_re, origin_im) = self.data_image.state_origin_at(x, y);
+
+        if pixel_states::is_active_new(state) {
+            /*
+             * calculation
+             */
+            let (iterator, quad) = self.calculate_mandelbrot_path(origin_re, origin_im);
+            // result
+            let state = self.state_from_path_length(iterator, iterator);
+            self.data_image.set_pixel_mandelbrot(
+                x,
+                y,
+                iterator,
+                quad,
+                state,
+                self.iterations.max(),
+            );
+            self.stats.new_element(state);
+            self.data_image.record_diagnostics(x, y, iterator, 0);
+            trace_pixel!(x, y, "{:?}, iterator {}, quad {}", state, iterator, quad);
+        }
+        self.data_image.value_state_at(x, y)
+    }
+
+    /**
+     * Mariani–Silver subdivision of a chunk
+     * returns amount of pixels filled without calculation and, if verified, how many of them were wrong
+     */
+    fn chunk_calculation_mariani_silver(&self, xy: &[u32; 2], verify: bool) -> (u64, u64) {
+        let (x_from, x_to, y_from, y_to) = self.chunk_boundaries(xy);
+        self.mariani_silver_rectangle(x_from, x_to - 1, y_from, y_to - 1, verify)
+    }
+
+    /**
+     * Calculate border of rectangle, boundaries are inclusive.
+     * If all border pixels have the same value and state, fill the inside with them.
+     * Otherwise split the rectangle in two halves, which share the middle line.
+     */
+    fn mariani_silver_rectangle(
+        &self,
+        x_min: usize,
+        x_max: usize,
+        y_min: usize,
+        y_max: usize,
+        verify: bool,
+    ) -> (u64, u64) {
+        let first = self.calculate_mandelbrot_xy(x_min, y_min);
+        let mut uniform = true;
+        for x in x_min..=x_max {
+            uniform &= self.calculate_mandelbrot_xy(x, y_min) == first;
+            uniform &= self.calculate_mandelbrot_xy(x, y_max) == first;
+        }
+        for y in y_min..=y_max {
+            uniform &= self.calculate_mandelbrot_xy(x_min, y) == first;
+            uniform &= self.calculate_mandelbrot_xy(x_max, y) == first;
+        }
+
+        // no inside
+        if x_max - x_min < 2 || y_max - y_min < 2 {
+            return (0, 0);
+        }
+
+        if uniform {
+            let (value, state) = first;
+            let (_, _, quad, _, _) = self.data_image.values5_at(x_min, y_min);
+            let mut filled = 0;
+            let mut mismatched = 0;
+            for x in x_min + 1..x_max {
+                for y in y_min + 1..y_max {
+                    if !pixel_states::is_active_new(self.data_image.state_at(x, y)) {
+                        // calculated before zoom
+                        continue;
+                    }
+                    filled += 1;
+                    if verify {
+                        // keep the correct result
+                        if self.calculate_mandelbrot_xy(x, y) != first {
+                            mismatched += 1;
+                        }
+                    } else {
+                        self.data_image
+                            .fill_pixel_mandelbrot(x, y, value, quad, state);
+                    }
                 }
             }
+            return (filled, mismatched);
         }
+
+        if x_max - x_min <= MARIANI_SILVER_MIN_SIZE && y_max - y_min <= MARIANI_SILVER_MIN_SIZE {
+            for x in x_min + 1..x_max {
+                for y in y_min + 1..y_max {
+                    self.calculate_mandelbrot_xy(x, y);
+                }
+            }
+            return (0, 0);
+        }
+
+        // split the longer side
+        let (a, b) = if x_max - x_min >= y_max - y_min {
+            let x_mid = (x_min + x_max) / 2;
+            (
+                self.mariani_silver_rectangle(x_min, x_mid, y_min, y_max, verify),
+                self.mariani_silver_rectangle(x_mid, x_max, y_min, y_max, verify),
+            )
+        } else {
+            let y_mid = (y_min + y_max) / 2;
+            (
+                self.mariani_silver_rectangle(x_min, x_max, y_min, y_mid, verify),
+                self.mariani_silver_rectangle(x_min, x_max, y_mid, y_max, verify),
+            )
+        };
+        (a.0 + b.0, a.1 + b.1)
     }
 
     pub fn calculate_mandelbrot_path(&self, origin_re: f64, origin_im: f64) -> (u32, f64) {
         let cb = CALCULATION_BOUNDARY as f64;
+        let iteration_max = self.iterations.max();
 
         let mut m = M::new(origin_re, origin_im);
 
         let mut iterator = 0;
-        while m.quad() < cb && iterator < self.iteration_max {
+        while m.quad() < cb && iterator < iteration_max {
             self.fractal.math(&mut m, origin_re, origin_im);
             iterator += 1;
         }
         (iterator, m.quad())
     }
 
-    fn translate_all_paths_to_point_grid(&self) {
-        self.data_image
-            .translate_all_paths_to_point_grid(&self.area);
+    fn translate_all_paths_to_point_grid(&self, area: &AreaDataCopy) {
+        self.data_image.translate_all_paths_to_point_grid(area);
     }
 
     fn clear_all_px_data(&self) {
@@ -606,19 +1096,41 @@ where
      * This method will paint only final image colors, no pixel states
      */
     pub fn paint_final_calculation_result_colors(&self) {
-        let app = self
-            .app_ref
-            .as_ref()
-            .unwrap()
-            .lock()
-            .expect("Failed to lock application reference");
+        if let Some(canvas) = &self.canvas {
+            canvas.paint_final_calculation_result_colors(&self.data_image);
+        }
+    }
 
-        app.paint_final_calculation_result_colors(&self.data_image);
+    /**
+     * Paint and send coloured refinement level to render sink
+     */
+    fn refinement_level_done(&self, step: usize) {
+        if step == 1 {
+            self.paint_final_calculation_result_colors();
+        } else if let Some(canvas) = &self.canvas {
+            canvas.paint_refinement_level(&self.data_image, step);
+        }
+        if let Some(render_sink) = &self.render_sink {
+            render_sink.refinement_level(&self.data_image, step);
+        }
     }
 
-    fn remove_elements_outside(&self) {
-        println!("remove_elements_outside()");
-        self.data_image.remove_elements_outside(&self.area);
+    /**
+     * Diagnostics which couldn't be saved are reported, the frame itself is fine
+     */
+    fn save_diagnostics(&self, frame: u32) {
+        if let Err(e) = diagnostics::save(&self.data_image, &self.name, frame) {
+            error!("frame {} diagnostics not saved: {}", frame, e);
+        }
+    }
+
+    fn remove_elements_outside(&self) -> Result<()> {
+        debug!("remove_elements_outside()");
+        let removed = self
+            .data_image
+            .remove_elements_outside(&self.area.copy_data())?;
+        self.stats.paths_removed(removed);
+        Ok(())
     }
 
     pub fn paint_partial_calculation_results_states_with_paths(&self) {
@@ -660,13 +1172,11 @@ where
         });
 
         if called_in_past_enough || paint_now {
-            println!("paint_partial_calculation_results_states() condition");
-            let app = self
-                .app_ref
-                .as_ref()
-                .unwrap()
-                .lock()
-                .expect("Failed to lock application reference");
+            debug!("paint_partial_calculation_results_states() condition");
+            let Some(canvas) = &self.canvas else {
+                // nothing to paint without window
+                return;
+            };
 
             let path: Option<Vec<[f64; 2]>>;
 
@@ -681,50 +1191,138 @@ where
                 path = None;
             }
 
-            app.paint_partial_calculation_result_states(&self.data_image, &self.area, path);
+            canvas.paint_partial_calculation_result_states(&self.data_image, &self.area, path);
         }
 
         *last_called = Some(now);
     }
 
     pub fn paint_pixel_states_now(&self) {
-        let app = self
-            .app_ref
-            .as_ref()
-            .unwrap()
-            .lock()
-            .expect("Failed to lock application reference");
-
-        app.paint_pixel_states(&self.data_image);
-    }
-}
-
-/* --------------
- * Static methods
- * ----------- */
-
-/**
- * Creates x,y pairs for calculation.
- * Then shuffles them, it looks better when rendering
- */
-pub fn shuffled_calculation_coordinates() -> Vec<[u32; 2]> {
-    let mut coordinates_xy: Vec<[u32; 2]> = Vec::new();
-
-    // 400 little subdivisions of the screen
-    for x in 0..20 {
-        for y in 0..20 {
-            coordinates_xy.push([x, y]);
+        if let Some(canvas) = &self.canvas {
+            canvas.paint_pixel_states(&self.data_image);
         }
     }
-    coordinates_xy.shuffle(&mut thread_rng());
-    coordinates_xy
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::area::Area;
+    use crate::canvas::Canvas;
+    use crate::chunks::ChunkLayout::Count;
+    use crate::control::Control;
+    use crate::data_image::DataImage;
+    use crate::error::FractalError;
+    use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticImageNebula};
+    use crate::fractal::MandelbrotRendering::{MarianiSilver, Progressive};
+    use crate::fractal::{
+        init_trivial_fractal, init_trivial_static_config, FractalConfig, FractalMath,
+    };
+    use crate::mem::Mem;
+    use crate::palettes::PaletteName::{BlueToWhiteCircleUp, GrayToBlackCircleDown};
     use crate::pixel_states::DomainElementState::{FinishedSuccess, FinishedTooLong};
-    use crate::{machine, pixel_states};
+    use crate::progress::{channel_listener, ProgressEvent, Stage};
+    use crate::render_sink::RenderSink;
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use crate::{machine, pixel_states, value_grid};
+    use image::RgbImage;
     use pixel_states::DomainElementState::FinishedTooShort;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::channel;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    struct LevelsSink {
+        levels: Mutex<Vec<usize>>,
+    }
+
+    impl RenderSink for LevelsSink {
+        fn refinement_level(&self, _: &DataImage, step: usize) {
+            self.levels.lock().unwrap().push(step);
+        }
+    }
+
+    // records zoom frames, cancels zoom after the last one
+    struct FramesSink {
+        control: Arc<Control>,
+        last: u32,
+        frames: Mutex<Vec<(u32, u32, u32)>>,
+    }
+
+    impl RenderSink for FramesSink {
+        fn refinement_level(&self, _: &DataImage, _: usize) {}
+
+        fn frame(&self, image: &RgbImage, frame: u32) {
+            self.frames
+                .lock()
+                .unwrap()
+                .push((frame, image.width(), image.height()));
+            if frame == self.last {
+                self.control.cancel();
+            }
+        }
+    }
+
+    // counts final paintings
+    struct CountingCanvas {
+        paints: AtomicUsize,
+    }
+
+    impl Canvas for CountingCanvas {
+        fn paint_final_calculation_result_colors(&self, _: &DataImage) {
+            self.paints.fetch_add(1, Ordering::Relaxed);
+        }
+        fn paint_frame(&self, _: &RgbImage) {}
+        fn paint_refinement_level(&self, _: &DataImage, _: usize) {}
+        fn paint_partial_calculation_result_states(
+            &self,
+            _: &DataImage,
+            _: &Area,
+            _: Option<Vec<[f64; 2]>>,
+        ) {
+        }
+        fn paint_pixel_states(&self, _: &DataImage) {}
+    }
+
+    // fractal borrowing its parameter, not 'static
+    struct ShiftedFractal<'a> {
+        shift: &'a f64,
+    }
+
+    impl FractalMath<Mem> for ShiftedFractal<'_> {
+        fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+            m.square();
+            m.plus(origin_re + self.shift, origin_im);
+        }
+    }
+
+    #[test]
+    fn test_machines_calculate_at_once() {
+        let canvas = Arc::new(CountingCanvas {
+            paints: AtomicUsize::new(0),
+        });
+        let shift = 0.25;
+        let conf = mandelbrot_config(30, 20);
+
+        let mut trivial = machine::init(&conf, init_trivial_fractal());
+        let mut shifted = machine::init(&conf, ShiftedFractal { shift: &shift });
+        trivial.set_canvas(canvas.clone());
+        shifted.set_canvas(canvas.clone());
+        thread::scope(|scope| {
+            scope.spawn(|| trivial.calculate_mandelbrot().unwrap());
+            scope.spawn(|| shifted.calculate_mandelbrot().unwrap());
+        });
+
+        assert_eq!(canvas.paints.load(Ordering::Relaxed), 2);
+        assert_ne!(
+            value_grid::snapshot(&trivial.data_image, 1).values,
+            value_grid::snapshot(&shifted.data_image, 1).values
+        );
+
+        // no reference cycle, dropped machines release the canvas
+        drop(trivial);
+        drop(shifted);
+        assert_eq!(Arc::strong_count(&canvas), 1);
+    }
 
     #[test]
     fn test_state_from_path_length() {
@@ -777,20 +1375,78 @@ mod tests {
         assert_eq!(pixel_states::is_active_new(s), true);
 
         // test result
-        machine.calculate_path_xy(0, 0, false);
+        machine
+            .calculate_path_xy(&machine.area.copy_data(), 0, 0, false)
+            .unwrap();
         let (s, _, _) = machine.data_image.state_origin_at(0, 0);
 
         assert_eq!(pixel_states::is_active_new(s), false);
         assert_eq!(pixel_states::is_finished_any(s), true);
     }
 
+    #[test]
+    fn test_recoverable_errors() {
+        let machine = machine::init_trivial();
+        let area = machine.area.copy_data();
+
+        // trivial config has ResolutionMultiplier Single
+        let wrap = machine.chunk_calculation_with_wrap(&area, &[0, 0], false);
+        assert!(matches!(wrap, Err(FractalError::Config(_))));
+
+        // without zoom in, all pixels stay in place
+        let pixels = machine.width_x * machine.height_y;
+        let zoom = machine.recalculate_pixels_positions_for_next_calculation();
+        assert!(matches!(
+            zoom,
+            Err(FractalError::Zoom { moved, created: 0 }) if moved == pixels
+        ));
+
+        machine.zoom_in();
+        machine
+            .recalculate_pixels_positions_for_next_calculation()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_record_diagnostics() {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = StaticImageNebula;
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_min = 20;
+        conf.iteration_max = 500;
+        conf.diagnostics = true;
+        let machine = machine::init(&conf, init_trivial_fractal());
+        let area = machine.area.copy_data();
+
+        for xy in machine.data_image.chunks.coordinates() {
+            machine.chunk_calculation(&area, &xy, false).unwrap();
+        }
+
+        let diagnostics = machine.data_image.diagnostics.as_ref().unwrap();
+        let (mut yields, mut cost) = (0, 0);
+        for y in 0..conf.height_y {
+            for x in 0..conf.width_x {
+                cost += diagnostics.cost_at(x, y);
+                if machine.data_image.state_at(x, y) != FinishedSuccess {
+                    assert_eq!(diagnostics.yield_at(x, y), 0);
+                }
+                yields += diagnostics.yield_at(x, y);
+            }
+        }
+        // good paths are calculated twice
+        assert!(cost >= 2 * yields);
+        assert!(yields > 0);
+        assert_eq!(yields, machine.stats.points());
+    }
+
     #[test]
     fn test_chunk_calculation_mandelbrot<'lt>() {
         let machine = machine::init_trivial();
 
         let xy = [0, 0];
 
-        machine.chunk_calculation_mandelbrot(&xy);
+        machine.chunk_calculation_mandelbrot(&xy, 1);
         let (s, _, _) = machine.data_image.state_origin_at(0, 0);
 
         println!("state: {:?}", s);
@@ -805,9 +1461,181 @@ mod tests {
         let machine = machine::init_trivial();
 
         // execute test
-        let (iterator, length) = machine.calculate_path(0.7, 0.7, false);
+        let area = machine.area.copy_data();
+        let (iterator, length) = machine.calculate_path(&area, 0.7, 0.7, false).unwrap();
 
         assert_eq!(iterator, 2); // trivial iteration_max = 3
         assert_eq!(length, 0);
     }
+
+    fn mandelbrot_config(width_x: usize, height_y: usize) -> FractalConfig {
+        let mut conf = init_trivial_static_config();
+        conf.width_x = width_x;
+        conf.height_y = height_y;
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_max = 100;
+        conf.palette = BlueToWhiteCircleUp;
+        conf.palette_zero = GrayToBlackCircleDown;
+        conf
+    }
+
+    #[test]
+    fn test_calculate_mandelbrot_progressive() {
+        let mut conf = mandelbrot_config(37, 23);
+        let shuffled = machine::init(&conf, init_trivial_fractal());
+        conf.mandelbrot_rendering = Progressive;
+        let mut progressive = machine::init(&conf, init_trivial_fractal());
+        let sink = Arc::new(LevelsSink {
+            levels: Mutex::new(Vec::new()),
+        });
+        progressive.set_render_sink(sink.clone());
+
+        shuffled.calculate_mandelbrot().unwrap();
+        progressive.calculate_mandelbrot().unwrap();
+
+        assert_eq!(*sink.levels.lock().unwrap(), vec![16, 8, 4, 2, 1]);
+        for y in 0..conf.height_y {
+            for x in 0..conf.width_x {
+                assert_eq!(
+                    progressive.data_image.value_state_at(x, y),
+                    shuffled.data_image.value_state_at(x, y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunk_calculation_mariani_silver() {
+        let mut conf = mandelbrot_config(120, 80);
+        conf.chunk_layout = Count(2);
+        let brute_force = machine::init(&conf, init_trivial_fractal());
+        let subdivided = machine::init(&conf, init_trivial_fractal());
+        let verified = machine::init(&conf, init_trivial_fractal());
+
+        let mut filled = 0;
+        let mut mismatched = 0;
+        for xy in brute_force.data_image.chunks.coordinates() {
+            brute_force.chunk_calculation_mandelbrot(&xy, 1);
+            subdivided.chunk_calculation_mariani_silver(&xy, false);
+            let (f, m) = verified.chunk_calculation_mariani_silver(&xy, true);
+            filled += f;
+            mismatched += m;
+        }
+
+        // Mandelbrot set inside is filled
+        assert!(filled > 0);
+        let mut different = 0;
+        for y in 0..conf.height_y {
+            for x in 0..conf.width_x {
+                let expected = brute_force.data_image.value_state_at(x, y);
+                assert_eq!(verified.data_image.value_state_at(x, y), expected);
+                if subdivided.data_image.value_state_at(x, y) != expected {
+                    different += 1;
+                }
+            }
+        }
+        assert_eq!(different, mismatched);
+    }
+
+    #[test]
+    fn test_calculate_mandelbrot_mariani_silver() {
+        let mut conf = mandelbrot_config(37, 23);
+        conf.chunk_layout = Count(2);
+        conf.mandelbrot_rendering = MarianiSilver;
+        let machine = machine::init(&conf, init_trivial_fractal());
+
+        machine.calculate_mandelbrot().unwrap();
+
+        for y in 0..conf.height_y {
+            for x in 0..conf.width_x {
+                assert!(machine.data_image.colour_at(x, y).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_calculate_nebula_zoom() {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = DynamicSequenceNebula;
+        conf.palette = BlueToWhiteCircleUp;
+        let mut machine = machine::init(&conf, init_trivial_fractal());
+        let sink = Arc::new(FramesSink {
+            control: machine.control.clone(),
+            last: 3,
+            frames: Mutex::new(Vec::new()),
+        });
+        machine.set_render_sink(sink.clone());
+
+        machine.calculate_nebula_zoom().unwrap();
+
+        // frames calculated ahead of the cancel are coloured too, all in order
+        let frames = sink.frames.lock().unwrap();
+        assert!(frames.len() >= 3);
+        for (i, (frame, width, height)) in frames.iter().enumerate() {
+            assert_eq!(*frame, i as u32 + 1);
+            assert_eq!(
+                (*width, *height),
+                (conf.width_x as u32, conf.height_y as u32)
+            );
+        }
+        let records = machine.stats.records();
+        assert_eq!(records.len(), frames.len());
+        let first = &records[0];
+        assert_eq!(
+            first.new_elements_long + first.new_elements_too_short + first.new_elements_too_long,
+            (conf.width_x * conf.height_y) as u32
+        );
+        assert!(records
+            .iter()
+            .zip(frames.iter())
+            .all(|(r, f)| r.frame == f.0));
+    }
+
+    #[test]
+    fn test_calculate_nebula_zoom_colouring_failed() {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = DynamicSequenceNebula;
+        let machine = machine::init(&conf, init_trivial_fractal());
+
+        // palette Nothing has no colours, colouring of the first frame fails
+        let result = machine.calculate_nebula_zoom();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "config: palette has no colours"
+        );
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let mut conf = init_trivial_static_config();
+        conf.fractal_calc_type = StaticImageNebula;
+        conf.resolution_multiplier = Square3;
+        conf.palette = BlueToWhiteCircleUp;
+        let mut machine = machine::init(&conf, init_trivial_fractal());
+        let (sender, receiver) = channel();
+        machine.set_progress_listener(channel_listener(sender));
+
+        machine.calculate_nebula_static_image().unwrap();
+        let machine_chunks = machine.data_image.chunks.amount();
+        drop(machine);
+
+        let events: Vec<ProgressEvent> = receiver.iter().collect();
+        let stages: Vec<Stage> = events
+            .iter()
+            .filter_map(|e| match e {
+                ProgressEvent::StageFinished {
+                    frame: 1, stage, ..
+                } => Some(*stage),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stages, vec![Stage::Nebula, Stage::Wrap, Stage::Colour]);
+
+        let chunks = events
+            .iter()
+            .filter(|e| matches!(e, ProgressEvent::ChunkDone { .. }))
+            .count();
+        assert_eq!(chunks, 2 * machine_chunks);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::debug;
 use crate::fractal::MemType;
 use crate::mem::Mem;
 use std::collections::HashSet;
@@ -127,7 +128,7 @@ impl Mathematician {
  */
 
 fn init_fibonacci(max: u32) {
-    println!("init_fibonacci()");
+    debug!("init_fibonacci()");
     let mut fibonacci: HashSet<u32> = HashSet::new();
     let mut a = 0;
     let mut b = 1;
@@ -145,7 +146,7 @@ fn init_fibonacci(max: u32) {
  */
 
 pub fn init_perfect_numbers(max: u32) {
-    println!("init_perfect_numbers()");
+    debug!("init_perfect_numbers()");
     let mut perfect: HashSet<u32> = HashSet::new();
     for i in 0..max {
         if is_perfect_init(i) {
@@ -170,7 +171,7 @@ fn is_perfect_init(num: u32) -> bool {
  */
 
 pub fn init_primes(max: u32) -> HashSet<u32> {
-    println!("init_primes()");
+    debug!("init_primes()");
     let mut primes: HashSet<u32> = HashSet::new();
     for i in 0..max {
         if is_prime_init(i) {
@@ -198,7 +199,7 @@ fn is_prime_init(n: u32) -> bool {
  */
 
 pub fn init_squares(max: u32) {
-    println!("init_squares()");
+    debug!("init_squares()");
     let mut square: HashSet<u32> = HashSet::new();
     let mut sq;
     let investigate_to = f64::sqrt(max as f64) as u32 + 1;
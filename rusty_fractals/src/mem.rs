@@ -1,6 +1,8 @@
 use crate::fractal::MemType;
 
 // Memory object to carry calculation results
+// C layout, plugins calculate with it, see plugin
+#[repr(C)]
 pub struct Mem {
     pub re: f64,
     pub im: f64,
@@ -1,6 +1,7 @@
 use crate::fractal::MemType;
 use crate::mem::Mem;
 
+#[repr(C)]
 pub struct MemCollatz {
     pub m: Mem,
     pub num: i128,
@@ -5,6 +5,7 @@ use crate::mem::Mem;
 /**
  * Memory object for Phoenix fractal
  */
+#[repr(C)]
 pub struct MemPhoenix {
     pub m: Mem,
     // Values of previous calculation results
@@ -2,6 +2,7 @@ use image::{Pixel, Rgb};
 use palettes::Function;
 
 use crate::palettes;
+use crate::{debug, trace};
 
 fn max(r: i32, g: i32, b: i32) -> i32 {
     if a(r) >= a(g) && a(r) >= a(b) {
@@ -36,20 +37,20 @@ pub fn make_spectrum(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Vec<Rgb<
     let g_dif = g_to as i32 - g_from as i32;
     let b_dif = b_to as i32 - b_from as i32;
 
-    println!("rgb from {} {} {} ", r_from, g_from, b_from);
-    println!("rgb to   {} {} {} ", r_to, g_to, b_to);
-    println!("rgb dif  {} {} {} ", r_dif, g_dif, b_dif);
+    trace!("rgb from {} {} {} ", r_from, g_from, b_from);
+    trace!("rgb to   {} {} {} ", r_to, g_to, b_to);
+    trace!("rgb dif  {} {} {} ", r_dif, g_dif, b_dif);
 
     let max_dif = max(r_dif, g_dif, b_dif) as f64;
     let max_dif_abs = a(max_dif as i32) as u32;
-    println!("max dif: {}", max_dif);
+    trace!("max dif: {}", max_dif);
 
     let r_step: f64 = (r_dif as f64 / max_dif_abs as f64) as f64;
     let g_step: f64 = (g_dif as f64 / max_dif_abs as f64) as f64;
     let b_step: f64 = (b_dif as f64 / max_dif_abs as f64) as f64;
-    println!("step r: {}", r_step);
-    println!("step g: {}", g_step);
-    println!("step b: {}", b_step);
+    trace!("step r: {}", r_step);
+    trace!("step g: {}", g_step);
+    trace!("step b: {}", b_step);
 
     let mut stop = false;
 
@@ -57,7 +58,7 @@ pub fn make_spectrum(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Vec<Rgb<
 
     let mut spectrum: Vec<Rgb<u8>> = Vec::new();
     let forward = function_result(0.0, &function) != 1.0;
-    println!("spectrum forward: {}", forward);
+    trace!("spectrum forward: {}", forward);
 
     for i in 0..max_dif_abs + 1 {
         let d: f64 = (i as f64 / max_dif) as f64;
@@ -71,8 +72,8 @@ pub fn make_spectrum(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Vec<Rgb<
         let mut g_new = g_from as f64 + (value * g_step);
         let mut b_new = b_from as f64 + (value * b_step);
         if i == 0 {
-            println!("v: {}", v);
-            println!("r_new={} = {} + ({} * {})", r_new, r_from, value, r_step);
+            trace!("v: {}", v);
+            trace!("r_new={} = {} + ({} * {})", r_new, r_from, value, r_step);
         }
 
         if r_new > rgb255 {
@@ -162,7 +163,7 @@ pub fn make_spectrum(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Vec<Rgb<
     assert_eq!(r_to, spec_r_to);
     assert_eq!(g_to, spec_g_to);
     assert_eq!(b_to, spec_b_to);
-    println!("color spectrum certified");
+    debug!("color spectrum certified");
     spectrum
 }
 
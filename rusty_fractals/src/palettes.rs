@@ -1,3 +1,4 @@
+use crate::debug;
 use crate::palette::Palette;
 use crate::palette::Palette3;
 use crate::palette_utils::make_spectrum;
@@ -21,6 +22,8 @@ pub enum Function {
     CircleUp,
 }
 
+#[repr(C)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum PaletteName {
     Nothing,
     BlackToWhiteExp2,
@@ -39,7 +42,7 @@ pub fn new<'lt>(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Palette {
 }
 
 pub fn new_palette_by_name<'lt>(palette_name: &PaletteName) -> Palette {
-    println!("new_palette_by_name()");
+    debug!("new_palette_by_name()");
     match palette_name {
         PaletteName::BlackToWhiteExp2 => palette_black_to_white_exp2(),
         PaletteName::BlackToWhiteCircleUp => palette_black_to_white_circle_up(),
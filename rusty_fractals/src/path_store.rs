@@ -0,0 +1,704 @@
+use crate::area::AreaDataCopy;
+use crate::constants::PATHS_SEGMENT_POINTS;
+use crate::path_store_disk;
+use crate::path_store_disk::DiskSegment;
+use crate::{info, warn};
+use rayon::prelude::*;
+use std::io;
+use std::mem::size_of;
+use std::path::PathBuf;
+
+/**
+ * How are path elements [re, im] stored
+ */
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PathEncoding {
+    // Exact f64 values, 16 bytes per path element
+    AbsoluteF64,
+    // f32 offsets from area center, 8 bytes per path element
+    // Offsets are re-based to the new area center at each compaction, so they keep their precision during zoom
+    CenterOffsetF32,
+}
+
+/**
+ * Where are the paths stored
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum PathStorage {
+    // All paths in RAM
+    Memory,
+    // Full segments are written to memory-mapped files in this work directory
+    // For OrbitType::Infinite zooms, path data don't fit in RAM
+    Disk(String),
+}
+
+/**
+ * Read access to one segment of path elements.
+ * Segment is either the in-memory Arena or a memory-mapped DiskSegment.
+ */
+pub trait Segment: Sync {
+    fn encoding(&self) -> PathEncoding;
+    fn center(&self) -> (f64, f64);
+    fn starts(&self) -> &[usize];
+    fn lengths(&self) -> &[u32];
+    fn points_amount(&self) -> usize;
+    // raw values of one segment element, offsets are not decoded yet
+    fn raw(&self, arena_index: usize) -> [f64; 2];
+
+    fn paths_amount(&self) -> usize {
+        self.starts().len()
+    }
+
+    fn point(&self, arena_index: usize) -> [f64; 2] {
+        let [re, im] = self.raw(arena_index);
+        match self.encoding() {
+            PathEncoding::AbsoluteF64 => [re, im],
+            PathEncoding::CenterOffsetF32 => {
+                let (center_re, center_im) = self.center();
+                [center_re + re, center_im + im]
+            }
+        }
+    }
+
+    fn path_copy(&self, index: usize) -> Vec<[f64; 2]> {
+        let start = self.starts()[index];
+        (start..start + self.lengths()[index] as usize)
+            .map(|i| self.point(i))
+            .collect()
+    }
+}
+
+/**
+ * In-memory structure-of-arrays arena, paths are only ranges of it.
+ */
+pub struct Arena {
+    encoding: PathEncoding,
+    // reference point for CenterOffsetF32 encoding
+    center_re: f64,
+    center_im: f64,
+    // arena for AbsoluteF64
+    pub re: Vec<f64>,
+    pub im: Vec<f64>,
+    // arena for CenterOffsetF32
+    pub re_offset: Vec<f32>,
+    pub im_offset: Vec<f32>,
+    // path i is arena[starts[i]..starts[i] + lengths[i]]
+    starts: Vec<usize>,
+    lengths: Vec<u32>,
+}
+
+impl Segment for Arena {
+    fn encoding(&self) -> PathEncoding {
+        self.encoding
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (self.center_re, self.center_im)
+    }
+
+    fn starts(&self) -> &[usize] {
+        &self.starts
+    }
+
+    fn lengths(&self) -> &[u32] {
+        &self.lengths
+    }
+
+    fn points_amount(&self) -> usize {
+        match self.encoding {
+            PathEncoding::AbsoluteF64 => self.re.len(),
+            PathEncoding::CenterOffsetF32 => self.re_offset.len(),
+        }
+    }
+
+    fn raw(&self, arena_index: usize) -> [f64; 2] {
+        match self.encoding {
+            PathEncoding::AbsoluteF64 => [self.re[arena_index], self.im[arena_index]],
+            PathEncoding::CenterOffsetF32 => [
+                self.re_offset[arena_index] as f64,
+                self.im_offset[arena_index] as f64,
+            ],
+        }
+    }
+}
+
+impl Arena {
+    pub fn append_path(&mut self, path: &[[f64; 2]]) {
+        self.starts.push(self.points_amount());
+        self.lengths.push(path.len() as u32);
+        match self.encoding {
+            PathEncoding::AbsoluteF64 => {
+                for [re, im] in path {
+                    self.re.push(*re);
+                    self.im.push(*im);
+                }
+            }
+            PathEncoding::CenterOffsetF32 => {
+                for [re, im] in path {
+                    self.re_offset.push((re - self.center_re) as f32);
+                    self.im_offset.push((im - self.center_im) as f32);
+                }
+            }
+        }
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.points_amount() * point_bytes(self.encoding) + index_bytes(self.paths_amount())
+    }
+
+    /**
+     * Paths of the other arena follow the paths of this one
+     */
+    pub fn append_arena(&mut self, other: Arena) {
+        if other.center() != self.center() {
+            for i in 0..other.paths_amount() {
+                self.append_path(&other.path_copy(i));
+            }
+            return;
+        }
+        let offset = self.points_amount();
+        self.starts
+            .extend(other.starts.iter().map(|start| start + offset));
+        self.lengths.extend(other.lengths);
+        self.re.extend(other.re);
+        self.im.extend(other.im);
+        self.re_offset.extend(other.re_offset);
+        self.im_offset.extend(other.im_offset);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.re.shrink_to_fit();
+        self.im.shrink_to_fit();
+        self.re_offset.shrink_to_fit();
+        self.im_offset.shrink_to_fit();
+        self.starts.shrink_to_fit();
+        self.lengths.shrink_to_fit();
+    }
+}
+
+/**
+ * Compact storage of calculation paths for dynamic zoom.
+ * New paths go to the in-memory arena.
+ * With PathStorage::Disk, full arena is sealed to a memory-mapped segment file.
+ */
+pub struct PathStore {
+    pub encoding: PathEncoding,
+    // memory cap in bytes, 0 means no cap
+    pub memory_max: usize,
+    work_dir: Option<PathBuf>,
+    // arena is sealed to a segment file when it has this many elements
+    pub segment_points: usize,
+    arena: Arena,
+    segments: Vec<DiskSegment>,
+    // full arena is being sealed or evicted
+    arena_out: bool,
+    // eviction statistics
+    pub evicted_paths: u64,
+    pub evicted_points: u64,
+}
+
+impl PathStore {
+    /**
+     * Append path elements to the arena, the full arena is sealed or evicted right away
+     * Store shared by calculation threads uses append() and attach(), see FullArena.
+     */
+    pub fn push(&mut self, path: &[[f64; 2]]) -> io::Result<()> {
+        match self.append(path) {
+            Some(full) => self.attach(full.finish()),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * Append path elements to the arena.
+     * If the memory cap is reached, the least-contributing paths are evicted.
+     * With disk storage, full arena is sealed to a segment file instead.
+     * The full arena is taken out of the store and returned, so that it is sealed or evicted
+     * without the lock of the store. Until it is attached back, new paths go to a new arena
+     * and no other arena is taken out.
+     */
+    pub fn append(&mut self, path: &[[f64; 2]]) -> Option<FullArena> {
+        self.arena.append_path(path);
+        if self.arena_out {
+            return None;
+        }
+        let work = if let Some(work_dir) = &self.work_dir {
+            if self.arena.points_amount() < self.segment_points {
+                return None;
+            }
+            Work::Seal(work_dir.clone())
+        } else if self.memory_max > 0 && self.memory_bytes() > self.memory_max {
+            // frees memory down to 90% of the cap, so that eviction doesn't repeat on each push
+            let target = self.memory_max / 10 * 9;
+            Work::Evict {
+                bytes_per_point: self.memory_bytes() as f64 / self.points_amount().max(1) as f64,
+                to_free: (self.memory_bytes() - target.min(self.memory_bytes())) as f64,
+            }
+        } else {
+            return None;
+        };
+        let (center_re, center_im) = self.arena.center();
+        let arena = std::mem::replace(
+            &mut self.arena,
+            init_arena(self.encoding, center_re, center_im),
+        );
+        self.arena_out = true;
+        Some(FullArena { arena, work })
+    }
+
+    /**
+     * Puts back the full arena taken out by append(), as a segment or without the evicted paths
+     * If sealing failed, the paths are back in the arena and sealing is tried again on the next push.
+     */
+    pub fn attach(&mut self, finished: FinishedArena) -> io::Result<()> {
+        self.arena_out = false;
+        match finished {
+            FinishedArena::Sealed(segment) => {
+                self.segments.push(segment);
+                Ok(())
+            }
+            FinishedArena::NotSealed(arena, e) => {
+                let newer = std::mem::replace(&mut self.arena, arena);
+                self.arena.append_arena(newer);
+                Err(e)
+            }
+            FinishedArena::Evicted {
+                arena,
+                paths,
+                points,
+            } => {
+                self.evicted_paths += paths;
+                self.evicted_points += points;
+                let newer = std::mem::replace(&mut self.arena, arena);
+                self.arena.append_arena(newer);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn paths_amount(&self) -> usize {
+        self.all_segments().iter().map(|s| s.paths_amount()).sum()
+    }
+
+    pub fn points_amount(&self) -> usize {
+        self.all_segments().iter().map(|s| s.points_amount()).sum()
+    }
+
+    /**
+     * RAM used by path data, segment files are not included
+     */
+    pub fn memory_bytes(&self) -> usize {
+        let segments_index: usize = self
+            .segments
+            .iter()
+            .map(|s| index_bytes(s.paths_amount()))
+            .sum();
+        self.arena.memory_bytes() + segments_index
+    }
+
+    pub fn disk_bytes(&self) -> usize {
+        self.segments.iter().map(|s| s.file_bytes()).sum()
+    }
+
+    pub fn segments_amount(&self) -> usize {
+        self.segments.len()
+    }
+
+    /**
+     * Length of path at index, in-memory arena paths go first
+     */
+    pub fn path_length(&self, index: usize) -> usize {
+        let (segment, i) = self.locate(index);
+        segment.lengths()[i] as usize
+    }
+
+    pub fn path_copy(&self, index: usize) -> Vec<[f64; 2]> {
+        let (segment, i) = self.locate(index);
+        segment.path_copy(i)
+    }
+
+    pub fn the_longest_path_copy(&self) -> Option<Vec<[f64; 2]>> {
+        let mut longest: Option<(&dyn Segment, usize, u32)> = None;
+        for segment in self.all_segments() {
+            for (i, length) in segment.lengths().iter().enumerate() {
+                if longest.is_none_or(|(_, _, l)| *length > l) {
+                    longest = Some((segment, i, *length));
+                }
+            }
+        }
+        longest.map(|(segment, i, _)| segment.path_copy(i))
+    }
+
+    /**
+     * Visit all path elements of all paths
+     */
+    pub fn for_each_point<P>(&self, mut visit: P)
+    where
+        P: FnMut(f64, f64),
+    {
+        for segment in self.all_segments() {
+            for i in 0..segment.points_amount() {
+                let [re, im] = segment.point(i);
+                visit(re, im);
+            }
+        }
+    }
+
+    /**
+     * Visit all path elements of all paths, streams over segments in parallel
+     */
+    pub fn par_for_each_point<P>(&self, visit: P)
+    where
+        P: Fn(f64, f64) + Sync,
+    {
+        self.all_segments().par_iter().for_each(|segment| {
+            (0..segment.points_amount()).into_par_iter().for_each(|i| {
+                let [re, im] = segment.point(i);
+                visit(re, im);
+            });
+        });
+    }
+
+    /**
+     * Remove elements outside area and paths which got too short.
+     * Compacts the arena and all segments in parallel and re-bases offsets to the new area center.
+     * Compaction of a segment rewrites its file, which reclaims disk space of removed elements.
+     * Segment which couldn't be rewritten is kept uncompacted, the first error is returned.
+     */
+    pub fn retain_inside(
+        &mut self,
+        area: &AreaDataCopy,
+        minimum_path_length: u32,
+    ) -> io::Result<()> {
+        let (center_re, center_im) = (area.center_re, area.center_im);
+        let keep_point = |re, im| area.contains(re, im);
+
+        let keep_path = vec![true; self.arena.paths_amount()];
+        self.arena = compact(
+            &self.arena,
+            &keep_path,
+            center_re,
+            center_im,
+            keep_point,
+            minimum_path_length,
+        );
+
+        let mut result = Ok(());
+        if let Some(work_dir) = &self.work_dir {
+            let segments = std::mem::take(&mut self.segments);
+            let compacted: Vec<_> = segments
+                .into_par_iter()
+                .filter_map(|segment| {
+                    let keep_path = vec![true; segment.paths_amount()];
+                    let arena = compact(
+                        &segment,
+                        &keep_path,
+                        center_re,
+                        center_im,
+                        keep_point,
+                        minimum_path_length,
+                    );
+                    // the old segment file is deleted when segment drops
+                    if arena.paths_amount() == 0 {
+                        None
+                    } else {
+                        Some(path_store_disk::write(&arena, work_dir).map_err(|e| (segment, e)))
+                    }
+                })
+                .collect();
+            for written in compacted {
+                match written {
+                    Ok(segment) => self.segments.push(segment),
+                    Err((segment, e)) => {
+                        warn!("retain_inside() segment not compacted: {}", e);
+                        self.segments.push(segment);
+                        if result.is_ok() {
+                            result = Err(e);
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn all_segments(&self) -> Vec<&dyn Segment> {
+        let mut all: Vec<&dyn Segment> = vec![&self.arena];
+        for segment in &self.segments {
+            all.push(segment);
+        }
+        all
+    }
+
+    fn locate(&self, index: usize) -> (&dyn Segment, usize) {
+        let mut i = index;
+        for segment in self.all_segments() {
+            if i < segment.paths_amount() {
+                return (segment, i);
+            }
+            i -= segment.paths_amount();
+        }
+        panic!("path index {} out of bounds", index);
+    }
+}
+
+/**
+ * Arena taken out of PathStore by append(), to be sealed or evicted without the lock of the store
+ */
+pub struct FullArena {
+    arena: Arena,
+    work: Work,
+}
+
+enum Work {
+    Seal(PathBuf),
+    Evict { bytes_per_point: f64, to_free: f64 },
+}
+
+/**
+ * Result of FullArena::finish(), for PathStore::attach()
+ */
+pub enum FinishedArena {
+    Sealed(DiskSegment),
+    NotSealed(Arena, io::Error),
+    Evicted {
+        arena: Arena,
+        paths: u64,
+        points: u64,
+    },
+}
+
+impl FullArena {
+    pub fn finish(self) -> FinishedArena {
+        match self.work {
+            Work::Seal(work_dir) => match path_store_disk::write(&self.arena, &work_dir) {
+                Ok(segment) => FinishedArena::Sealed(segment),
+                Err(e) => FinishedArena::NotSealed(self.arena, e),
+            },
+            Work::Evict {
+                bytes_per_point,
+                to_free,
+            } => evict_least_contributing(self.arena, bytes_per_point, to_free),
+        }
+    }
+}
+
+/**
+ * Drop the shortest paths, these contribute the least to the image.
+ */
+fn evict_least_contributing(arena: Arena, bytes_per_point: f64, mut to_free: f64) -> FinishedArena {
+    let mut order: Vec<usize> = (0..arena.paths_amount()).collect();
+    order.sort_by_key(|i| arena.lengths[*i]);
+
+    let mut keep_path = vec![true; arena.paths_amount()];
+    let mut paths = 0;
+    let mut points = 0;
+    for i in order {
+        if to_free <= 0.0 {
+            break;
+        }
+        keep_path[i] = false;
+        paths += 1;
+        points += arena.lengths[i] as u64;
+        to_free -= arena.lengths[i] as f64 * bytes_per_point;
+    }
+    info!("evicted paths:  {} ({} points)", paths, points);
+
+    let (center_re, center_im) = arena.center();
+    let arena = compact(&arena, &keep_path, center_re, center_im, |_, _| true, 0);
+    FinishedArena::Evicted {
+        arena,
+        paths,
+        points,
+    }
+}
+
+/**
+ * Copy kept paths of a segment to a new Arena, remove elements by keep_point.
+ * Paths not longer than minimum_path_length are removed.
+ */
+pub fn compact<S, K>(
+    segment: &S,
+    keep_path: &[bool],
+    center_re: f64,
+    center_im: f64,
+    keep_point: K,
+    minimum_path_length: u32,
+) -> Arena
+where
+    S: Segment + ?Sized,
+    K: Fn(f64, f64) -> bool,
+{
+    let mut arena = init_arena(segment.encoding(), center_re, center_im);
+    let mut path: Vec<[f64; 2]> = Vec::new();
+    for (p, keep) in keep_path.iter().enumerate() {
+        if !keep {
+            continue;
+        }
+        path.clear();
+        let start = segment.starts()[p];
+        for i in start..start + segment.lengths()[p] as usize {
+            let [re, im] = segment.point(i);
+            if keep_point(re, im) {
+                path.push([re, im]);
+            }
+        }
+        if path.len() as u32 > minimum_path_length {
+            arena.append_path(&path);
+        }
+    }
+    arena.shrink_to_fit();
+    arena
+}
+
+pub fn point_bytes(encoding: PathEncoding) -> usize {
+    match encoding {
+        PathEncoding::AbsoluteF64 => 2 * size_of::<f64>(),
+        PathEncoding::CenterOffsetF32 => 2 * size_of::<f32>(),
+    }
+}
+
+pub fn index_bytes(paths_amount: usize) -> usize {
+    paths_amount * (size_of::<usize>() + size_of::<u32>())
+}
+
+pub fn init_arena(encoding: PathEncoding, center_re: f64, center_im: f64) -> Arena {
+    Arena {
+        encoding,
+        center_re,
+        center_im,
+        re: Vec::new(),
+        im: Vec::new(),
+        re_offset: Vec::new(),
+        im_offset: Vec::new(),
+        starts: Vec::new(),
+        lengths: Vec::new(),
+    }
+}
+
+/**
+ * memory_max_mb 0 means no memory cap
+ * With PathStorage::Disk, RAM is limited by segment size and the cap isn't used
+ * Work directory is created with the first segment.
+ */
+pub fn init(
+    encoding: PathEncoding,
+    storage: &PathStorage,
+    memory_max_mb: usize,
+    center_re: f64,
+    center_im: f64,
+) -> PathStore {
+    let work_dir = match storage {
+        PathStorage::Memory => None,
+        PathStorage::Disk(dir) => Some(PathBuf::from(dir)),
+    };
+    PathStore {
+        encoding,
+        memory_max: memory_max_mb * 1024 * 1024,
+        work_dir,
+        segment_points: PATHS_SEGMENT_POINTS,
+        arena: init_arena(encoding, center_re, center_im),
+        segments: Vec::new(),
+        arena_out: false,
+        evicted_paths: 0,
+        evicted_points: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::path_store::init;
+    use crate::path_store::PathEncoding::{AbsoluteF64, CenterOffsetF32};
+    use crate::path_store::PathStorage::Memory;
+
+    #[test]
+    fn test_push_path_copy() {
+        let mut store = init(AbsoluteF64, &Memory, 0, 0.0, 0.0);
+
+        store.push(&[[0.1, 0.2], [0.3, 0.4]]).unwrap();
+        store.push(&[[0.5, 0.6]]).unwrap();
+
+        assert_eq!(store.paths_amount(), 2);
+        assert_eq!(store.points_amount(), 3);
+        assert_eq!(store.path_copy(0), vec![[0.1, 0.2], [0.3, 0.4]]);
+        assert_eq!(store.path_copy(1), vec![[0.5, 0.6]]);
+        assert_eq!(store.memory_bytes(), 3 * 16 + 2 * 12);
+    }
+
+    #[test]
+    fn test_center_offset_f32() {
+        let mut store = init(CenterOffsetF32, &Memory, 0, -0.5, 0.1);
+
+        store.push(&[[-0.5000001, 0.1000002]]).unwrap();
+        let [re, im] = store.path_copy(0)[0];
+
+        assert!((re - -0.5000001).abs() < 1e-12);
+        assert!((im - 0.1000002).abs() < 1e-12);
+        assert_eq!(store.memory_bytes(), 8 + 12);
+    }
+
+    #[test]
+    fn test_retain_inside() {
+        let conf = init_trivial_dynamic_config();
+        let area = area::init(&conf).copy_data();
+        let mut store = init(CenterOffsetF32, &Memory, 0, 0.0, 0.0);
+
+        store
+            .push(&[[0.0, 0.0], [0.1, 0.1], [0.2, 0.2], [10.0, 0.0], [0.3, 0.3]])
+            .unwrap();
+        store.push(&[[0.0, 0.0], [10.0, 10.0]]).unwrap();
+
+        store.retain_inside(&area, 1).unwrap();
+
+        assert_eq!(store.paths_amount(), 1);
+        assert_eq!(store.points_amount(), 4);
+    }
+
+    #[test]
+    fn test_evict_least_contributing() {
+        // 1 MB cap
+        let mut store = init(AbsoluteF64, &Memory, 1, 0.0, 0.0);
+        let long = vec![[0.0, 0.0]; 50_000];
+        let short = vec![[0.0, 0.0]; 10_000];
+
+        store.push(&long).unwrap();
+        store.push(&short).unwrap();
+        assert_eq!(store.evicted_paths, 0);
+
+        // exceeds 1 MB, the short paths go first
+        store.push(&short).unwrap();
+
+        assert_eq!(store.evicted_paths, 2);
+        assert_eq!(store.evicted_points, 20_000);
+        assert_eq!(store.paths_amount(), 1);
+        assert_eq!(store.path_length(0), 50_000);
+        assert!(store.memory_bytes() <= store.memory_max);
+    }
+
+    #[test]
+    fn test_append_while_arena_out() {
+        let mut store = init(AbsoluteF64, &Memory, 1, 0.0, 0.0);
+        let long = vec![[0.0, 0.0]; 50_000];
+        let short = vec![[0.0, 0.0]; 10_000];
+        store.push(&long).unwrap();
+        store.push(&short).unwrap();
+
+        let full = store.append(&short).unwrap();
+        assert_eq!(store.paths_amount(), 0);
+        // other threads keep appending, without taking out another arena
+        assert!(store.append(&short).is_none());
+        assert!(store.append(&short).is_none());
+        assert_eq!(store.paths_amount(), 2);
+
+        store.attach(full.finish()).unwrap();
+
+        assert_eq!(store.evicted_paths, 2);
+        assert_eq!(store.paths_amount(), 3);
+        assert_eq!(store.path_length(0), 50_000);
+        assert_eq!(store.points_amount(), 70_000);
+        // over the cap, the next append takes the arena out again
+        assert!(store.append(&short).is_some());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::path_store::{Arena, PathEncoding, Segment};
+use crate::warn;
+use memmap2::Mmap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// unique segment file names within process
+static SEGMENT_ID: AtomicU64 = AtomicU64::new(0);
+
+/**
+ * Sealed segment of path elements, memory-mapped from a file in work directory.
+ * File layout: all re values, then all im values, little endian.
+ * Values are f64 or f32 offsets from center, by encoding.
+ * Path index (starts, lengths) stays in RAM.
+ */
+pub struct DiskSegment {
+    file: PathBuf,
+    mmap: Mmap,
+    encoding: PathEncoding,
+    center_re: f64,
+    center_im: f64,
+    starts: Vec<usize>,
+    lengths: Vec<u32>,
+    points: usize,
+}
+
+impl Segment for DiskSegment {
+    fn encoding(&self) -> PathEncoding {
+        self.encoding
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (self.center_re, self.center_im)
+    }
+
+    fn starts(&self) -> &[usize] {
+        &self.starts
+    }
+
+    fn lengths(&self) -> &[u32] {
+        &self.lengths
+    }
+
+    fn points_amount(&self) -> usize {
+        self.points
+    }
+
+    fn raw(&self, arena_index: usize) -> [f64; 2] {
+        match self.encoding {
+            PathEncoding::AbsoluteF64 => [
+                self.read_f64(arena_index),
+                self.read_f64(self.points + arena_index),
+            ],
+            PathEncoding::CenterOffsetF32 => [
+                self.read_f32(arena_index) as f64,
+                self.read_f32(self.points + arena_index) as f64,
+            ],
+        }
+    }
+}
+
+impl DiskSegment {
+    pub fn file_bytes(&self) -> usize {
+        self.mmap.len()
+    }
+
+    fn read_f64(&self, i: usize) -> f64 {
+        let bytes: [u8; 8] = self.mmap[i * 8..i * 8 + 8].try_into().unwrap();
+        f64::from_le_bytes(bytes)
+    }
+
+    fn read_f32(&self, i: usize) -> f32 {
+        let bytes: [u8; 4] = self.mmap[i * 4..i * 4 + 4].try_into().unwrap();
+        f32::from_le_bytes(bytes)
+    }
+}
+
+impl Drop for DiskSegment {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.file) {
+            warn!("DiskSegment.drop(): {}: {}", self.file.display(), e);
+        }
+    }
+}
+
+/**
+ * Write arena to a new segment file and memory-map it
+ * Work directory is created if missing. Segment file of a failed write is removed.
+ */
+pub fn write(arena: &Arena, work_dir: &Path) -> io::Result<DiskSegment> {
+    fs::create_dir_all(work_dir)?;
+    let id = SEGMENT_ID.fetch_add(1, Ordering::Relaxed);
+    let file = work_dir.join(format!("paths_{}_{}.seg", std::process::id(), id));
+
+    let mmap = match write_mapped(arena, &file) {
+        Ok(mmap) => mmap,
+        Err(e) => {
+            warn!("write(): {}: {}", file.display(), e);
+            let _ = fs::remove_file(&file);
+            return Err(e);
+        }
+    };
+    let (center_re, center_im) = arena.center();
+    Ok(DiskSegment {
+        file,
+        mmap,
+        encoding: arena.encoding(),
+        center_re,
+        center_im,
+        starts: arena.starts().to_vec(),
+        lengths: arena.lengths().to_vec(),
+        points: arena.points_amount(),
+    })
+}
+
+fn write_mapped(arena: &Arena, file: &Path) -> io::Result<Mmap> {
+    let mut writer = BufWriter::new(File::create(file)?);
+    match arena.encoding() {
+        PathEncoding::AbsoluteF64 => {
+            for v in arena.re.iter().chain(arena.im.iter()) {
+                writer.write_all(&v.to_le_bytes())?;
+            }
+        }
+        PathEncoding::CenterOffsetF32 => {
+            for v in arena.re_offset.iter().chain(arena.im_offset.iter()) {
+                writer.write_all(&v.to_le_bytes())?;
+            }
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+
+    let read = File::open(file)?;
+    // Safety: segment files are private to this process, nobody modifies them after they were written
+    unsafe { Mmap::map(&read) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::path_store::init;
+    use crate::path_store::PathEncoding::{AbsoluteF64, CenterOffsetF32};
+    use crate::path_store::PathStorage::Disk;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn work_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(name);
+        dir.to_str().unwrap().to_string()
+    }
+
+    fn files_in(dir: &str) -> usize {
+        fs::read_dir(dir).unwrap().count()
+    }
+
+    #[test]
+    fn test_segments() {
+        let dir = work_dir("rusty_fractals_test_segments");
+        let mut store = init(AbsoluteF64, &Disk(dir.clone()), 0, 0.0, 0.0);
+        store.segment_points = 4;
+
+        store.push(&[[0.1, 0.1], [0.2, 0.2], [0.3, 0.3]]).unwrap();
+        assert_eq!(store.segments_amount(), 0);
+        store.push(&[[0.4, 0.4], [0.5, 0.5]]).unwrap();
+        assert_eq!(store.segments_amount(), 1);
+        store.push(&[[0.6, 0.6]]).unwrap();
+
+        assert_eq!(files_in(&dir), 1);
+        assert_eq!(store.disk_bytes(), 5 * 16);
+        assert_eq!(store.paths_amount(), 3);
+        assert_eq!(store.points_amount(), 6);
+        assert_eq!(store.path_copy(0), vec![[0.6, 0.6]]);
+        assert_eq!(store.path_copy(2), vec![[0.4, 0.4], [0.5, 0.5]]);
+
+        let mut sum = 0.0;
+        store.for_each_point(|re, _| sum += re);
+        assert!((sum - 2.1).abs() < 1e-12);
+
+        let count = AtomicUsize::new(0);
+        store.par_for_each_point(|_, _| {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(count.load(Ordering::Relaxed), 6);
+
+        drop(store);
+        assert_eq!(files_in(&dir), 0);
+    }
+
+    #[test]
+    fn test_compaction() {
+        let dir = work_dir("rusty_fractals_test_compaction");
+        let conf = init_trivial_dynamic_config();
+        let area = area::init(&conf).copy_data();
+        let mut store = init(CenterOffsetF32, &Disk(dir.clone()), 0, 0.0, 0.0);
+        store.segment_points = 2;
+
+        // sealed, stays after compaction
+        store
+            .push(&[[0.1, 0.1], [10.0, 0.0], [0.2, 0.2], [0.3, 0.3]])
+            .unwrap();
+        // sealed, removed by compaction
+        store.push(&[[10.0, 10.0], [20.0, 20.0]]).unwrap();
+        assert_eq!(store.segments_amount(), 2);
+        let before = store.disk_bytes();
+
+        store.retain_inside(&area, 1).unwrap();
+
+        assert_eq!(store.segments_amount(), 1);
+        assert_eq!(files_in(&dir), 1);
+        assert_eq!(store.points_amount(), 3);
+        assert!(store.disk_bytes() < before);
+        let [re, im] = store.path_copy(0)[2];
+        assert!((re - 0.3).abs() < 1e-7);
+        assert!((im - 0.3).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_write_error() {
+        // work directory can't be created under a file
+        let file = std::env::temp_dir().join("rusty_fractals_test_write_error");
+        fs::write(&file, "").unwrap();
+        let dir = file.join("paths").to_str().unwrap().to_string();
+        let mut store = init(AbsoluteF64, &Disk(dir), 0, 0.0, 0.0);
+        store.segment_points = 2;
+
+        assert!(store.push(&[[0.1, 0.1], [0.2, 0.2]]).is_err());
+        // path stays in memory
+        assert_eq!(store.segments_amount(), 0);
+        assert_eq!(store.paths_amount(), 1);
+        fs::remove_file(&file).unwrap();
+    }
+}
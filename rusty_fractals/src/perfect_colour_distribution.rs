@@ -6,7 +6,12 @@
 
 use crate::constants::COLOURING_THRESHOLD;
 use crate::data_image::DataImage;
+use crate::debug;
+use crate::error::FractalError::{Colouring, Config};
+use crate::error::Result;
 use crate::palette::Palette;
+use crate::value_grid::ValueGrid;
+use image::{Rgb, RgbImage};
 use std::cmp::Ordering::Equal;
 
 // for Nebula like fractals
@@ -26,11 +31,43 @@ struct Mix {
     quid: f64,
 }
 
-pub fn perfectly_colour_nebula_values(data: &DataImage, palette: &Palette) {
-    println!("perfectly_colour_nebula_values()");
-    let width = data.width_x;
-    let height = data.height_y;
+pub fn perfectly_colour_nebula_values(data: &DataImage, palette: &Palette) -> Result<()> {
+    debug!("perfectly_colour_nebula_values()");
+    perfectly_colour_nebula(
+        data.width_x,
+        data.height_y,
+        |x, y| data.value_at(x, y),
+        |x, y, colour| data.colour(x, y, colour),
+        palette,
+    )
+}
+
+/**
+ * Colour snapshot of frame values, the live DataImage isn't touched
+ */
+pub fn perfectly_colour_nebula_grid(grid: &ValueGrid, palette: &Palette) -> Result<RgbImage> {
+    debug!("perfectly_colour_nebula_grid() {}", grid.frame);
+    let mut image = RgbImage::new(grid.width_x as u32, grid.height_y as u32);
+    perfectly_colour_nebula(
+        grid.width_x,
+        grid.height_y,
+        |x, y| grid.value_at(x, y),
+        |x, y, colour| image.put_pixel(x as u32, y as u32, colour),
+        palette,
+    )?;
+    Ok(image)
+}
 
+fn perfectly_colour_nebula(
+    width: usize,
+    height: usize,
+    value_at: impl Fn(usize, usize) -> u32,
+    mut colour: impl FnMut(usize, usize, Rgb<u8>),
+    palette: &Palette,
+) -> Result<()> {
+    if palette.spectrum.is_empty() {
+        return Err(Config("palette has no colours".to_string()));
+    }
     // Result pixels, order by value
     let mut pixels: Vec<Pix> = Vec::new();
     let mut zero_value_elements = 0;
@@ -38,7 +75,7 @@ pub fn perfectly_colour_nebula_values(data: &DataImage, palette: &Palette) {
     // read screen values
     for y in 0..height {
         for x in 0..width {
-            let v = data.value_at(x, y);
+            let v = value_at(x, y);
             if v <= COLOURING_THRESHOLD {
                 zero_value_elements += 1;
             }
@@ -55,39 +92,45 @@ pub fn perfectly_colour_nebula_values(data: &DataImage, palette: &Palette) {
     let single_colour_use: u32 = (all_pixels_non_zero as f64 / palette_colour_count as f64) as u32;
     let left: u32 = all_pixels_non_zero - (palette_colour_count * single_colour_use);
 
-    println!("------------------------------------");
-    println!("All pixels to paint:         {}", all_pixels_total);
-    println!(
+    debug!("------------------------------------");
+    debug!("All pixels to paint:         {}", all_pixels_total);
+    debug!(
         "---------------------------> {}",
         zero_value_elements + left + (single_colour_use * palette_colour_count)
     );
-    println!("Zero value pixels to paint:  {}", zero_value_elements);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero);
-    println!("Spectrum, available colours: {}", palette_colour_count);
-    println!("Pixels per each colour:      {}", single_colour_use);
-    println!("left:                        {}", left);
-    println!("------------------------------------");
+    debug!("Zero value pixels to paint:  {}", zero_value_elements);
+    debug!("Non zero pixels to paint:    {}", all_pixels_non_zero);
+    debug!("Spectrum, available colours: {}", palette_colour_count);
+    debug!("Pixels per each colour:      {}", single_colour_use);
+    debug!("left:                        {}", left);
+    debug!("------------------------------------");
 
     // paint mismatched pixel amount with the least value colour
     let mut pi = 0;
     for _ in 0..(left + zero_value_elements) {
-        let sp = pixels.get(pi).expect("pixels error");
+        let sp = pixels.get(pi).ok_or(Colouring {
+            painted: pi,
+            pixels: pixels.len(),
+        })?;
         pi += 1;
-        data.colour(sp.x, sp.y, palette.spectrum_value(0));
+        colour(sp.x, sp.y, palette.spectrum_value(0));
     }
 
     // colour all remaining pixels, these are order by value
     for palette_colour_index in 0..palette_colour_count {
         for _ in 0..single_colour_use {
             // colour all these pixels with same colour
-            let sp = pixels.get(pi).expect("pixels error");
+            let sp = pixels.get(pi).ok_or(Colouring {
+                painted: pi,
+                pixels: pixels.len(),
+            })?;
             pi += 1;
             if sp.value <= COLOURING_THRESHOLD {
                 // colour zero-value elements and low-value-noise with the darkest colour
-                data.colour(sp.x, sp.y, palette.spectrum_value(0));
+                colour(sp.x, sp.y, palette.spectrum_value(0));
             } else {
                 // perfect-colour all significant pixels
-                data.colour(
+                colour(
                     sp.x,
                     sp.y,
                     palette.spectrum_value(palette_colour_index as usize),
@@ -95,9 +138,15 @@ pub fn perfectly_colour_nebula_values(data: &DataImage, palette: &Palette) {
             }
         }
     }
-    assert_eq!(pixels.len(), pi);
-    println!("painted:                     {}", pi);
+    if pixels.len() != pi {
+        return Err(Colouring {
+            painted: pi,
+            pixels: pixels.len(),
+        });
+    }
+    debug!("painted:                     {}", pi);
     // Behold, the colouring is perfect!
+    Ok(())
 }
 
 /*
@@ -261,8 +310,11 @@ pub fn perfectly_colour_mandelbrot_values(
     data: &DataImage,
     palette: &Palette,
     palette_zero: &Palette,
-) {
-    println!("perfectly_colour_mandelbrot_values()");
+) -> Result<()> {
+    debug!("perfectly_colour_mandelbrot_values()");
+    if palette.spectrum.is_empty() {
+        return Err(Config("palette has no colours".to_string()));
+    }
 
     let width = data.width_x;
     let height = data.height_y;
@@ -314,23 +366,26 @@ pub fn perfectly_colour_mandelbrot_values(
 
     let left = all_pixels_non_zero - (palette_colour_count * single_colour_use);
 
-    println!("------------------------------------");
-    println!("All pixels to paint:         {}", all_pixels_total);
-    println!(
+    debug!("------------------------------------");
+    debug!("All pixels to paint:         {}", all_pixels_total);
+    debug!(
         "---------------------------> {}",
         zero_value_elements + left + (single_colour_use * palette_colour_count)
     );
-    println!("Zero value pixels to paint:  {}", zero_value_elements);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero);
-    println!("Spectrum, available colours: {}", palette_colour_count);
-    println!("Pixels per each colour:      {}", single_colour_use);
-    println!("left:                        {}", left);
-    println!("------------------------------------");
+    debug!("Zero value pixels to paint:  {}", zero_value_elements);
+    debug!("Non zero pixels to paint:    {}", all_pixels_non_zero);
+    debug!("Spectrum, available colours: {}", palette_colour_count);
+    debug!("Pixels per each colour:      {}", single_colour_use);
+    debug!("left:                        {}", left);
+    debug!("------------------------------------");
 
     // paint mismatched pixel amount with the least value colour
     let mut pi = 0;
     for _ in 0..left {
-        let mp = pixels.get(pi).expect("pixels error");
+        let mp = pixels.get(pi).ok_or(Colouring {
+            painted: pi,
+            pixels: pixels.len(),
+        })?;
         pi += 1;
         data.colour(mp.x, mp.y, palette.spectrum_value(0));
     }
@@ -338,7 +393,10 @@ pub fn perfectly_colour_mandelbrot_values(
     for palette_colour_index in 0..palette_colour_count {
         for _ in 0..single_colour_use {
             // colour all these pixels with same colour
-            let mp = pixels.get(pi).expect("pixels error");
+            let mp = pixels.get(pi).ok_or(Colouring {
+                painted: pi,
+                pixels: pixels.len(),
+            })?;
             pi += 1;
             // perfect-colour all significant pixels
             data.colour(
@@ -348,8 +406,12 @@ pub fn perfectly_colour_mandelbrot_values(
             );
         }
     }
-    let pixels_length = pixels.len();
-    assert_eq!(pixels.len(), pi);
+    if pixels.len() != pi {
+        return Err(Colouring {
+            painted: pi,
+            pixels: pixels.len(),
+        });
+    }
 
     // Fix black dots caused by quad inverse imperfection
     // Keep incorrect quad results
@@ -366,24 +428,33 @@ pub fn perfectly_colour_mandelbrot_values(
     }
 
     // Paint insides of Mandelbrot set
+    if zero_value_elements > 0 && palette_zero.spectrum.is_empty() {
+        return Err(Config("palette_zero has no colours".to_string()));
+    }
     let zero_palette_colour_count = palette_zero.spectrum.len() as u32;
     let zero_single_colour_use =
         (zero_value_elements as f64 / zero_palette_colour_count as f64) as u32;
     let zero_left = zero_value_elements - (zero_palette_colour_count * zero_single_colour_use);
 
-    println!("zero_palette_colour_count:   {}", zero_palette_colour_count);
-    println!("zero_single_colour_use:      {}", zero_single_colour_use);
-    println!("zero_left:                   {}", zero_left);
+    debug!("zero_palette_colour_count:   {}", zero_palette_colour_count);
+    debug!("zero_single_colour_use:      {}", zero_single_colour_use);
+    debug!("zero_left:                   {}", zero_left);
     let mut piz = 0;
     for _ in 0..zero_left {
-        let mp = pixels_zero.get(piz).expect("pixel error");
+        let mp = pixels_zero.get(piz).ok_or(Colouring {
+            painted: pi + piz,
+            pixels: all_pixels_total as usize,
+        })?;
         piz += 1;
         data.colour(mp.x, mp.y, palette_zero.spectrum_value(0usize));
     }
     for zero_palette_colour_index in 0..zero_palette_colour_count {
         for _ in 0..zero_single_colour_use {
             // colour all these pixels with same colour
-            let mp = pixels_zero.get(piz).expect("pixel error");
+            let mp = pixels_zero.get(piz).ok_or(Colouring {
+                painted: pi + piz,
+                pixels: all_pixels_total as usize,
+            })?;
             piz += 1;
             data.colour(
                 mp.x,
@@ -392,11 +463,15 @@ pub fn perfectly_colour_mandelbrot_values(
             );
         }
     }
-    assert_eq!(pixels_zero.len(), piz);
-    assert_eq!(pixels_zero.len() + pixels_length, all_pixels_total as usize);
-    assert_eq!(all_pixels_total as usize, pi + piz);
-    println!("painted:                     {}", pi + piz);
+    if pixels_zero.len() != piz || all_pixels_total as usize != pi + piz {
+        return Err(Colouring {
+            painted: pi + piz,
+            pixels: all_pixels_total as usize,
+        });
+    }
+    debug!("painted:                     {}", pi + piz);
     // Behold, the colouring is perfect!
+    Ok(())
 }
 
 // Return average colour of neighbour elements
@@ -438,6 +513,38 @@ fn check_domain(x: i32, y: i32, width: usize, height: usize) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use crate::fractal::init_trivial_static_config;
+    use crate::palettes::new_palette_by_name;
+    use crate::palettes::PaletteName::BlueToWhiteCircleUp;
+    use crate::perfect_colour_distribution::{
+        perfectly_colour_nebula_grid, perfectly_colour_nebula_values,
+    };
+    use crate::{area, data_image, value_grid};
+
     #[test]
     fn test_it() {}
+
+    #[test]
+    fn test_perfectly_colour_nebula_grid() {
+        let conf = init_trivial_static_config();
+        let area = area::init(&conf);
+        let data_image = data_image::init(&conf, &area);
+        for y in 0..conf.height_y {
+            for x in 0..conf.width_x {
+                data_image.mo_px_at(x, y).as_mut().unwrap().value = (x * y % 13) as u32;
+            }
+        }
+        let palette = new_palette_by_name(&BlueToWhiteCircleUp);
+
+        let image =
+            perfectly_colour_nebula_grid(&value_grid::snapshot(&data_image, 1), &palette).unwrap();
+        perfectly_colour_nebula_values(&data_image, &palette).unwrap();
+
+        for y in 0..conf.height_y {
+            for x in 0..conf.width_x {
+                let colour = *image.get_pixel(x as u32, y as u32);
+                assert_eq!(data_image.colour_at(x, y), Some(colour));
+            }
+        }
+    }
 }
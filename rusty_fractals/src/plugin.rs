@@ -0,0 +1,267 @@
+use crate::config_builder;
+use crate::config_builder::Zoom::Width;
+use crate::fractal::{FractalCalculationType, FractalConfig, OrbitType};
+use crate::mem::Mem;
+use crate::mem_collatz::MemCollatz;
+use crate::mem_phoenix::MemPhoenix;
+use crate::palettes::PaletteName;
+use crate::resolution_multiplier::ResolutionMultiplier;
+use std::ffi::c_char;
+
+/**
+ * Symbol every plugin exports, the version changes with any incompatible change of the structs below
+ * extern "C" fn rusty_fractals_plugin_v1() -> *const PluginDescriptor
+ */
+pub const PLUGIN_ENTRY_POINT: &str = "rusty_fractals_plugin_v1";
+
+/**
+ * Formulas of one plugin library
+ * Plugin returns pointer to its static descriptor, which is never released.
+ */
+#[repr(C)]
+pub struct PluginDescriptor {
+    pub formulas: *const PluginFormula,
+    pub formulas_len: usize,
+}
+
+/**
+ * Formula of the plugin is registered by its name, and replaces formula of the same name
+ */
+#[repr(C)]
+pub struct PluginFormula {
+    // nul terminated
+    pub name: *const c_char,
+    pub config: PluginConfig,
+    pub math: PluginMath,
+}
+
+/**
+ * FractalMath of the plugin, for one of the memory types
+ */
+#[repr(C)]
+pub enum PluginMath {
+    Mem(extern "C" fn(m: &mut Mem, origin_re: f64, origin_im: f64)),
+    MemCollatz(extern "C" fn(m: &mut MemCollatz, origin_re: f64, origin_im: f64)),
+    MemPhoenix(extern "C" fn(m: &mut MemPhoenix, origin_re: f64, origin_im: f64)),
+}
+
+/**
+ * Default config of the plugin formula, other values are the defaults of its calculation type
+ */
+#[repr(C)]
+pub struct PluginConfig {
+    // nul terminated
+    pub name: *const c_char,
+    pub fractal_calc_type: FractalCalculationType,
+    pub orbits: OrbitType,
+    pub iteration_min: u32,
+    pub iteration_max: u32,
+    pub resolution_multiplier: ResolutionMultiplier,
+    pub palette: PaletteName,
+    pub palette_zero: PaletteName,
+    pub width_x: usize,
+    pub height_y: usize,
+    pub width_re: f64,
+    pub center_re: f64,
+    pub center_im: f64,
+}
+
+// plugins keep descriptors in statics, pointers of them point only to other statics
+unsafe impl Sync for PluginDescriptor {}
+unsafe impl Sync for PluginFormula {}
+
+impl PluginConfig {
+    pub fn build(&self, name: &str) -> FractalConfig {
+        config_builder::init(name, self.fractal_calc_type)
+            .orbits(self.orbits)
+            .iterations(self.iteration_min, self.iteration_max)
+            .resolution_multiplier(self.resolution_multiplier)
+            .palette(self.palette)
+            .palette_zero(self.palette_zero)
+            .size(self.width_x, self.height_y)
+            .zoom(Width(self.width_re))
+            .center(self.center_re, self.center_im)
+            .build()
+    }
+}
+
+#[cfg(feature = "plugins")]
+pub use loader::{load, load_dir};
+
+/**
+ * Loading of plugin libraries, only applications which load plugins need the plugins feature
+ */
+#[cfg(feature = "plugins")]
+mod loader {
+    use crate::error::FractalError::Plugin;
+    use crate::error::Result;
+    use crate::formulas;
+    use crate::formulas::{Formula, Registry};
+    use crate::fractal::{FractalConfig, FractalMath, MemType};
+    use crate::plugin::{PluginDescriptor, PluginMath, PLUGIN_ENTRY_POINT};
+    use crate::{error, info};
+    use libloading::{Library, Symbol};
+    use std::env::consts::DLL_EXTENSION;
+    use std::ffi::{c_char, CStr};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    // plugin math with the library it lives in, the library is unloaded after the last formula is dropped
+    struct PluginFractal<M> {
+        math: extern "C" fn(m: &mut M, origin_re: f64, origin_im: f64),
+        _library: Arc<Library>,
+    }
+
+    impl<M: Send + Sync> FractalMath<M> for PluginFractal<M> {
+        fn math(&self, m: &mut M, origin_re: f64, origin_im: f64) {
+            (self.math)(m, origin_re, origin_im);
+        }
+    }
+
+    /**
+     * Registers formulas of the plugin library
+     * Loading runs the initialization code of the library, load only trusted plugins.
+     * returns names of the registered formulas
+     */
+    pub fn load(path: &Path, registry: &mut Registry) -> Result<Vec<String>> {
+        let plugin_error = |message: String| Plugin(format!("{}: {}", path.display(), message));
+        let library = unsafe { Library::new(path) }.map_err(|e| plugin_error(e.to_string()))?;
+        let library = Arc::new(library);
+        let descriptor = unsafe {
+            let entry_point: Symbol<extern "C" fn() -> *const PluginDescriptor> = library
+                .get(PLUGIN_ENTRY_POINT.as_bytes())
+                .map_err(|e| plugin_error(e.to_string()))?;
+            entry_point()
+                .as_ref()
+                .ok_or_else(|| plugin_error("no descriptor".to_string()))?
+        };
+        if descriptor.formulas.is_null() {
+            return Err(plugin_error("no formulas".to_string()));
+        }
+        let plugin_formulas =
+            unsafe { std::slice::from_raw_parts(descriptor.formulas, descriptor.formulas_len) };
+
+        let mut names = Vec::new();
+        for plugin_formula in plugin_formulas {
+            let name = unsafe { c_string(plugin_formula.name) }
+                .ok_or_else(|| plugin_error("formula without name".to_string()))?;
+            let config_name =
+                unsafe { c_string(plugin_formula.config.name) }.unwrap_or(name.clone());
+            let config = plugin_formula.config.build(&config_name);
+            if config.iteration_min >= config.iteration_max
+                || config.width_x == 0
+                || config.height_y == 0
+            {
+                return Err(plugin_error(format!("invalid config of {}", name)));
+            }
+            let formula = match plugin_formula.math {
+                PluginMath::Mem(math) => plugin(&name, config, math, &library),
+                PluginMath::MemCollatz(math) => plugin(&name, config, math, &library),
+                PluginMath::MemPhoenix(math) => plugin(&name, config, math, &library),
+            };
+            info!("plugin formula {} from {}", name, path.display());
+            registry.register(formula);
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    /**
+     * Registers formulas of all plugin libraries in the directory, in order of file names
+     * Plugin which can't be loaded is reported and skipped.
+     * returns names of the registered formulas
+     */
+    pub fn load_dir(dir: &Path, registry: &mut Registry) -> Result<Vec<String>> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.retain(|path| path.extension().is_some_and(|e| e == DLL_EXTENSION));
+        paths.sort();
+
+        let mut names = Vec::new();
+        for path in paths {
+            match load(&path, registry) {
+                Ok(loaded) => names.extend(loaded),
+                Err(e) => error!("{}", e),
+            }
+        }
+        Ok(names)
+    }
+
+    fn plugin<M: MemType<M> + 'static>(
+        name: &str,
+        config: FractalConfig,
+        math: extern "C" fn(m: &mut M, origin_re: f64, origin_im: f64),
+        library: &Arc<Library>,
+    ) -> Formula {
+        let fractal = PluginFractal {
+            math,
+            _library: library.clone(),
+        };
+        formulas::formula(name, config, fractal)
+    }
+
+    unsafe fn c_string(name: *const c_char) -> Option<String> {
+        if name.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(name).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::FractalCalculationType::StaticImageNebula;
+    use crate::fractal::OrbitType::Finite;
+    use crate::palettes::PaletteName::{BlackToWhiteCircleUp, Nothing};
+    use crate::plugin::PluginConfig;
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use std::ptr;
+
+    #[test]
+    fn test_build() {
+        let plugin_config = PluginConfig {
+            name: ptr::null(),
+            fractal_calc_type: StaticImageNebula,
+            orbits: Finite,
+            iteration_min: 42,
+            iteration_max: 800,
+            resolution_multiplier: Square3,
+            palette: BlackToWhiteCircleUp,
+            palette_zero: Nothing,
+            width_x: 60,
+            height_y: 40,
+            width_re: 3.0,
+            center_re: -0.5,
+            center_im: 0.1,
+        };
+        let config = plugin_config.build("Plugin");
+        assert_eq!(config.name, "Plugin");
+        assert_eq!(config.iteration_max, 800);
+        assert_eq!(config.resolution_multiplier, Square3);
+        assert_eq!((config.width_x, config.height_y), (60, 40));
+        assert_eq!(config.center_im, 0.1);
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_load_dir() {
+        use crate::formulas;
+        use crate::plugin::{load, load_dir};
+        use std::fs;
+
+        let dir = std::env::temp_dir().join("rusty_fractals_test_plugins");
+        fs::create_dir_all(&dir).unwrap();
+        // not a library, reported and skipped
+        let broken = dir.join(format!("broken.{}", std::env::consts::DLL_EXTENSION));
+        fs::write(&broken, "not a library").unwrap();
+
+        let mut registry = formulas::init();
+        assert!(load(&broken, &mut registry).is_err());
+        assert!(load_dir(&dir, &mut registry).unwrap().is_empty());
+        assert!(registry.is_empty());
+        assert!(load_dir(&dir.join("missing"), &mut registry).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
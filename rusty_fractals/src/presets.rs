@@ -0,0 +1,210 @@
+use crate::config_builder;
+use crate::config_builder::ConfigBuilder;
+use crate::config_builder::Zoom::Width;
+use crate::fractal::FractalCalculationType::{
+    DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot,
+    StaticSpectralImageEuler,
+};
+use crate::fractal::OrbitType::{Ignore, Infinite};
+use crate::palettes::PaletteName::{BlackToWhiteCircleUp, GrayToBlue, Nothing, PurpleToWhite};
+use crate::resolution_multiplier::ResolutionMultiplier::{Square11, Square3, Square5, Square9};
+
+/**
+ * Configs of the fractal binaries, by binary name without the fractal_ prefix
+ */
+pub const PRESETS: [&str; 18] = [
+    "collatz_conjecture",
+    "collatz_conjecture_orbits",
+    "collatz_mandelbrot",
+    "euler",
+    "fat_star",
+    "fat_star_magnific",
+    "fat_star_tentacle",
+    "head",
+    "infinity",
+    "infinity_top",
+    "lotus",
+    "mandelbrot",
+    "mandelbrot_of_mandelbrot",
+    "nebula",
+    "nebula_of_nebula",
+    "nebula_side",
+    "nebula_top",
+    "science",
+];
+
+pub fn by_name(preset: &str) -> Option<ConfigBuilder> {
+    let builder = match preset {
+        "collatz_conjecture" => collatz_conjecture(),
+        "collatz_conjecture_orbits" => collatz_conjecture_orbits(),
+        "collatz_mandelbrot" => collatz_mandelbrot(),
+        "euler" => euler(),
+        "fat_star" => fat_star(),
+        "fat_star_magnific" => fat_star_magnific(),
+        "fat_star_tentacle" => fat_star_tentacle(),
+        "head" => head(),
+        "infinity" => infinity(),
+        "infinity_top" => infinity_top(),
+        "lotus" => lotus(),
+        "mandelbrot" => mandelbrot(),
+        "mandelbrot_of_mandelbrot" => mandelbrot_of_mandelbrot(),
+        "nebula" => nebula(),
+        "nebula_of_nebula" => nebula_of_nebula(),
+        "nebula_side" => nebula_side(),
+        "nebula_top" => nebula_top(),
+        "science" => science(),
+        _ => return None,
+    };
+    Some(builder)
+}
+
+pub fn collatz_conjecture() -> ConfigBuilder {
+    config_builder::init("Collatz Conjecture", StaticSequenceMandelbrot)
+        .orbits(Ignore)
+        .iterations(0, 1348)
+        .center(-0.08848544555358048, -0.2006794350685328)
+        .update(0, 0)
+}
+
+pub fn collatz_conjecture_orbits() -> ConfigBuilder {
+    config_builder::init("CollatzConjectureOrbits", StaticSequenceMandelbrot)
+        .orbits(Infinite)
+        .iterations(7, 1348)
+        .resolution_multiplier(Square11)
+        .palette_zero(Nothing)
+        .zoom(Width(5.0))
+        .center(-0.08848544555358048, -0.2006794350685328)
+}
+
+pub fn collatz_mandelbrot() -> ConfigBuilder {
+    config_builder::init("Collatz Conjecture Mandelbrot", StaticSequenceMandelbrot)
+        .orbits(Infinite)
+        .iterations(0, 14800)
+        .palette_zero(GrayToBlue)
+        .zoom(Width(3.0))
+        .center(-0.8829529917141723, -0.21469922133531946)
+}
+
+pub fn euler() -> ConfigBuilder {
+    config_builder::init("Euler", StaticSpectralImageEuler)
+}
+
+pub fn fat_star() -> ConfigBuilder {
+    config_builder::init("Fat Star", StaticImageNebula)
+        .orbits(Infinite)
+        .iterations(42, 22000)
+        .size(800, 800)
+        .center(0.0, 0.0)
+}
+
+pub fn fat_star_magnific() -> ConfigBuilder {
+    config_builder::init("Fat Star", StaticImageNebula)
+        .orbits(Infinite)
+        .iterations(8, 81000)
+        .size(1200, 1200)
+        .zoom(Width(0.15))
+        .center(0.5425, -0.31)
+}
+
+pub fn fat_star_tentacle() -> ConfigBuilder {
+    config_builder::init("Fat Star Tentacle", StaticImageNebula)
+        .orbits(Infinite)
+        .iterations(42, 2200)
+        .size(600, 600)
+        .zoom(Width(0.5))
+        .center(0.5, -0.38)
+}
+
+pub fn head() -> ConfigBuilder {
+    config_builder::init("Head", StaticImageNebula)
+        .iterations(8, 25000)
+        .resolution_multiplier(Square9)
+        .zoom(Width(5.0))
+        .center(-0.16884290496519, -0.37573460559804)
+}
+
+pub fn infinity() -> ConfigBuilder {
+    config_builder::init("Infinity", StaticImageNebula)
+        .orbits(Infinite)
+        .iterations(3000, 30_000)
+        .size(600, 600)
+        .zoom(Width(2.6))
+}
+
+pub fn infinity_top() -> ConfigBuilder {
+    config_builder::init("Infinity Top", StaticImageNebula)
+        .orbits(Infinite)
+        .iterations(3000, 180_000)
+        .size(600, 600)
+        .zoom(Width(1.8))
+        .center(-1.0, 0.0)
+}
+
+pub fn lotus() -> ConfigBuilder {
+    config_builder::init("Lotus", StaticImageNebula)
+        .iterations(42, 8000)
+        .resolution_multiplier(Square11)
+        .size(1280, 1000)
+        .center(0.67748277351478, -1.18770078111202)
+}
+
+pub fn mandelbrot() -> ConfigBuilder {
+    config_builder::init("Mandelbrot", StaticSequenceMandelbrot).zoom(Width(4.5))
+}
+
+pub fn mandelbrot_of_mandelbrot() -> ConfigBuilder {
+    config_builder::init("Mandelbrot of Mandelbrot", StaticImageMandelbrot)
+}
+
+pub fn nebula() -> ConfigBuilder {
+    config_builder::init("Nebula", StaticImageNebula)
+        .resolution_multiplier(Square9)
+        .palette(BlackToWhiteCircleUp)
+        .size(1280, 1000)
+}
+
+pub fn nebula_of_nebula() -> ConfigBuilder {
+    config_builder::init("Nebula of Nebula", StaticImageNebula)
+        .iterations(42, 2480)
+        .resolution_multiplier(Square3)
+        .size(800, 800)
+        .zoom(Width(0.5))
+        .center(0.0, 0.0)
+}
+
+pub fn nebula_side() -> ConfigBuilder {
+    config_builder::init("Nebula Side", DynamicSequenceNebula)
+        .zoom(Width(7.0))
+        .center(-0.10675625916322415, -0.8914368889277283)
+}
+
+pub fn nebula_top() -> ConfigBuilder {
+    config_builder::init("Nebula Top", DynamicSequenceNebula)
+        .palette(PurpleToWhite)
+        .zoom(Width(6.0))
+        .center(-1.40115859004747, -0.00000000709356)
+}
+
+pub fn science() -> ConfigBuilder {
+    config_builder::init("Science", StaticImageNebula)
+        .iterations(42, 1800)
+        .resolution_multiplier(Square5)
+        .size(600, 600)
+        .zoom(Width(3.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::presets::{by_name, PRESETS};
+
+    #[test]
+    fn test_by_name() {
+        for preset in PRESETS {
+            let config = by_name(preset).unwrap().build();
+            assert!(config.iteration_min < config.iteration_max);
+            assert!(config.width_re > 0.0);
+        }
+        assert!(by_name("nebula_x").is_none());
+        assert_eq!(by_name("head").unwrap().build().name, "Head");
+    }
+}
@@ -0,0 +1,433 @@
+use crate::estimator::format_duration;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// characters of the progress bar
+const BAR_WIDTH: usize = 40;
+
+/**
+ * Stages of calculation reported by progress events
+ * Chunked stages report each finished chunk, Translate and Colour only start and finish.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    // Mandelbrot type fractal
+    Mandelbrot,
+    // Nebula type fractal, one origin in the center of each pixel
+    Nebula,
+    // Nebula type fractal, more origins within pixels close to good pixels
+    Wrap,
+    // dynamic paths to pixel values
+    Translate,
+    Colour,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Mandelbrot => "mandelbrot",
+            Stage::Nebula => "nebula",
+            Stage::Wrap => "wrap",
+            Stage::Translate => "translate",
+            Stage::Colour => "colour",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgressEvent {
+    StageStarted {
+        frame: u32,
+        stage: Stage,
+        chunks_total: usize,
+    },
+    ChunkDone {
+        frame: u32,
+        stage: Stage,
+        chunks_done: usize,
+        chunks_total: usize,
+        // path points accumulated by the frame so far, 0 for Mandelbrot
+        points: u64,
+        // remaining time of the whole calculation, not of the stage
+        eta: Option<Duration>,
+    },
+    StageFinished {
+        frame: u32,
+        stage: Stage,
+        duration: Duration,
+    },
+}
+
+impl ProgressEvent {
+    /**
+     * One line of JSON, durations in milliseconds
+     */
+    pub fn to_json(&self) -> String {
+        match self {
+            ProgressEvent::StageStarted {
+                frame,
+                stage,
+                chunks_total,
+            } => format!(
+                "{{\"event\":\"stage_started\",\"frame\":{},\"stage\":\"{}\",\"chunks_total\":{}}}",
+                frame,
+                stage.name(),
+                chunks_total
+            ),
+            ProgressEvent::ChunkDone {
+                frame,
+                stage,
+                chunks_done,
+                chunks_total,
+                points,
+                eta,
+            } => format!(
+                "{{\"event\":\"chunk_done\",\"frame\":{},\"stage\":\"{}\",\"chunks_done\":{},\"chunks_total\":{},\"points\":{},\"eta_ms\":{}}}",
+                frame,
+                stage.name(),
+                chunks_done,
+                chunks_total,
+                points,
+                eta.map_or("null".to_string(), |eta| eta.as_millis().to_string())
+            ),
+            ProgressEvent::StageFinished {
+                frame,
+                stage,
+                duration,
+            } => format!(
+                "{{\"event\":\"stage_finished\",\"frame\":{},\"stage\":\"{}\",\"duration_ms\":{}}}",
+                frame,
+                stage.name(),
+                duration.as_millis()
+            ),
+        }
+    }
+}
+
+/**
+ * Receives progress events of a calculation
+ * Events come from calculation threads, the listener should return quickly.
+ */
+pub trait ProgressListener: Send + Sync {
+    fn event(&self, event: &ProgressEvent);
+}
+
+/**
+ * Callback listener
+ */
+impl<T> ProgressListener for T
+where
+    T: Fn(&ProgressEvent) + Send + Sync,
+{
+    fn event(&self, event: &ProgressEvent) {
+        self(event);
+    }
+}
+
+/**
+ * Channel listener, events are dropped when the receiver is gone
+ */
+pub fn channel_listener(sender: Sender<ProgressEvent>) -> Arc<dyn ProgressListener> {
+    Arc::new(move |event: &ProgressEvent| {
+        let _ = sender.send(event.clone());
+    })
+}
+
+/**
+ * Writes each event as a line of JSON, events are dropped when the output is closed, e.g. by | head
+ */
+pub struct JsonLines<W: Write + Send> {
+    out: Mutex<W>,
+}
+
+impl<W: Write + Send> ProgressListener for JsonLines<W> {
+    fn event(&self, event: &ProgressEvent) {
+        let out = &mut self.out.lock().unwrap();
+        let _ = writeln!(out, "{}", event.to_json()).and_then(|_| out.flush());
+    }
+}
+
+pub fn init_json_lines<W: Write + Send>(out: W) -> JsonLines<W> {
+    JsonLines {
+        out: Mutex::new(out),
+    }
+}
+
+/**
+ * Progress bar redrawn on one line of stderr
+ * [##########------------------------------] 25% nebula 100/400, frame 3, ETA 5m 12s
+ */
+pub struct ProgressBar;
+
+impl ProgressListener for ProgressBar {
+    fn event(&self, event: &ProgressEvent) {
+        match event {
+            ProgressEvent::ChunkDone {
+                frame,
+                stage,
+                chunks_done,
+                chunks_total,
+                eta,
+                ..
+            } => {
+                eprint!(
+                    "\r{}",
+                    progress_bar_line(*frame, *stage, *chunks_done, *chunks_total, *eta)
+                );
+            }
+            ProgressEvent::StageFinished {
+                frame,
+                stage,
+                duration,
+            } => {
+                eprintln!(
+                    "\r{} frame {} finished in {}{}",
+                    stage.name(),
+                    frame,
+                    format_duration(*duration),
+                    " ".repeat(BAR_WIDTH)
+                );
+            }
+            ProgressEvent::StageStarted { .. } => {}
+        }
+    }
+}
+
+fn progress_bar_line(
+    frame: u32,
+    stage: Stage,
+    chunks_done: usize,
+    chunks_total: usize,
+    eta: Option<Duration>,
+) -> String {
+    let done = (chunks_done * BAR_WIDTH) / chunks_total.max(1);
+    let eta = eta.map_or(String::new(), |eta| {
+        format!(", ETA {}", format_duration(eta))
+    });
+    format!(
+        "[{}{}] {:3}% {} {}/{}, frame {}{}",
+        "#".repeat(done),
+        "-".repeat(BAR_WIDTH - done),
+        chunks_done * 100 / chunks_total.max(1),
+        stage.name(),
+        chunks_done,
+        chunks_total,
+        frame,
+        eta
+    )
+}
+
+/**
+ * Emits progress events of Machine to its listener
+ */
+pub struct Progress {
+    listener: Option<Arc<dyn ProgressListener>>,
+}
+
+impl Progress {
+    pub fn set_listener(&mut self, listener: Arc<dyn ProgressListener>) {
+        self.listener = Some(listener);
+    }
+
+    /**
+     * Stage of frame started, chunks_total is 0 for stages which don't calculate by chunks
+     * Progress of the stage is reported through the returned StageProgress.
+     * Stages may overlap, zoom colours frame N during calculation of frame N+1.
+     */
+    pub fn stage(&self, frame: u32, stage: Stage, chunks_total: usize) -> StageProgress {
+        let stage_progress = StageProgress {
+            listener: self.listener.clone(),
+            frame,
+            stage,
+            chunks_total,
+            chunks_done: AtomicUsize::new(0),
+            start: Instant::now(),
+        };
+        stage_progress.emit(ProgressEvent::StageStarted {
+            frame,
+            stage,
+            chunks_total,
+        });
+        stage_progress
+    }
+}
+
+pub fn init() -> Progress {
+    Progress { listener: None }
+}
+
+/**
+ * Progress of one running stage, shared by calculation threads
+ */
+pub struct StageProgress {
+    listener: Option<Arc<dyn ProgressListener>>,
+    frame: u32,
+    stage: Stage,
+    chunks_total: usize,
+    chunks_done: AtomicUsize,
+    start: Instant,
+}
+
+impl StageProgress {
+    /**
+     * points and eta are evaluated only when there is a listener
+     */
+    pub fn chunk_done<P, E>(&self, points: P, eta: E)
+    where
+        P: FnOnce() -> u64,
+        E: FnOnce() -> Option<Duration>,
+    {
+        let chunks_done = self.chunks_done.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.listener.is_some() {
+            self.emit(ProgressEvent::ChunkDone {
+                frame: self.frame,
+                stage: self.stage,
+                chunks_done,
+                chunks_total: self.chunks_total,
+                points: points(),
+                eta: eta(),
+            });
+        }
+    }
+
+    /**
+     * returns duration of the stage
+     */
+    pub fn finish(self) -> Duration {
+        let duration = self.start.elapsed();
+        self.emit(ProgressEvent::StageFinished {
+            frame: self.frame,
+            stage: self.stage,
+            duration,
+        });
+        duration
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(listener) = &self.listener {
+            listener.event(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress::Stage::{Colour, Nebula};
+    use crate::progress::{
+        channel_listener, init, init_json_lines, progress_bar_line, ProgressEvent,
+    };
+    use std::io;
+    use std::io::Write;
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stage() {
+        let (sender, receiver) = channel();
+        let mut progress = init();
+        progress.set_listener(channel_listener(sender));
+
+        let stage = progress.stage(2, Nebula, 2);
+        stage.chunk_done(|| 10, || None);
+        stage.chunk_done(|| 25, || Some(Duration::ZERO));
+        stage.finish();
+        drop(progress);
+
+        let events: Vec<ProgressEvent> = receiver.iter().collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            ProgressEvent::StageStarted {
+                frame: 2,
+                stage: Nebula,
+                chunks_total: 2
+            }
+        );
+        assert_eq!(
+            events[2],
+            ProgressEvent::ChunkDone {
+                frame: 2,
+                stage: Nebula,
+                chunks_done: 2,
+                chunks_total: 2,
+                points: 25,
+                eta: Some(Duration::ZERO)
+            }
+        );
+        assert!(matches!(
+            events[3],
+            ProgressEvent::StageFinished {
+                frame: 2,
+                stage: Nebula,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_without_listener() {
+        let stage = init().stage(1, Colour, 0);
+        stage.chunk_done(|| panic!("not evaluated"), || None);
+        stage.finish();
+    }
+
+    #[test]
+    fn test_to_json() {
+        let chunk = ProgressEvent::ChunkDone {
+            frame: 1,
+            stage: Nebula,
+            chunks_done: 3,
+            chunks_total: 400,
+            points: 1234,
+            eta: Some(Duration::from_millis(5500)),
+        };
+        assert_eq!(
+            chunk.to_json(),
+            "{\"event\":\"chunk_done\",\"frame\":1,\"stage\":\"nebula\",\"chunks_done\":3,\"chunks_total\":400,\"points\":1234,\"eta_ms\":5500}"
+        );
+
+        let json_lines = Arc::new(init_json_lines(Vec::new()));
+        let mut progress = init();
+        progress.set_listener(json_lines.clone());
+        progress.stage(1, Colour, 0).finish();
+        drop(progress);
+
+        let out = String::from_utf8(json_lines.out.lock().unwrap().clone()).unwrap();
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.starts_with("{\"event\":\"stage_started\",\"frame\":1,\"stage\":\"colour\""));
+
+        // closed output doesn't stop the calculation
+        let mut progress = init();
+        progress.set_listener(Arc::new(init_json_lines(Closed {})));
+        progress.stage(1, Colour, 0).finish();
+    }
+
+    struct Closed {}
+
+    impl Write for Closed {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[test]
+    fn test_progress_bar_line() {
+        let line = progress_bar_line(3, Nebula, 100, 400, Some(Duration::from_secs(312)));
+        assert_eq!(
+            line,
+            format!(
+                "[{}{}]  25% nebula 100/400, frame 3, ETA 5m 12s",
+                "#".repeat(10),
+                "-".repeat(30)
+            )
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use crate::control::Control;
+use crate::data_image::DataImage;
+use crate::error::FractalError::{Config, Panicked};
+use crate::error::Result;
+use crate::fractal::FractalCalculationType::{
+    DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot,
+    StaticSpectralImageEuler,
+};
+use crate::fractal::{FractalConfig, FractalMath, MemType};
+use crate::machine::Machine;
+use crate::progress::ProgressListener;
+use crate::value_grid::ValueGrid;
+use crate::{control, machine, value_grid};
+use image::{Rgb, RgbImage};
+use std::any::Any;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+
+/**
+ * Image rendered without window
+ */
+pub struct Render {
+    pub image: RgbImage,
+    // pixel values the image was coloured from
+    pub values: ValueGrid,
+}
+
+/**
+ * Render the config to an image, blocks until the calculation finishes
+ * Sequence types render only their first frame.
+ * Each render has its own Machine, renders from more threads share the rayon pool.
+ */
+pub fn render<F, M>(config: &FractalConfig, fractal: F) -> Result<Render>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    render_with_progress(config, fractal, None)
+}
+
+/**
+ * Render the config to an image, progress events come from the calculation threads
+ */
+pub fn render_with_progress<F, M>(
+    config: &FractalConfig,
+    fractal: F,
+    listener: Option<Arc<dyn ProgressListener>>,
+) -> Result<Render>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    let mut machine = machine::init(config, fractal);
+    if let Some(listener) = listener {
+        machine.set_progress_listener(listener);
+    }
+    render_machine(&machine)
+}
+
+/**
+ * Render the config in a new thread
+ * The calculation can be paused or cancelled through the handle, cancelled render returns partial result.
+ */
+pub fn spawn<F, M>(
+    config: &FractalConfig,
+    fractal: F,
+    listener: Option<Arc<dyn ProgressListener>>,
+) -> RenderHandle
+where
+    F: FractalMath<M> + 'static,
+    M: MemType<M> + 'static,
+{
+    let config = config.clone();
+    let control = Arc::new(control::init());
+    let machine_control = control.clone();
+    let thread = thread::spawn(move || {
+        let mut machine = machine::init(&config, fractal);
+        machine.control = machine_control;
+        if let Some(listener) = listener {
+            machine.set_progress_listener(listener);
+        }
+        render_machine(&machine)
+    });
+    RenderHandle { control, thread }
+}
+
+/**
+ * Running render started by spawn()
+ */
+pub struct RenderHandle {
+    control: Arc<Control>,
+    thread: JoinHandle<Result<Render>>,
+}
+
+impl RenderHandle {
+    /**
+     * pause, resume or cancel the render
+     */
+    pub fn control(&self) -> &Control {
+        &self.control
+    }
+
+    /**
+     * true if join() won't block
+     */
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /**
+     * Waits for the render, panic of the render thread is returned as an error
+     */
+    pub fn join(self) -> Result<Render> {
+        self.thread
+            .join()
+            .unwrap_or_else(|panic| Err(Panicked(panic_message(panic))))
+    }
+}
+
+fn render_machine<F, M>(machine: &Machine<F, M>) -> Result<Render>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    match machine.fractal_calc_type {
+        StaticImageNebula => machine.calculate_nebula_static_image()?,
+        DynamicSequenceNebula => machine.calculate_nebula_dynamic_data()?,
+        StaticImageMandelbrot | StaticSequenceMandelbrot => machine.calculate_mandelbrot()?,
+        StaticSpectralImageEuler => {
+            return Err(Config(
+                "StaticSpectralImageEuler is not calculated by Machine".to_string(),
+            ))
+        }
+    }
+    Ok(Render {
+        image: colour_image(&machine.data_image),
+        values: value_grid::snapshot(&machine.data_image, 1),
+    })
+}
+
+// pixels without colour, e.g. of cancelled render, are black
+fn colour_image(data_image: &DataImage) -> RgbImage {
+    let mut image = RgbImage::new(data_image.width_x as u32, data_image.height_y as u32);
+    for (x, y, px) in image.enumerate_pixels_mut() {
+        *px = data_image
+            .colour_at(x as usize, y as usize)
+            .unwrap_or(Rgb([0, 0, 0]));
+    }
+    image
+}
+
+pub fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "render thread panicked".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::{init_trivial_fractal, init_trivial_static_config};
+    use crate::palettes::PaletteName::{BlueToWhiteCircleUp, GrayToBlackCircleDown};
+    use crate::progress::{channel_listener, ProgressEvent};
+    use crate::render::{render, spawn};
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_render() {
+        let mut conf = init_trivial_static_config();
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_max = 100;
+        conf.palette = BlueToWhiteCircleUp;
+        conf.palette_zero = GrayToBlackCircleDown;
+
+        let result = render(&conf, init_trivial_fractal()).unwrap();
+        assert_eq!(result.image.dimensions(), (20, 20));
+        assert_eq!(result.values.values.len(), 400);
+
+        // renders in parallel, each with its own progress
+        let (sender, receiver) = channel();
+        let first = spawn(&conf, init_trivial_fractal(), None);
+        let second = spawn(
+            &conf,
+            init_trivial_fractal(),
+            Some(channel_listener(sender)),
+        );
+        let first = first.join().unwrap();
+        let second = second.join().unwrap();
+
+        assert_eq!(first.values.values, result.values.values);
+        assert_eq!(second.image, result.image);
+        assert!(receiver
+            .iter()
+            .any(|e| matches!(e, ProgressEvent::StageFinished { .. })));
+    }
+
+    #[test]
+    fn test_spawn_cancelled() {
+        let mut conf = init_trivial_static_config();
+        conf.palette = BlueToWhiteCircleUp;
+        conf.palette_zero = GrayToBlackCircleDown;
+
+        let handle = spawn(&conf, init_trivial_fractal(), None);
+        handle.control().cancel();
+        let result = handle.join().unwrap();
+        assert_eq!(result.image.dimensions(), (20, 20));
+    }
+}
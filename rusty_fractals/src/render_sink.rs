@@ -0,0 +1,21 @@
+use crate::data_image::DataImage;
+use image::RgbImage;
+
+/**
+ * Receives intermediate results of a calculation, for example to show or save previews without a window
+ */
+pub trait RenderSink: Send + Sync {
+    /**
+     * Progressive Mandelbrot rendering finished one refinement level
+     * Pixels [x, y] with x and y divisible by step are calculated and coloured,
+     * each of them represents a step x step square. The last level has step 1.
+     */
+    fn refinement_level(&self, data_image: &DataImage, step: usize);
+
+    /**
+     * Zoom video frame was coloured
+     * Called from the colouring stage, while the next frame is already being calculated.
+     * Frames come in order, a slow sink also holds back the calculation.
+     */
+    fn frame(&self, _image: &RgbImage, _frame: u32) {}
+}
@@ -1,4 +1,5 @@
-#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum ResolutionMultiplier {
     // Single point at the center of Mandelbrot pixel
     Single,
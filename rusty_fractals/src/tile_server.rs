@@ -0,0 +1,552 @@
+use crate::constants::{
+    NEBULA_TILE_GRID_ITERATION_MAX, NEBULA_TILE_GRID_WIDTH, TILE_READ_TIMEOUT_SECONDS, TILE_SIZE,
+    TILE_ZOOM_MAX,
+};
+use crate::error::FractalError::Config;
+use crate::error::Result;
+use crate::formulas::{Formula, Registry};
+use crate::fractal::FractalCalculationType::{StaticImageMandelbrot, StaticImageNebula};
+use crate::fractal::FractalConfig;
+use crate::fractal::MandelbrotRendering::Shuffled;
+use crate::palette::Palette;
+use crate::palettes;
+use crate::palettes::PaletteName::{GrayToBlackCircleDown, Nothing};
+use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use crate::value_grid::ValueGrid;
+use crate::{debug, info, warn};
+use image::{ImageFormat, Rgb, RgbImage};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Cursor, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+const NEBULA_GRID_FILE: &str = "grid.png";
+
+/**
+ * Web map tiles of registered formulas, for any slippy map viewer
+ * GET /tiles/{fractal}/{z}/{x}/{y}.png
+ * Tile 0/0/0 is the default area of the formula, squared to its width. Tile y grows with im.
+ * Mandelbrot like formulas render each tile, Nebula formulas cut tiles from one precomputed grid.
+ * Tiles are cached on disk as {cache_dir}/{fractal}/{z}/{x}/{y}.png
+ */
+pub struct TileServer {
+    registry: Registry,
+    cache_dir: PathBuf,
+    // pixels of precomputed nebula grid along each side
+    pub nebula_grid_width: usize,
+    // idle connections are dropped after this long
+    pub read_timeout: Duration,
+    nebula_grids: Mutex<HashMap<String, NebulaGrid>>,
+}
+
+// grid of one formula, locked while it's calculated
+type NebulaGrid = Arc<Mutex<Option<Arc<RgbImage>>>>;
+
+pub fn init(registry: Registry, cache_dir: &Path) -> TileServer {
+    TileServer {
+        registry,
+        cache_dir: cache_dir.to_path_buf(),
+        nebula_grid_width: NEBULA_TILE_GRID_WIDTH,
+        read_timeout: Duration::from_secs(TILE_READ_TIMEOUT_SECONDS),
+        nebula_grids: Mutex::new(HashMap::new()),
+    }
+}
+
+/**
+ * Only local clients can connect, port 0 picks a free port
+ */
+pub fn bind(port: u16) -> io::Result<TcpListener> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+}
+
+impl TileServer {
+    /**
+     * PNG of the tile, from cache or rendered now
+     * Unknown fractal or tile out of range is Config error.
+     */
+    pub fn tile(&self, fractal: &str, z: u32, x: u64, y: u64) -> Result<Vec<u8>> {
+        let formula = self
+            .registry
+            .get(fractal)
+            .ok_or_else(|| Config(format!("unknown fractal {}", fractal)))?;
+        if !in_range(z, x, y) {
+            return Err(Config(format!("no tile {}/{}/{}", z, x, y)));
+        }
+        let file = self
+            .cache_dir
+            .join(fractal)
+            .join(z.to_string())
+            .join(x.to_string())
+            .join(format!("{}.png", y));
+        if let Ok(png) = fs::read(&file) {
+            return Ok(png);
+        }
+
+        debug!("tile() render {}", file.display());
+        let image = if formula.config.is_mandelbrot() {
+            render_tile(formula, z, x, y)?
+        } else {
+            let grid = self.nebula_grid(formula)?;
+            cut_tile(&grid, z, x, y)
+        };
+        let png = encode_png(&image)?;
+        write_file(&file, &png)?;
+        Ok(png)
+    }
+
+    /**
+     * Serves requests until the listener fails, each connection in its own thread
+     */
+    pub fn serve(self: Arc<Self>, listener: TcpListener) -> io::Result<()> {
+        info!("serve() http://{}/", listener.local_addr()?);
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = self.clone();
+            thread::spawn(move || {
+                if let Err(e) = server.respond(stream) {
+                    warn!("could not respond: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.read_timeout))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // headers aren't used
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            header.clear();
+        }
+
+        let response = self.response(&request_line);
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            response.content_type,
+            response.body.len()
+        )?;
+        stream.write_all(&response.body)?;
+        stream.flush()
+    }
+
+    fn response(&self, request_line: &str) -> Response {
+        let mut parts = request_line.split_whitespace();
+        let (method, path) = (parts.next(), parts.next().unwrap_or(""));
+        if method != Some("GET") {
+            return Response::text("405 Method Not Allowed", "only GET is supported");
+        }
+        if path == "/" {
+            return Response::text("200 OK", &self.registry.names().join("\n"));
+        }
+        let Some((fractal, z, x, y)) = parse_tile_path(path) else {
+            return Response::text("404 Not Found", "no such path");
+        };
+        // any other error of an existing tile is the server's fault
+        if self.registry.get(fractal).is_none() || !in_range(z, x, y) {
+            return Response::text("404 Not Found", "no such tile");
+        }
+        match self.tile(fractal, z, x, y) {
+            Ok(png) => Response {
+                status: "200 OK",
+                content_type: "image/png",
+                body: png,
+            },
+            Err(e) => {
+                warn!("could not render tile {}: {}", path, e);
+                Response::text("500 Internal Server Error", &e.to_string())
+            }
+        }
+    }
+
+    /**
+     * The grid is calculated once, other tiles of the formula wait for it, tiles of other formulas don't
+     * Failed or panicked calculation leaves no grid, the next tile calculates it again.
+     */
+    fn nebula_grid(&self, formula: &Formula) -> Result<Arc<RgbImage>> {
+        let grid_lock = recover(&self.nebula_grids)
+            .entry(formula.name.clone())
+            .or_default()
+            .clone();
+        let mut stored = recover(&grid_lock);
+        if let Some(grid) = stored.as_ref() {
+            return Ok(grid.clone());
+        }
+        let file = self.cache_dir.join(&formula.name).join(NEBULA_GRID_FILE);
+        let cached = image::open(&file)
+            .ok()
+            .map(|image| image.into_rgb8())
+            .filter(|image| image.width() as usize == self.nebula_grid_width);
+        let grid = match cached {
+            Some(grid) => grid,
+            None => {
+                info!("nebula_grid() calculate {}", formula.name);
+                let config = nebula_grid_config(&formula.config, self.nebula_grid_width);
+                let grid = formula.render(&config, None)?.image;
+                write_file(&file, &encode_png(&grid)?)?;
+                grid
+            }
+        };
+        let grid = Arc::new(grid);
+        *stored = Some(grid.clone());
+        Ok(grid)
+    }
+}
+
+// no grid is left half stored, so a panic while a lock is held leaves consistent data
+fn recover<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        warn!("nebula grid lock poisoned, recovered");
+        poisoned.into_inner()
+    })
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, text: &str) -> Response {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: text.as_bytes().to_vec(),
+        }
+    }
+}
+
+/**
+ * /tiles/{fractal}/{z}/{x}/{y}.png
+ */
+fn parse_tile_path(path: &str) -> Option<(&str, u32, u64, u64)> {
+    let parts: Vec<&str> = path.strip_prefix("/tiles/")?.split('/').collect();
+    match parts[..] {
+        [fractal, z, x, y] => Some((
+            fractal,
+            z.parse().ok()?,
+            x.parse().ok()?,
+            y.strip_suffix(".png")?.parse().ok()?,
+        )),
+        _ => None,
+    }
+}
+
+// tiles of zoom z cover tile 0/0/0 by 2^z x 2^z tiles
+fn in_range(z: u32, x: u64, y: u64) -> bool {
+    z <= TILE_ZOOM_MAX && x < 1 << z && y < 1 << z
+}
+
+/**
+ * Area of the tile within the default area of the formula
+ * returns (width_re, center_re, center_im)
+ */
+fn tile_area(config: &FractalConfig, z: u32, x: u64, y: u64) -> (f64, f64, f64) {
+    let width_re = config.width_re / (1u64 << z) as f64;
+    (
+        width_re,
+        config.center_re - config.width_re / 2.0 + (x as f64 + 0.5) * width_re,
+        config.center_im - config.width_re / 2.0 + (y as f64 + 0.5) * width_re,
+    )
+}
+
+fn render_tile(formula: &Formula, z: u32, x: u64, y: u64) -> Result<RgbImage> {
+    let mut config = formula.config.clone();
+    let (width_re, center_re, center_im) = tile_area(&config, z, x, y);
+    config.width_x = TILE_SIZE;
+    config.height_y = TILE_SIZE;
+    config.width_re = width_re;
+    config.center_re = center_re;
+    config.center_im = center_im;
+    config.fractal_calc_type = StaticImageMandelbrot;
+    config.mandelbrot_rendering = Shuffled;
+    config.resolution_multiplier = Single;
+    // the render colours its own image, which isn't used
+    if config.palette_zero == Nothing {
+        config.palette_zero = GrayToBlackCircleDown;
+    }
+    headless(&mut config);
+    let render = formula.render(&config, None)?;
+    colour_tile(
+        &render.values,
+        config.iteration_max,
+        &palettes::new_palette_by_name(&config.palette),
+    )
+}
+
+/**
+ * Grid of a Nebula formula at a bounded cost, one calculation per pixel and capped iterations
+ * Minimal iterations shrink with the maximum, or long orbits of the formula would leave the grid empty.
+ */
+fn nebula_grid_config(formula_config: &FractalConfig, width: usize) -> FractalConfig {
+    let mut config = formula_config.clone();
+    config.width_x = width;
+    config.height_y = width;
+    config.fractal_calc_type = StaticImageNebula;
+    config.resolution_multiplier = Single;
+    if config.iteration_max > NEBULA_TILE_GRID_ITERATION_MAX {
+        config.iteration_min = (config.iteration_min as u64 * NEBULA_TILE_GRID_ITERATION_MAX as u64
+            / config.iteration_max as u64) as u32;
+        config.iteration_max = NEBULA_TILE_GRID_ITERATION_MAX;
+    }
+    headless(&mut config);
+    config
+}
+
+// no zoom, no files besides the tiles
+fn headless(config: &mut FractalConfig) {
+    config.stats_file = None;
+    config.diagnostics = false;
+}
+
+/**
+ * Colour of a value is the same in all tiles, unlike perfect colouring of a whole image
+ * Values are spread over the palette logarithmically, zero values are black.
+ */
+fn colour_tile(values: &ValueGrid, iteration_max: u32, palette: &Palette) -> Result<RgbImage> {
+    if palette.spectrum.is_empty() {
+        return Err(Config("palette has no colours".to_string()));
+    }
+    let last = palette.spectrum.len() - 1;
+    let scale = (iteration_max.max(2) as f64).ln();
+    Ok(RgbImage::from_fn(
+        values.width_x as u32,
+        values.height_y as u32,
+        |x, y| match values.value_at(x as usize, y as usize) {
+            0 => Rgb([0, 0, 0]),
+            value => {
+                let index = ((value as f64).ln() / scale * last as f64) as usize;
+                palette.spectrum_value(index.min(last))
+            }
+        },
+    ))
+}
+
+/**
+ * Tile from the grid of tile 0/0/0, nearest grid pixel for each tile pixel
+ */
+fn cut_tile(grid: &RgbImage, z: u32, x: u64, y: u64) -> RgbImage {
+    let tiles = (1u64 << z) as f64;
+    let scale = grid.width() as f64 / (tiles * TILE_SIZE as f64);
+    let grid_at = |tile: u64, px: u32| {
+        let at = ((tile as f64 * TILE_SIZE as f64 + px as f64 + 0.5) * scale) as u32;
+        at.min(grid.width() - 1)
+    };
+    RgbImage::from_fn(TILE_SIZE as u32, TILE_SIZE as u32, |px, py| {
+        *grid.get_pixel(grid_at(x, px), grid_at(y, py))
+    })
+}
+
+fn encode_png(image: &RgbImage) -> Result<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(io::Error::other)?;
+    Ok(png.into_inner())
+}
+
+// written under temporary name first, so that concurrent requests never read half of the file
+fn write_file(file: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    static TEMPORARY: AtomicUsize = AtomicUsize::new(0);
+    let temporary = file.with_extension(format!(
+        "{}.{}.tmp",
+        process::id(),
+        TEMPORARY.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temporary, data)?;
+    fs::rename(&temporary, file)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config_builder;
+    use crate::config_builder::Zoom::Width;
+    use crate::formulas;
+    use crate::formulas::formula;
+    use crate::fractal::FractalCalculationType::{StaticImageMandelbrot, StaticImageNebula};
+    use crate::fractal::{init_trivial_fractal, FractalMath};
+    use crate::mem::Mem;
+    use crate::palettes::PaletteName::Nothing;
+    use crate::resolution_multiplier::ResolutionMultiplier::{Single, Square5};
+    use crate::tile_server::{bind, init, nebula_grid_config, parse_tile_path, tile_area};
+    use std::io;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::{fs, thread};
+
+    #[test]
+    fn test_parse_tile_path() {
+        assert_eq!(
+            parse_tile_path("/tiles/nebula/3/2/7.png"),
+            Some(("nebula", 3, 2, 7))
+        );
+        assert_eq!(parse_tile_path("/tiles/nebula/3/2/7.jpg"), None);
+        assert_eq!(parse_tile_path("/tiles/nebula/3/2.png"), None);
+        assert_eq!(parse_tile_path("/nebula/3/2/7.png"), None);
+    }
+
+    #[test]
+    fn test_tile_area() {
+        let config = config_builder::init("Tile", StaticImageMandelbrot)
+            .zoom(Width(4.0))
+            .center(-0.5, 0.0)
+            .build();
+        assert_eq!(tile_area(&config, 0, 0, 0), (4.0, -0.5, 0.0));
+        assert_eq!(tile_area(&config, 1, 0, 1), (2.0, -1.5, 1.0));
+        assert_eq!(tile_area(&config, 2, 3, 0), (1.0, 1.0, -1.5));
+    }
+
+    struct PanickingFractal {}
+
+    impl FractalMath<Mem> for PanickingFractal {
+        fn math(&self, _: &mut Mem, _: f64, _: f64) {
+            panic!("math failed");
+        }
+    }
+
+    #[test]
+    fn test_nebula_grid_panic() {
+        let nebula = config_builder::init("Nebula", StaticImageNebula)
+            .iterations(4, 100)
+            .zoom(Width(4.0))
+            .build();
+        let mut registry = formulas::init();
+        registry.register(formula("panicking", nebula.clone(), PanickingFractal {}));
+        registry.register(formula("nebula", nebula, init_trivial_fractal()));
+
+        let cache_dir = std::env::temp_dir().join("rusty_fractals_test_tiles_panic");
+        let _ = fs::remove_dir_all(&cache_dir);
+        let mut server = init(registry, &cache_dir);
+        server.nebula_grid_width = 16;
+        let server = Arc::new(server);
+        let tile = |fractal: &'static str| {
+            let server = server.clone();
+            thread::spawn(move || server.tile(fractal, 0, 0, 0).is_ok()).join()
+        };
+
+        // neither the panicked grid nor the other formulas are locked out
+        assert!(tile("panicking").is_err());
+        assert!(tile("panicking").is_err());
+        assert!(tile("nebula").unwrap());
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_nebula_grid_config() {
+        let nebula = config_builder::init("Nebula", StaticImageNebula)
+            .iterations(3000, 180_000)
+            .resolution_multiplier(Square5)
+            .build();
+        let config = nebula_grid_config(&nebula, 64);
+        assert_eq!((config.width_x, config.height_y), (64, 64));
+        assert_eq!(config.resolution_multiplier, Single);
+        assert_eq!((config.iteration_min, config.iteration_max), (333, 20_000));
+
+        let nebula = config_builder::init("Nebula", StaticImageNebula)
+            .iterations(4, 100)
+            .build();
+        let config = nebula_grid_config(&nebula, 64);
+        assert_eq!((config.iteration_min, config.iteration_max), (4, 100));
+    }
+
+    #[test]
+    fn test_respond_idle() {
+        let cache_dir = std::env::temp_dir().join("rusty_fractals_test_tiles_idle");
+        let mut server = init(formulas::init(), &cache_dir);
+        server.read_timeout = Duration::from_millis(50);
+        let listener = bind(0).unwrap();
+        let _idle = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        // the client sends nothing, the connection is dropped
+        let error = server.respond(stream).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ));
+    }
+
+    fn get(address: SocketAddr, path: &str) -> (String, Vec<u8>) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let end = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8_lossy(&response[..end]).to_string();
+        let status = head.lines().next().unwrap().to_string();
+        (status, response[end + 4..].to_vec())
+    }
+
+    #[test]
+    fn test_serve() {
+        let mandelbrot = config_builder::init("Mandelbrot", StaticImageMandelbrot)
+            .iterations(0, 100)
+            .zoom(Width(4.0))
+            .build();
+        let nebula = config_builder::init("Nebula", StaticImageNebula)
+            .iterations(4, 100)
+            .zoom(Width(4.0))
+            .build();
+        let mut registry = formulas::init();
+        registry.register(formula("mandelbrot", mandelbrot, init_trivial_fractal()));
+        registry.register(formula("nebula", nebula, init_trivial_fractal()));
+        // config error of an existing tile
+        let colourless = config_builder::init("Colourless", StaticImageMandelbrot)
+            .iterations(0, 100)
+            .palette(Nothing)
+            .build();
+        registry.register(formula("colourless", colourless, init_trivial_fractal()));
+
+        let cache_dir = std::env::temp_dir().join("rusty_fractals_test_tiles");
+        let _ = fs::remove_dir_all(&cache_dir);
+        let mut server = init(registry, &cache_dir);
+        server.nebula_grid_width = 64;
+        let listener = bind(0).unwrap();
+        let address = listener.local_addr().unwrap();
+        assert!(address.ip().is_loopback());
+        let server = Arc::new(server);
+        thread::spawn(move || server.serve(listener));
+
+        let (status, body) = get(address, "/");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body, b"mandelbrot\nnebula\ncolourless");
+
+        for path in ["/tiles/mandelbrot/1/0/1.png", "/tiles/nebula/2/3/1.png"] {
+            let (status, png) = get(address, path);
+            assert_eq!(status, "HTTP/1.1 200 OK");
+            let tile = image::load_from_memory(&png).unwrap();
+            assert_eq!((tile.width(), tile.height()), (256, 256));
+            // cached
+            let file = cache_dir.join(&path["/tiles/".len()..]);
+            assert_eq!(fs::read(file).unwrap(), png);
+            assert_eq!(get(address, path).1, png);
+        }
+        assert!(cache_dir.join("nebula/grid.png").exists());
+
+        let (status, _) = get(address, "/tiles/mandelbrot/1/2/0.png");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+        let (status, _) = get(address, "/tiles/nebula_x/0/0/0.png");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+        let (status, body) = get(address, "/tiles/colourless/0/0/0.png");
+        assert_eq!(status, "HTTP/1.1 500 Internal Server Error");
+        assert_eq!(body, b"config: palette has no colours");
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,673 @@
+use crate::area;
+use crate::constants::{
+    CALCULATION_BOUNDARY, COLOURING_THRESHOLD, TILED_QUAD_STEPS, TILED_SPILL_BUFFER,
+};
+use crate::dzi;
+use crate::error::FractalError::Config;
+use crate::error::Result;
+use crate::fractal::FractalCalculationType::StaticSpectralImageEuler;
+use crate::fractal::{FractalConfig, FractalMath, MemType};
+use crate::machine;
+use crate::palette::Palette;
+use crate::palettes::new_palette_by_name;
+use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use crate::{info, warn};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/**
+ * Rectangle of the image, in pixels
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width_x: usize,
+    pub height_y: usize,
+}
+
+// values of a tile row by row, and for Mandelbrot like fractals quad steps of the pixels inside of the set
+struct TileValues {
+    values: Vec<u32>,
+    quad_steps: Vec<u32>,
+}
+
+/**
+ * Render of image larger than memory, e.g. 20000 x 20000 px print, to .png file or .dzi Deep Zoom pyramid
+ * Each tile has its own Machine, so that only one tile is held in DataImage.
+ * The first pass calculates the tiles, keeps their values on disk and gathers histogram of all values.
+ * The second pass perfectly colours the values by the global histogram and streams them to the image row by row.
+ * Deep Zoom pyramid keeps only the rows of its current row of tiles for each level, see dzi::Pyramid.
+ * Mandelbrot tiles are independent. Nebula paths go across tiles, so origins of the whole image are iterated once
+ * and the path elements are spilled to a file of the tile they fall into, see spill_paths.
+ * Tiled image calculates only the centers of pixels, Nebula with resolution multiplier other than Single
+ * is sparser than its untiled render, which also calculates the wrap of each pixel.
+ */
+pub fn render<F, M>(
+    config: &FractalConfig,
+    fractal: F,
+    tile_size: usize,
+    image: &Path,
+) -> Result<()>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    if tile_size == 0 {
+        return Err(Config("tile size must be positive".to_string()));
+    }
+    if image.extension().is_none_or(|e| e != "png" && e != "dzi") {
+        return Err(Config(
+            "tiled image is saved only as .png or .dzi".to_string(),
+        ));
+    }
+    if config.fractal_calc_type == StaticSpectralImageEuler {
+        return Err(Config(
+            "StaticSpectralImageEuler is not calculated by Machine".to_string(),
+        ));
+    }
+    let palette = new_palette_by_name(&config.palette);
+    let palette_zero = new_palette_by_name(&config.palette_zero);
+    if palette.spectrum.is_empty() {
+        return Err(Config("palette has no colours".to_string()));
+    }
+    if !config.is_mandelbrot() && config.resolution_multiplier != Single {
+        warn!(
+            "render() resolution multiplier {:?} is ignored, only centers of pixels are calculated",
+            config.resolution_multiplier
+        );
+    }
+    let tiles = tiles(config.width_x, config.height_y, tile_size);
+    info!(
+        "render() {} {} x {}, {} tiles",
+        image.display(),
+        config.width_x,
+        config.height_y,
+        tiles.len()
+    );
+    if let Some(dir) = image.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // spill files left by a failed render would add to the new paths
+    let values_dir = values_dir(image);
+    if values_dir.exists() {
+        fs::remove_dir_all(&values_dir)?;
+    }
+    fs::create_dir_all(&values_dir)?;
+    let result = render_passes(
+        config,
+        fractal,
+        &tiles,
+        tile_size,
+        (&palette, &palette_zero),
+        &values_dir,
+        image,
+    );
+    let removed = fs::remove_dir_all(&values_dir);
+    result?;
+    Ok(removed?)
+}
+
+// values of the tiles are kept in values_dir between the passes
+fn render_passes<F, M>(
+    config: &FractalConfig,
+    fractal: F,
+    tiles: &[Tile],
+    tile_size: usize,
+    (palette, palette_zero): (&Palette, &Palette),
+    values_dir: &Path,
+    image: &Path,
+) -> Result<()>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    // first pass
+    let fractal = Arc::new(fractal);
+    if !config.is_mandelbrot() {
+        spill_paths(
+            config,
+            &fractal,
+            tiles,
+            tile_size,
+            values_dir,
+            TILED_SPILL_BUFFER,
+        )?;
+    }
+    let mut histogram = BTreeMap::new();
+    let mut histogram_zero = BTreeMap::new();
+    for (i, tile) in tiles.iter().enumerate() {
+        info!("tile {} of {}", i + 1, tiles.len());
+        let tile_values = if config.is_mandelbrot() {
+            calculate_tile(config, &fractal, tile)?
+        } else {
+            count_spilled(values_dir, tile)?
+        };
+        for (&value, &quad_step) in tile_values.values.iter().zip(&tile_values.quad_steps) {
+            if config.is_mandelbrot() && value == 0 {
+                *histogram_zero.entry(quad_step).or_insert(0u64) += 1;
+            } else {
+                *histogram.entry(value).or_insert(0u64) += 1;
+            }
+        }
+        save_tile(values_dir, tile, &tile_values)?;
+    }
+    if !histogram_zero.is_empty() && palette_zero.spectrum.is_empty() {
+        return Err(Config("palette_zero has no colours".to_string()));
+    }
+
+    // second pass
+    let noise = if config.is_mandelbrot() {
+        0
+    } else {
+        histogram
+            .range(..=COLOURING_THRESHOLD)
+            .map(|(_, count)| count)
+            .sum()
+    };
+    let mut colour_ranks = ranks(&histogram, palette.spectrum.len(), noise);
+    let mut zero_ranks = ranks(&histogram_zero, palette_zero.spectrum.len().max(1), 0);
+    let mut colour = |value: u32, quad_step: u32| {
+        if config.is_mandelbrot() && value == 0 {
+            palette_zero.spectrum_value(zero_ranks.colour_index(quad_step))
+        } else {
+            palette.spectrum_value(colour_ranks.colour_index(value))
+        }
+    };
+    let mut rows = image_rows(image, config.width_x, config.height_y)?;
+    let mut row = Vec::with_capacity(config.width_x * 3);
+    for tiles_row in tiles.chunk_by(|a, b| a.y == b.y) {
+        let tiles_values = tiles_row
+            .iter()
+            .map(|tile| load_tile(values_dir, tile))
+            .collect::<Result<Vec<_>>>()?;
+        for y in 0..tiles_row[0].height_y {
+            row.clear();
+            for (tile, tile_values) in tiles_row.iter().zip(&tiles_values) {
+                for i in y * tile.width_x..(y + 1) * tile.width_x {
+                    let rgb = colour(tile_values.values[i], tile_values.quad_steps[i]);
+                    row.extend_from_slice(&rgb.0);
+                }
+            }
+            rows.row(&row)?;
+        }
+    }
+    rows.finish()
+}
+
+/**
+ * Tiles of the image, row by row
+ * Tiles in the last column and the last row are smaller, if image size isn't divisible by tile size.
+ */
+pub fn tiles(width_x: usize, height_y: usize, tile_size: usize) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for y in (0..height_y).step_by(tile_size) {
+        for x in (0..width_x).step_by(tile_size) {
+            tiles.push(Tile {
+                x,
+                y,
+                width_x: tile_size.min(width_x - x),
+                height_y: tile_size.min(height_y - y),
+            });
+        }
+    }
+    tiles
+}
+
+/**
+ * Config of the tile, its area is the part of the image area
+ */
+pub fn tile_config(config: &FractalConfig, tile: &Tile) -> FractalConfig {
+    let plank = config.width_re / config.width_x as f64;
+    let height_im = config.width_re * (config.height_y as f64 / config.width_x as f64);
+    let mut tile_config = config.clone();
+    tile_config.width_x = tile.width_x;
+    tile_config.height_y = tile.height_y;
+    tile_config.width_re = plank * tile.width_x as f64;
+    tile_config.center_re = config.center_re - config.width_re / 2.0
+        + plank * (tile.x as f64 + tile.width_x as f64 / 2.0);
+    tile_config.center_im =
+        config.center_im - height_im / 2.0 + plank * (tile.y as f64 + tile.height_y as f64 / 2.0);
+    // statistics and diagnostics are of the whole image only
+    tile_config.stats_file = None;
+    tile_config.diagnostics = false;
+    tile_config
+}
+
+// Mandelbrot tile calculated by its own Machine, with the same origins as without tiles
+fn calculate_tile<F, M>(config: &FractalConfig, fractal: &Arc<F>, tile: &Tile) -> Result<TileValues>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    let image_area = area::init(config);
+    let numbers_re = image_area.screen_to_domain_re_copy();
+    let numbers_im = image_area.screen_to_domain_im_copy();
+    let machine = machine::init(&tile_config(config, tile), fractal.clone());
+    let data_image = &machine.data_image;
+    for y in 0..tile.height_y {
+        for x in 0..tile.width_x {
+            let mut mo_px = data_image.mo_px_at(x, y);
+            let p = mo_px.as_mut().unwrap();
+            p.origin_re = numbers_re[tile.x + x];
+            p.origin_im = numbers_im[tile.y + y];
+        }
+    }
+    machine.calculate_mandelbrot()?;
+    let mut tile_values = TileValues {
+        values: Vec::with_capacity(tile.width_x * tile.height_y),
+        quad_steps: Vec::with_capacity(tile.width_x * tile.height_y),
+    };
+    for y in 0..tile.height_y {
+        for x in 0..tile.width_x {
+            let (value, _, quad, _, _) = data_image.values5_at(x, y);
+            tile_values.values.push(value);
+            tile_values.quad_steps.push(quad_step(quad));
+        }
+    }
+    Ok(tile_values)
+}
+
+/**
+ * Iterates origins of the whole image once, each row of origins in parallel
+ * Elements of good paths are appended, as pixel index within the tile, to {x}_{y}.paths file of their tile.
+ * Each tile buffers this many elements in memory before it writes them, see TILED_SPILL_BUFFER.
+ */
+fn spill_paths<F, M>(
+    config: &FractalConfig,
+    fractal: &Arc<F>,
+    tiles: &[Tile],
+    tile_size: usize,
+    dir: &Path,
+    buffer_size: usize,
+) -> Result<()>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    let image_area = area::init(config);
+    let numbers_re = image_area.screen_to_domain_re_copy();
+    let numbers_im = image_area.screen_to_domain_im_copy();
+    let area = image_area.copy_data();
+    // calculates paths only, data image of the tile isn't used
+    let machine = machine::init(
+        &tile_config(config, &tiles[tiles.len() - 1]),
+        fractal.clone(),
+    );
+    let tiles_x = config.width_x.div_ceil(tile_size);
+    let buffers: Vec<Mutex<Vec<u32>>> = tiles.iter().map(|_| Mutex::new(Vec::new())).collect();
+
+    numbers_im
+        .par_iter()
+        .try_for_each(|&origin_im| -> io::Result<()> {
+            let mut row: HashMap<usize, Vec<u32>> = HashMap::new();
+            for &origin_re in &numbers_re {
+                let (iterator, length) = machine.path_length(&area, origin_re, origin_im);
+                if !machine.path_test(length, iterator) {
+                    continue;
+                }
+                for [re, im] in machine.path(&area, origin_re, origin_im, iterator) {
+                    let (x, y) = area.point_to_pixel(re, im);
+                    let t = (y / tile_size) * tiles_x + x / tile_size;
+                    let tile = &tiles[t];
+                    let i = (y - tile.y) * tile.width_x + x - tile.x;
+                    row.entry(t).or_default().push(i as u32);
+                }
+            }
+            for (t, elements) in row {
+                let mut buffer = buffers[t].lock().unwrap();
+                buffer.extend(elements);
+                if buffer.len() >= buffer_size {
+                    append_spilled(dir, &tiles[t], &buffer)?;
+                    buffer.clear();
+                }
+            }
+            Ok(())
+        })?;
+    for (tile, buffer) in tiles.iter().zip(buffers) {
+        append_spilled(dir, tile, &buffer.into_inner().unwrap())?;
+    }
+    Ok(())
+}
+
+fn spill_file(dir: &Path, tile: &Tile) -> PathBuf {
+    dir.join(format!("{}_{}.paths", tile.x, tile.y))
+}
+
+fn append_spilled(dir: &Path, tile: &Tile, elements: &[u32]) -> io::Result<()> {
+    if elements.is_empty() {
+        return Ok(());
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(spill_file(dir, tile))?;
+    let mut writer = BufWriter::new(file);
+    for element in elements {
+        writer.write_all(&element.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+// values of Nebula tile are the amounts of its spilled path elements, the spill file is removed
+fn count_spilled(dir: &Path, tile: &Tile) -> Result<TileValues> {
+    let pixels = tile.width_x * tile.height_y;
+    let mut values = vec![0u32; pixels];
+    let file = spill_file(dir, tile);
+    if file.exists() {
+        let mut reader = BufReader::new(File::open(&file)?);
+        let mut bytes = [0u8; 4];
+        loop {
+            match reader.read_exact(&mut bytes) {
+                Ok(()) => values[u32::from_le_bytes(bytes) as usize] += 1,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        fs::remove_file(&file)?;
+    }
+    Ok(TileValues {
+        values,
+        quad_steps: vec![0; pixels],
+    })
+}
+
+// quad of pixels inside of Mandelbrot set is less than CALCULATION_BOUNDARY
+fn quad_step(quad: f64) -> u32 {
+    let step = quad / CALCULATION_BOUNDARY as f64 * TILED_QUAD_STEPS as f64;
+    (step as u32).min(TILED_QUAD_STEPS - 1)
+}
+
+/**
+ * Perfect colouring of values coming in the image order, see perfect_colour_distribution
+ * Histogram of the whole image tells the rank of the first pixel of each value,
+ * pixels of the same value are ranked in the image order, as by the stable sort of perfect colouring.
+ */
+struct Ranks {
+    // rank of the next pixel of the value
+    next: HashMap<u32, u64>,
+    // the lowest ranks, noise and pixels which don't divide between colours evenly, have the first colour
+    dark: u64,
+    // pixels of each colour
+    single_colour_use: u64,
+}
+
+impl Ranks {
+    fn colour_index(&mut self, value: u32) -> usize {
+        let next = self
+            .next
+            .get_mut(&value)
+            .expect("could not find value in histogram");
+        let rank = *next;
+        *next += 1;
+        if rank < self.dark {
+            0
+        } else {
+            ((rank - self.dark) / self.single_colour_use) as usize
+        }
+    }
+}
+
+fn ranks(histogram: &BTreeMap<u32, u64>, colours: usize, noise: u64) -> Ranks {
+    let pixels: u64 = histogram.values().sum();
+    let single_colour_use = (pixels - noise) / colours as u64;
+    let left = pixels - noise - single_colour_use * colours as u64;
+    let mut next = HashMap::new();
+    let mut rank = 0;
+    for (&value, &count) in histogram {
+        next.insert(value, rank);
+        rank += count;
+    }
+    Ranks {
+        next,
+        dark: noise + left,
+        single_colour_use,
+    }
+}
+
+// image.png -> image_values
+fn values_dir(image: &Path) -> PathBuf {
+    let name = image.file_stem().unwrap_or_default().to_string_lossy();
+    image.with_file_name(format!("{}_values", name))
+}
+
+fn tile_file(dir: &Path, tile: &Tile) -> PathBuf {
+    dir.join(format!("{}_{}.values", tile.x, tile.y))
+}
+
+fn save_tile(dir: &Path, tile: &Tile, tile_values: &TileValues) -> Result<()> {
+    let bytes: Vec<u8> = tile_values
+        .values
+        .iter()
+        .chain(&tile_values.quad_steps)
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    fs::write(tile_file(dir, tile), bytes)?;
+    Ok(())
+}
+
+fn load_tile(dir: &Path, tile: &Tile) -> Result<TileValues> {
+    let bytes = fs::read(tile_file(dir, tile))?;
+    let mut numbers: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    let quad_steps = numbers.split_off(tile.width_x * tile.height_y);
+    Ok(TileValues {
+        values: numbers,
+        quad_steps,
+    })
+}
+
+// rows of the image are written as they are coloured
+enum ImageRows {
+    Png(Box<png::StreamWriter<'static, BufWriter<File>>>),
+    Dzi(dzi::Pyramid),
+}
+
+impl ImageRows {
+    fn row(&mut self, row: &[u8]) -> Result<()> {
+        match self {
+            ImageRows::Png(stream) => stream.write_all(row)?,
+            ImageRows::Dzi(pyramid) => pyramid.row(row)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            ImageRows::Png(stream) => stream.finish().map_err(io::Error::other)?,
+            ImageRows::Dzi(pyramid) => pyramid.finish()?,
+        }
+        Ok(())
+    }
+}
+
+fn image_rows(image: &Path, width_x: usize, height_y: usize) -> Result<ImageRows> {
+    if image.extension().is_some_and(|e| e == "dzi") {
+        let pyramid = dzi::init(image, width_x as u32, height_y as u32)?;
+        return Ok(ImageRows::Dzi(pyramid));
+    }
+    Ok(ImageRows::Png(Box::new(png_stream(
+        image, width_x, height_y,
+    )?)))
+}
+
+fn png_stream(
+    image: &Path,
+    width_x: usize,
+    height_y: usize,
+) -> Result<png::StreamWriter<'static, BufWriter<File>>> {
+    let file = BufWriter::new(File::create(image)?);
+    let mut encoder = png::Encoder::new(file, width_x as u32, height_y as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let stream = encoder
+        .write_header()
+        .and_then(|writer| writer.into_stream_writer())
+        .map_err(io::Error::other)?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dzi;
+    use crate::fractal::FractalCalculationType::StaticImageNebula;
+    use crate::fractal::{init_trivial_fractal, init_trivial_static_config, FractalConfig};
+    use crate::palettes::new_palette_by_name;
+    use crate::palettes::PaletteName::{BlueToWhiteCircleUp, GrayToBlackCircleDown, Nothing};
+    use crate::render;
+    use crate::tiled::{calculate_tile, count_spilled, render, spill_paths, tiles, Tile};
+    use std::fs;
+    use std::sync::Arc;
+
+    fn config() -> FractalConfig {
+        let mut conf = init_trivial_static_config();
+        conf.width_x = 23;
+        conf.height_y = 17;
+        conf.width_re = 3.0;
+        conf.center_re = -0.5;
+        conf.iteration_max = 100;
+        conf.palette = BlueToWhiteCircleUp;
+        conf.palette_zero = GrayToBlackCircleDown;
+        conf
+    }
+
+    #[test]
+    fn test_tiles() {
+        let result = tiles(23, 17, 10);
+        assert_eq!(result.len(), 3 * 2);
+        assert_eq!(
+            result[2],
+            Tile {
+                x: 20,
+                y: 0,
+                width_x: 3,
+                height_y: 10
+            }
+        );
+        assert_eq!(result[5].y, 10);
+        assert_eq!(result[5].height_y, 7);
+        assert_eq!(tiles(4, 4, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_calculate_tile_mandelbrot() {
+        let conf = config();
+        let expected = render::render(&conf, init_trivial_fractal()).unwrap();
+
+        let fractal = Arc::new(init_trivial_fractal());
+        for tile in tiles(conf.width_x, conf.height_y, 7) {
+            let tile_values = calculate_tile(&conf, &fractal, &tile).unwrap();
+            for y in 0..tile.height_y {
+                for x in 0..tile.width_x {
+                    assert_eq!(
+                        tile_values.values[y * tile.width_x + x],
+                        expected.values.value_at(tile.x + x, tile.y + y)
+                    );
+                }
+            }
+        }
+
+        let dir = std::env::temp_dir().join("rusty_fractals_test_tiled_mandelbrot");
+        let _ = fs::remove_dir_all(&dir);
+        render(
+            &conf,
+            init_trivial_fractal(),
+            7,
+            &dir.join("mandelbrot.png"),
+        )
+        .unwrap();
+        let result = image::open(dir.join("mandelbrot.png")).unwrap().into_rgb8();
+        assert_eq!(result.dimensions(), (23, 17));
+        // inside of the set is coloured by palette_zero
+        let palette_zero = new_palette_by_name(&conf.palette_zero);
+        for (x, y, colour) in result.enumerate_pixels() {
+            if expected.values.value_at(x as usize, y as usize) == 0 {
+                assert!(palette_zero.spectrum.contains(colour));
+            }
+        }
+
+        // values of failed render are removed
+        let mut conf = conf;
+        conf.palette_zero = Nothing;
+        let failed = dir.join("failed.png");
+        assert!(render(&conf, init_trivial_fractal(), 7, &failed).is_err());
+        assert!(!dir.join("failed_values").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_nebula() {
+        let mut conf = config();
+        conf.fractal_calc_type = StaticImageNebula;
+        conf.iteration_min = 3;
+        let expected = render::render(&conf, init_trivial_fractal()).unwrap();
+        assert!(expected.values.values.iter().any(|v| *v > 0));
+
+        let dir = std::env::temp_dir().join("rusty_fractals_test_tiled");
+        let _ = fs::remove_dir_all(&dir);
+        let image = dir.join("nebula.png");
+        // spilled elements of a failed render
+        fs::create_dir_all(dir.join("nebula_values")).unwrap();
+        fs::write(dir.join("nebula_values/0_0.paths"), [0u8; 400]).unwrap();
+        render(&conf, init_trivial_fractal(), 7, &image).unwrap();
+
+        // the same perfect colouring as without tiles
+        let result = image::open(&image).unwrap().into_rgb8();
+        assert_eq!(result, expected.image);
+        assert!(!dir.join("nebula_values").exists());
+        assert!(render(&conf, init_trivial_fractal(), 7, &dir.join("nebula.jpg")).is_err());
+
+        // the same Deep Zoom pyramid as exported from the whole image
+        render(&conf, init_trivial_fractal(), 7, &dir.join("nebula.dzi")).unwrap();
+        dzi::export(&expected.image, &dir.join("expected.dzi")).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("nebula.dzi")).unwrap(),
+            fs::read_to_string(dir.join("expected.dzi")).unwrap()
+        );
+        for level in 0..=dzi::max_level(23, 17) {
+            let tile = format!("{}/0_0.png", level);
+            let result = image::open(dir.join("nebula_files").join(&tile)).unwrap();
+            let expected = image::open(dir.join("expected_files").join(&tile)).unwrap();
+            assert_eq!(result, expected);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_spill_paths() {
+        let mut conf = config();
+        conf.fractal_calc_type = StaticImageNebula;
+        conf.iteration_min = 3;
+        let expected = render::render(&conf, init_trivial_fractal()).unwrap();
+
+        let dir = std::env::temp_dir().join("rusty_fractals_test_spill_paths");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let tiles = tiles(conf.width_x, conf.height_y, 7);
+        let fractal = Arc::new(init_trivial_fractal());
+        // each row of origins appends to the spill files
+        spill_paths(&conf, &fractal, &tiles, 7, &dir, 1).unwrap();
+
+        for tile in &tiles {
+            let tile_values = count_spilled(&dir, tile).unwrap();
+            for y in 0..tile.height_y {
+                for x in 0..tile.width_x {
+                    assert_eq!(
+                        tile_values.values[y * tile.width_x + x],
+                        expected.values.value_at(tile.x + x, tile.y + y)
+                    );
+                }
+            }
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+use crate::data_image::DataImage;
+
+/**
+ * Snapshot of pixel values of one finished frame
+ * Colouring and export of the frame work with the snapshot,
+ * while calculation of the next frame already changes the live DataImage.
+ */
+pub struct ValueGrid {
+    pub frame: u32,
+    pub width_x: usize,
+    pub height_y: usize,
+    // row by row
+    pub values: Vec<u32>,
+}
+
+impl ValueGrid {
+    pub fn value_at(&self, x: usize, y: usize) -> u32 {
+        self.values[y * self.width_x + x]
+    }
+}
+
+pub fn snapshot(data_image: &DataImage, frame: u32) -> ValueGrid {
+    let width_x = data_image.width_x;
+    let height_y = data_image.height_y;
+    ValueGrid {
+        frame,
+        width_x,
+        height_y,
+        values: (0..height_y)
+            .flat_map(|y| (0..width_x).map(move |x| data_image.value_at(x, y)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::data_image;
+    use crate::fractal::init_trivial_static_config;
+    use crate::value_grid::snapshot;
+
+    #[test]
+    fn test_snapshot() {
+        let conf = init_trivial_static_config();
+        let area = area::init(&conf);
+        let data_image = data_image::init(&conf, &area);
+        data_image.mo_px_at(3, 1).as_mut().unwrap().value = 7;
+
+        let grid = snapshot(&data_image, 2);
+        data_image.mo_px_at(3, 1).as_mut().unwrap().value = 8;
+
+        assert_eq!(grid.frame, 2);
+        assert_eq!(grid.values.len(), conf.width_x * conf.height_y);
+        assert_eq!(grid.value_at(3, 1), 7);
+        assert_eq!(grid.value_at(1, 3), 0);
+    }
+}
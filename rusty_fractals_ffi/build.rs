@@ -0,0 +1,30 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/**
+ * Generates rusty_fractals.h from the extern "C" functions of lib.rs into OUT_DIR
+ * tests/c_api.rs checks that include/rusty_fractals.h matches it.
+ */
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").expect("could not get crate dir");
+    let config = cbindgen::Config::from_file(Path::new(&crate_dir).join("cbindgen.toml"))
+        .expect("could not read cbindgen.toml");
+    let mut header = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("could not generate header")
+        .write(&mut header);
+    // doc comments are /** */ blocks, drop their leading asterisks from the C comments
+    let header = String::from_utf8(header)
+        .expect("could not read header")
+        .replace("\n * * ", "\n * ")
+        .replace("\n * *\n", "\n *\n");
+    let out_dir = env::var("OUT_DIR").expect("could not get out dir");
+    fs::write(Path::new(&out_dir).join("rusty_fractals.h"), header)
+        .expect("could not write header");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
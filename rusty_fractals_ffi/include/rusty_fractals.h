@@ -0,0 +1,190 @@
+#ifndef RUSTY_FRACTALS_H
+#define RUSTY_FRACTALS_H
+
+/* Generated by cbindgen from rusty_fractals_ffi/src/lib.rs, don't edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * C API of rusty_fractals
+ * The version changes with any incompatible change of the functions or structs below.
+ */
+#define RF_API_VERSION 1
+
+#define RF_OK 0
+
+/**
+ * null render or formula name 
+ */
+#define RF_ERROR_NULL -1
+
+/**
+ * unknown formula, or parameter the calculation can't use 
+ */
+#define RF_ERROR_CONFIG -2
+
+/**
+ * calculation failed, see rf_last_error() 
+ */
+#define RF_ERROR_RENDER -3
+
+/**
+ * calculation panicked, see rf_last_error() 
+ */
+#define RF_ERROR_PANIC -4
+
+typedef enum RfStage {
+  RF_STAGE_MANDELBROT,
+  RF_STAGE_NEBULA,
+  RF_STAGE_WRAP,
+  RF_STAGE_TRANSLATE,
+  RF_STAGE_COLOUR,
+} RfStage;
+
+/**
+ * Render of one formula, created by rf_render_new() and released by rf_render_free()
+ */
+typedef struct RfRender RfRender;
+
+/**
+ * Progress of a running render, reported after each calculated chunk
+ */
+typedef struct RfProgress {
+  uint32_t frame;
+  enum RfStage stage;
+  size_t chunks_done;
+  size_t chunks_total;
+} RfProgress;
+
+/**
+ * Called from calculation threads, possibly from more threads at once
+ */
+typedef void (*RfProgressCallback)(const struct RfProgress *progress, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+uint32_t rf_api_version(void);
+
+/**
+ * Amount of registered formulas
+ */
+size_t rf_formula_count(void);
+
+/**
+ * Name of formula at index, null if index is out of range
+ * The name is valid until the program exits.
+ */
+const char *rf_formula_name(size_t index);
+
+/**
+ * Message of the last error of this thread, empty if there was none
+ * The message is valid until the next error of this thread.
+ */
+const char *rf_last_error(void);
+
+/**
+ * New render of the named formula with its default config, null if there is no such formula
+ *
+ * # Safety
+ * formula must be null or a nul terminated string
+ */
+struct RfRender *rf_render_new(const char *formula);
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+int32_t rf_render_set_size(struct RfRender *render, size_t width_x, size_t height_y);
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+int32_t rf_render_set_center(struct RfRender *render, double center_re, double center_im);
+
+/**
+ * Width of the rendered area in the complex plane, height follows from the image size
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+int32_t rf_render_set_width_re(struct RfRender *render, double width_re);
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+int32_t rf_render_set_iterations(struct RfRender *render,
+                                 uint32_t iteration_min,
+                                 uint32_t iteration_max);
+
+/**
+ * Calculates the render, blocks until it finishes
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+int32_t rf_render_run(struct RfRender *render);
+
+/**
+ * Calculates the render, blocks until it finishes
+ * callback may be null, user_data is passed to it unchanged.
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet,
+ * callback must be safe to call from more threads with user_data
+ */
+int32_t rf_render_run_with_progress(struct RfRender *render,
+                                    RfProgressCallback callback,
+                                    void *user_data);
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+size_t rf_render_width(const struct RfRender *render);
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+size_t rf_render_height(const struct RfRender *render);
+
+/**
+ * Rendered image, 3 bytes per pixel, row by row, null before successful rf_render_run()
+ * len is set to the amount of bytes. The buffer is valid until the next run or free of the render.
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet, len must be null or valid
+ */
+const uint8_t *rf_render_rgb(const struct RfRender *render,
+                             size_t *len);
+
+/**
+ * Pixel values the image was coloured from, one per pixel, row by row,
+ * null before successful rf_render_run()
+ * len is set to the amount of values. The buffer is valid until the next run or free of the render.
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet, len must be null or valid
+ */
+const uint32_t *rf_render_values(const struct RfRender *render,
+                                 size_t *len);
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+void rf_render_free(struct RfRender *render);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUSTY_FRACTALS_H */
@@ -0,0 +1,442 @@
+use rusty_fractals::error::FractalError;
+use rusty_fractals::formulas;
+use rusty_fractals::formulas::{Formula, Registry};
+use rusty_fractals::fractal::FractalConfig;
+use rusty_fractals::progress::{ProgressEvent, ProgressListener, Stage};
+use rusty_fractals::render::Render;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Arc, OnceLock};
+
+/**
+ * C API of rusty_fractals
+ * The version changes with any incompatible change of the functions or structs below.
+ */
+pub const RF_API_VERSION: u32 = 1;
+
+pub const RF_OK: i32 = 0;
+/** null render or formula name */
+pub const RF_ERROR_NULL: i32 = -1;
+/** unknown formula, or parameter the calculation can't use */
+pub const RF_ERROR_CONFIG: i32 = -2;
+/** calculation failed, see rf_last_error() */
+pub const RF_ERROR_RENDER: i32 = -3;
+/** calculation panicked, see rf_last_error() */
+pub const RF_ERROR_PANIC: i32 = -4;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RfStage {
+    Mandelbrot,
+    Nebula,
+    Wrap,
+    Translate,
+    Colour,
+}
+
+/**
+ * Progress of a running render, reported after each calculated chunk
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RfProgress {
+    pub frame: u32,
+    pub stage: RfStage,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+}
+
+/**
+ * Called from calculation threads, possibly from more threads at once
+ */
+pub type RfProgressCallback =
+    Option<extern "C" fn(progress: *const RfProgress, user_data: *mut c_void)>;
+
+/**
+ * Render of one formula, created by rf_render_new() and released by rf_render_free()
+ */
+pub struct RfRender {
+    formula: &'static Formula,
+    config: FractalConfig,
+    result: Option<Render>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(formulas::init_builtin)
+}
+
+fn formula_names() -> &'static [CString] {
+    static NAMES: OnceLock<Vec<CString>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        registry()
+            .names()
+            .into_iter()
+            .map(|name| CString::new(name).expect("could not convert formula name"))
+            .collect()
+    })
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).expect("could not convert error");
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+// user data is only passed back to the callback, the caller is responsible for its thread safety
+struct CallbackListener {
+    callback: extern "C" fn(*const RfProgress, *mut c_void),
+    user_data: *mut c_void,
+}
+
+unsafe impl Send for CallbackListener {}
+unsafe impl Sync for CallbackListener {}
+
+impl ProgressListener for CallbackListener {
+    fn event(&self, event: &ProgressEvent) {
+        if let ProgressEvent::ChunkDone {
+            frame,
+            stage,
+            chunks_done,
+            chunks_total,
+            ..
+        } = event
+        {
+            let progress = RfProgress {
+                frame: *frame,
+                stage: rf_stage(*stage),
+                chunks_done: *chunks_done,
+                chunks_total: *chunks_total,
+            };
+            (self.callback)(&progress, self.user_data);
+        }
+    }
+}
+
+fn rf_stage(stage: Stage) -> RfStage {
+    match stage {
+        Stage::Mandelbrot => RfStage::Mandelbrot,
+        Stage::Nebula => RfStage::Nebula,
+        Stage::Wrap => RfStage::Wrap,
+        Stage::Translate => RfStage::Translate,
+        Stage::Colour => RfStage::Colour,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rf_api_version() -> u32 {
+    RF_API_VERSION
+}
+
+/**
+ * Amount of registered formulas
+ */
+#[no_mangle]
+pub extern "C" fn rf_formula_count() -> usize {
+    formula_names().len()
+}
+
+/**
+ * Name of formula at index, null if index is out of range
+ * The name is valid until the program exits.
+ */
+#[no_mangle]
+pub extern "C" fn rf_formula_name(index: usize) -> *const c_char {
+    formula_names()
+        .get(index)
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/**
+ * Message of the last error of this thread, empty if there was none
+ * The message is valid until the next error of this thread.
+ */
+#[no_mangle]
+pub extern "C" fn rf_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/**
+ * New render of the named formula with its default config, null if there is no such formula
+ *
+ * # Safety
+ * formula must be null or a nul terminated string
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_new(formula: *const c_char) -> *mut RfRender {
+    if formula.is_null() {
+        set_last_error("formula name is null");
+        return ptr::null_mut();
+    }
+    let name = CStr::from_ptr(formula).to_string_lossy();
+    match registry().get(&name) {
+        Some(formula) => Box::into_raw(Box::new(RfRender {
+            formula,
+            config: formula.config.clone(),
+            result: None,
+        })),
+        None => {
+            set_last_error(&format!("unknown formula {}", name));
+            ptr::null_mut()
+        }
+    }
+}
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_set_size(
+    render: *mut RfRender,
+    width_x: usize,
+    height_y: usize,
+) -> i32 {
+    with_render(render, |render| {
+        if width_x == 0 || height_y == 0 {
+            return config_error("size must be positive");
+        }
+        render.config.width_x = width_x;
+        render.config.height_y = height_y;
+        RF_OK
+    })
+}
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_set_center(
+    render: *mut RfRender,
+    center_re: f64,
+    center_im: f64,
+) -> i32 {
+    with_render(render, |render| {
+        if !center_re.is_finite() || !center_im.is_finite() {
+            return config_error("center must be finite");
+        }
+        render.config.center_re = center_re;
+        render.config.center_im = center_im;
+        RF_OK
+    })
+}
+
+/**
+ * Width of the rendered area in the complex plane, height follows from the image size
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_set_width_re(render: *mut RfRender, width_re: f64) -> i32 {
+    with_render(render, |render| {
+        if !(width_re.is_finite() && width_re > 0.0) {
+            return config_error("width_re must be positive");
+        }
+        render.config.width_re = width_re;
+        RF_OK
+    })
+}
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_set_iterations(
+    render: *mut RfRender,
+    iteration_min: u32,
+    iteration_max: u32,
+) -> i32 {
+    with_render(render, |render| {
+        if iteration_min >= iteration_max {
+            return config_error("iteration_min must be less than iteration_max");
+        }
+        render.config.iteration_min = iteration_min;
+        render.config.iteration_max = iteration_max;
+        RF_OK
+    })
+}
+
+/**
+ * Calculates the render, blocks until it finishes
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_run(render: *mut RfRender) -> i32 {
+    rf_render_run_with_progress(render, None, ptr::null_mut())
+}
+
+/**
+ * Calculates the render, blocks until it finishes
+ * callback may be null, user_data is passed to it unchanged.
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet,
+ * callback must be safe to call from more threads with user_data
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_run_with_progress(
+    render: *mut RfRender,
+    callback: RfProgressCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    with_render(render, |render| {
+        render.result = None;
+        let listener = callback.map(|callback| {
+            Arc::new(CallbackListener {
+                callback,
+                user_data,
+            }) as Arc<dyn ProgressListener>
+        });
+        let formula = render.formula;
+        let config = &render.config;
+        match catch_unwind(AssertUnwindSafe(|| formula.render(config, listener))) {
+            Ok(Ok(result)) => {
+                render.result = Some(result);
+                RF_OK
+            }
+            Ok(Err(FractalError::Config(message))) => config_error(&message),
+            Ok(Err(e)) => {
+                set_last_error(&e.to_string());
+                RF_ERROR_RENDER
+            }
+            Err(_) => {
+                set_last_error("render panicked");
+                RF_ERROR_PANIC
+            }
+        }
+    })
+}
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_width(render: *const RfRender) -> usize {
+    render.as_ref().map_or(0, |render| render.config.width_x)
+}
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_height(render: *const RfRender) -> usize {
+    render.as_ref().map_or(0, |render| render.config.height_y)
+}
+
+/**
+ * Rendered image, 3 bytes per pixel, row by row, null before successful rf_render_run()
+ * len is set to the amount of bytes. The buffer is valid until the next run or free of the render.
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet, len must be null or valid
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_rgb(render: *const RfRender, len: *mut usize) -> *const u8 {
+    let image = render
+        .as_ref()
+        .and_then(|render| render.result.as_ref())
+        .map(|result| result.image.as_raw().as_slice());
+    buffer(image, len)
+}
+
+/**
+ * Pixel values the image was coloured from, one per pixel, row by row,
+ * null before successful rf_render_run()
+ * len is set to the amount of values. The buffer is valid until the next run or free of the render.
+ *
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet, len must be null or valid
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_values(render: *const RfRender, len: *mut usize) -> *const u32 {
+    let values = render
+        .as_ref()
+        .and_then(|render| render.result.as_ref())
+        .map(|result| result.values.values.as_slice());
+    buffer(values, len)
+}
+
+/**
+ * # Safety
+ * render must be null or returned by rf_render_new() and not freed yet
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rf_render_free(render: *mut RfRender) {
+    if !render.is_null() {
+        drop(Box::from_raw(render));
+    }
+}
+
+unsafe fn with_render(render: *mut RfRender, f: impl FnOnce(&mut RfRender) -> i32) -> i32 {
+    match render.as_mut() {
+        Some(render) => f(render),
+        None => {
+            set_last_error("render is null");
+            RF_ERROR_NULL
+        }
+    }
+}
+
+unsafe fn buffer<T>(data: Option<&[T]>, len: *mut usize) -> *const T {
+    if let Some(len) = len.as_mut() {
+        *len = data.map_or(0, |data| data.len());
+    }
+    data.map_or(ptr::null(), |data| data.as_ptr())
+}
+
+fn config_error(message: &str) -> i32 {
+    set_last_error(message);
+    RF_ERROR_CONFIG
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rf_formula_count, rf_formula_name, rf_last_error, rf_render_free, rf_render_new,
+        rf_render_rgb, rf_render_run, rf_render_set_iterations, rf_render_set_size,
+        rf_render_set_width_re, rf_render_values, RF_ERROR_CONFIG, RF_OK,
+    };
+    use std::ffi::CStr;
+    use std::ptr;
+
+    #[test]
+    fn test_render() {
+        unsafe {
+            assert!(rf_formula_count() > 0);
+            assert!(rf_formula_name(rf_formula_count()).is_null());
+            assert!(rf_render_new(c"nebula_x".as_ptr()).is_null());
+            assert_eq!(
+                CStr::from_ptr(rf_last_error()).to_str().unwrap(),
+                "unknown formula nebula_x"
+            );
+
+            let render = rf_render_new(c"mandelbrot".as_ptr());
+            assert!(!render.is_null());
+            assert_eq!(rf_render_set_iterations(render, 10, 10), RF_ERROR_CONFIG);
+            assert_eq!(rf_render_set_size(render, 40, 30), RF_OK);
+            assert_eq!(rf_render_set_width_re(render, 3.0), RF_OK);
+            assert_eq!(rf_render_set_iterations(render, 0, 100), RF_OK);
+            assert!(rf_render_rgb(render, ptr::null_mut()).is_null());
+
+            assert_eq!(rf_render_run(render), RF_OK);
+            let mut len = 0;
+            assert!(!rf_render_rgb(render, &mut len).is_null());
+            assert_eq!(len, 40 * 30 * 3);
+            assert!(!rf_render_values(render, &mut len).is_null());
+            assert_eq!(len, 40 * 30);
+            rf_render_free(render);
+        }
+    }
+}
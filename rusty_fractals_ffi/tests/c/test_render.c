@@ -0,0 +1,79 @@
+/*
+ * Renders a small Mandelbrot through the C API
+ * Built and run by tests/c_api.rs, exits with non zero status on failure.
+ */
+#include <stdatomic.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "rusty_fractals.h"
+
+#define CHECK(condition)                                                      \
+    do {                                                                      \
+        if (!(condition)) {                                                   \
+            fprintf(stderr, "%s:%d: %s failed, last error: %s\n", __FILE__, \
+                    __LINE__, #condition, rf_last_error());                  \
+            return 1;                                                         \
+        }                                                                     \
+    } while (0)
+
+/* called from calculation threads */
+static void on_progress(const RfProgress *progress, void *user_data) {
+    atomic_size_t *chunks = user_data;
+    if (progress->chunks_done <= progress->chunks_total) {
+        atomic_fetch_add(chunks, 1);
+    }
+}
+
+int main(void) {
+    CHECK(rf_api_version() == RF_API_VERSION);
+
+    int found = 0;
+    for (size_t i = 0; i < rf_formula_count(); i++) {
+        if (strcmp(rf_formula_name(i), "mandelbrot") == 0) {
+            found = 1;
+        }
+    }
+    CHECK(found);
+    CHECK(rf_formula_name(rf_formula_count()) == NULL);
+
+    CHECK(rf_render_new("nebula_x") == NULL);
+    CHECK(strlen(rf_last_error()) > 0);
+    CHECK(rf_render_run(NULL) == RF_ERROR_NULL);
+
+    RfRender *render = rf_render_new("mandelbrot");
+    CHECK(render != NULL);
+    CHECK(rf_render_set_size(render, 0, 30) == RF_ERROR_CONFIG);
+    CHECK(rf_render_set_size(render, 40, 30) == RF_OK);
+    CHECK(rf_render_set_center(render, -0.5, 0.0) == RF_OK);
+    CHECK(rf_render_set_width_re(render, 3.0) == RF_OK);
+    CHECK(rf_render_set_iterations(render, 0, 200) == RF_OK);
+    CHECK(rf_render_width(render) == 40);
+    CHECK(rf_render_height(render) == 30);
+
+    size_t len = 1;
+    CHECK(rf_render_rgb(render, &len) == NULL);
+    CHECK(len == 0);
+
+    /* blocking */
+    CHECK(rf_render_run(render) == RF_OK);
+    const uint8_t *rgb = rf_render_rgb(render, &len);
+    CHECK(rgb != NULL);
+    CHECK(len == 40 * 30 * 3);
+    const uint32_t *values = rf_render_values(render, &len);
+    CHECK(values != NULL);
+    CHECK(len == 40 * 30);
+    /* the center of the image is in the Mandelbrot set */
+    CHECK(values[15 * 40 + 20] == 0);
+
+    /* with progress */
+    atomic_size_t chunks = 0;
+    CHECK(rf_render_run_with_progress(render, on_progress, &chunks) == RF_OK);
+    CHECK(atomic_load(&chunks) > 0);
+    CHECK(rf_render_values(render, &len) != NULL);
+
+    rf_render_free(render);
+    rf_render_free(NULL);
+    printf("ok\n");
+    return 0;
+}
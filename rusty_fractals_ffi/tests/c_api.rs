@@ -0,0 +1,69 @@
+use std::env;
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/**
+ * The committed header is the one generated by build.rs
+ * Update it with: cp $OUT_DIR/rusty_fractals.h include/
+ */
+#[test]
+fn test_header() {
+    let generated = Path::new(env!("OUT_DIR")).join("rusty_fractals.h");
+    let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join("include/rusty_fractals.h");
+    assert_eq!(
+        fs::read_to_string(committed).expect("could not read header"),
+        fs::read_to_string(&generated).expect("could not read generated header"),
+        "include/rusty_fractals.h is out of date, copy {}",
+        generated.display()
+    );
+}
+
+/**
+ * Compiles tests/c/test_render.c against the cdylib and runs it
+ * Skipped if there is no C compiler.
+ */
+#[test]
+fn test_c_program() {
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    if Command::new(&cc).arg("--version").output().is_err() {
+        eprintln!("{} not found, skipping C test", cc);
+        return;
+    }
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // next to the test in target/debug/deps, or in target/debug
+    let exe = env::current_exe().expect("could not get test path");
+    let file = format!("{}rusty_fractals_ffi{}", DLL_PREFIX, DLL_SUFFIX);
+    let lib_dir = exe
+        .ancestors()
+        .skip(1)
+        .take(2)
+        .find(|dir| dir.join(&file).exists())
+        .expect("could not find library");
+    let program = lib_dir.join("test_render_c");
+
+    let status = Command::new(&cc)
+        .arg(crate_dir.join("tests/c/test_render.c"))
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg("-lrusty_fractals_ffi")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("could not run C compiler");
+    assert!(status.success());
+
+    let output = Command::new(&program)
+        .env("LD_LIBRARY_PATH", lib_dir)
+        .output()
+        .expect("could not run C program");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}